@@ -10,7 +10,6 @@ uniform float exposure;
 
 void main()
 {
-    const float gamma = 2.2;
     vec3 hdr_color = texture(hdr_buffer, texture_coord).rgb;
 
     if (enable_tone_mapping)
@@ -21,6 +20,6 @@ void main()
         hdr_color = vec3(1.0) - exp(-hdr_color * exposure);
     }
 
-    vec3 rst = pow(hdr_color, vec3(1.0 / gamma)); // Gamma correct
-    frag_color = vec4(rst, 1.0);
+    // Gamma correction is done by sRGB framebuffer
+    frag_color = vec4(hdr_color, 1.0);
 }
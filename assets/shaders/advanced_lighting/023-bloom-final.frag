@@ -11,7 +11,6 @@ uniform float exposure;
 
 void main()
 {
-    const float gamma = 2.2;
     vec3 hdr_color = texture(hdr_buffer, texture_coord).rgb;
     vec3 bloom_color = texture(bloom_blur_buffer, texture_coord).rgb;
 
@@ -26,6 +25,6 @@ void main()
         rst += bloom_color;
     }
 
-    rst = pow(rst, vec3(1.0 / gamma)); // Gamma correct
+    // Gamma correction is done by sRGB framebuffer
     frag_color = vec4(rst, 1.0);
 }
@@ -4,6 +4,8 @@ out vec4 frag_color;
 
 in vec2 texture_coord;
 
+#include "../include/attenuation.glsl"
+
 struct Light {
     vec3 position;
     vec3 color;
@@ -40,7 +42,7 @@ void main()
         vec3 specular_term = k_s * lights[i].color * pow(max(0.0, dot(normal, half_vec)), 16.0);
         // Lighting attenuation
         float light_distance = distance(lights[i].position, world_pos);
-        float attenuation = 1.0 / (light_distance * light_distance);
+        float attenuation = attenuation_inverse_square(light_distance);
 
         rst += diffuse_term * attenuation + specular_term * attenuation;
     }
@@ -4,6 +4,8 @@ out vec4 frag_color;
 
 in vec2 texture_coord;
 
+#include "../include/attenuation.glsl"
+
 struct Light {
     vec3 position;
     vec3 color;
@@ -44,10 +46,9 @@ void main()
     vec3 specular_term = k_s * light.color * pow(max(0.0, dot(normal, half_vec)), 16.0);
     // Lighting attenuation
     float light_distance = distance(light.position, frag_pos_view);
-    float attenuation = 1.0 / (light_distance * light_distance);
+    float attenuation = attenuation_inverse_square(light_distance);
 
     vec3 rst = ambient_term + diffuse_term * attenuation + specular_term * attenuation;
-    rst = pow(rst, vec3(1.0 / 2.2)); // Gamma correct
 
     frag_color = vec4(rst, 1.0);
 }
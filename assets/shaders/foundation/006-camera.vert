@@ -0,0 +1,16 @@
+#version 330 core
+
+layout (location = 0) in vec3 a_pos;
+layout (location = 1) in vec2 a_texture_coord; // texture coord from vertex attribute
+layout (location = 8) in mat4 instance_model; // model matrix of instance, takes location 8-11
+
+out vec2 texture_coord;
+
+uniform mat4 view;
+uniform mat4 projection;
+
+void main() {
+    texture_coord = a_texture_coord;
+
+    gl_Position = projection * view * instance_model * vec4(a_pos.x, a_pos.y, a_pos.z, 1.0);
+}
@@ -0,0 +1,14 @@
+// Distance attenuation of light sources.
+#pragma once
+
+// Physically correct inverse-square law
+float attenuation_inverse_square(float light_distance)
+{
+    return 1.0 / (light_distance * light_distance);
+}
+
+// Classic constant-linear-quadratic attenuation, constant term is 1.0
+float attenuation_linear_quadratic(float light_distance, float linear, float quadratic)
+{
+    return 1.0 / (1.0 + linear * light_distance + quadratic * (light_distance * light_distance));
+}
@@ -0,0 +1,22 @@
+// Per-frame camera & lights shared by all programs, matching `FrameUniforms` in Rust.
+// The block is connected to `FRAME_UNIFORMS_BINDING` automatically, members are accessed by `frame.xxx`.
+#pragma once
+
+#define MAX_FRAME_LIGHTS 8
+
+struct FrameLight {
+    vec4 position; // w: 0 for directional light (xyz is direction), 1 for point light (xyz is position)
+    vec3 color;
+
+    float attenuation_linear;
+    float attenuation_quadratic;
+};
+
+layout (std140) uniform FrameUniforms {
+    mat4 view;
+    mat4 projection;
+    vec3 camera_pos;
+    float time;
+    int light_count;
+    FrameLight lights[MAX_FRAME_LIGHTS];
+} frame;
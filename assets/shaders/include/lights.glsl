@@ -0,0 +1,29 @@
+// Light sources of Blinn-Phong shading, matching `DirectionalLight`, `PointLight` and `FlashLight` in Rust.
+#pragma once
+
+#include "attenuation.glsl"
+
+struct DirLight {
+    vec3 direction;
+    vec3 color;
+};
+
+struct PointLight {
+    vec3 position;
+    vec3 color;
+
+    float attenuation_linear;
+    float attenuation_quadratic;
+};
+
+struct SpotLight {
+    vec3 color;
+
+    vec3 position;
+    vec3 direction;
+    float cutoff;
+    float outer_cutoff;
+
+    float attenuation_linear;
+    float attenuation_quadratic;
+};
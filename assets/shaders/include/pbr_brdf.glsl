@@ -0,0 +1,44 @@
+// Cook-Torrance BRDF terms of PBR shading.
+#pragma once
+
+const float PI = 3.14159265359;
+
+// D term use GGX Model
+float DistributionTerm(vec3 N, vec3 H, float roughness)
+{
+    float a2     = roughness * roughness;
+    float NdotH  = max(dot(N, H), 0.0);
+    float NdotH2 = NdotH * NdotH;
+
+    float nom    = a2;
+    float denom  = (NdotH2 * (a2 - 1.0) + 1.0);
+    denom        = PI * denom * denom;
+    return nom / denom;
+}
+
+// F term use Schlick’s approximation
+vec3 FresnelTerm(float cosTheta, vec3 F0)
+{
+    return F0 + (1.0 - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+float GeometrySchlickGGX(float NdotV, float roughness)
+{
+    float r = (roughness + 1.0);
+    float k = (r*r) / 8.0;
+
+    float nom   = NdotV;
+    float denom = NdotV * (1.0 - k) + k;
+    return nom / denom;
+}
+
+// G term use The Smith shadowing-masking algorithm
+float GeometryTerm(vec3 N, vec3 V, vec3 L, float roughness)
+{
+    float NdotV = max(dot(N, V), 0.0);
+    float NdotL = max(dot(N, L), 0.0);
+
+    float ggx_view = GeometrySchlickGGX(NdotV, roughness);
+    float ggx_light = GeometrySchlickGGX(NdotL, roughness);
+    return ggx_view * ggx_light;
+}
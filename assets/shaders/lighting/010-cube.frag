@@ -8,37 +8,17 @@ struct Material {
     float shininess;
 }; 
 
-struct DirLight {
-    vec3 direction;
-    vec3 color;
-};  
-
-struct PointLight {
-    vec3 position;
-    vec3 color;
-
-    float attenuation_linear;
-    float attenuation_quadratic;
-};  
-#define POINT_LIGHTS_NUM 4
-
-struct SpotLight {
-    vec3 color;
+#include "../include/frame_uniforms.glsl"
+#include "../include/lights.glsl"
 
-    vec3 position;
-    vec3 direction;
-    float cutoff;
-    float outer_cutoff;
-  
-    float attenuation_linear;
-    float attenuation_quadratic;
-};
+#ifndef POINT_LIGHTS_NUM
+#define POINT_LIGHTS_NUM 4
+#endif
 
 in vec3 normal;
 in vec3 world_pos;
 in vec2 texture_coord;
 
-uniform vec3 camera_pos;
 uniform Material material;
 uniform DirLight dir_light;
 uniform PointLight point_lights[POINT_LIGHTS_NUM];
@@ -85,7 +65,7 @@ vec3 calc_point_light(PointLight light, vec3 n, vec3 view_dir) {
     vec3 light_dir = normalize(light.position - world_pos);
 
     float light_distance = distance(light.position, world_pos);
-    float attenuation = 1.0 / (1.0 + light.attenuation_linear * light_distance + light.attenuation_quadratic * (light_distance * light_distance));
+    float attenuation = attenuation_linear_quadratic(light_distance, light.attenuation_linear, light.attenuation_quadratic);
     vec3 light_intensity = light.color * attenuation;
 
     vec3 diffuse_term = blinn_phong_diffuse_term(light_dir, light_intensity, n);
@@ -98,7 +78,7 @@ vec3 calc_spot_light(SpotLight light, vec3 n, vec3 view_dir) {
     vec3 light_dir = normalize(light.position - world_pos);
 
     float light_distance = distance(light.position, world_pos);
-    float attenuation = 1.0 / (1.0 + light.attenuation_linear * light_distance + light.attenuation_quadratic * (light_distance * light_distance));
+    float attenuation = attenuation_linear_quadratic(light_distance, light.attenuation_linear, light.attenuation_quadratic);
     float theta = dot(light_dir, normalize(-light.direction)); 
     float edge_smooth = clamp((theta - light.outer_cutoff) / (light.cutoff - light.outer_cutoff), 0.0, 1.0);
     vec3 light_intensity = light.color * attenuation * edge_smooth;
@@ -111,9 +91,9 @@ vec3 calc_spot_light(SpotLight light, vec3 n, vec3 view_dir) {
 
 void main() {
     vec3 n = normalize(normal);
-    vec3 view_dir = normalize(camera_pos - world_pos);
+    vec3 view_dir = normalize(frame.camera_pos - world_pos);
 
-    vec3 rst;
+    vec3 rst = vec3(0.0);
     rst += blinn_phong_ambient_term();
     rst += calc_dir_light(dir_light, n, view_dir);
     for(int i = 0; i < POINT_LIGHTS_NUM; i++)
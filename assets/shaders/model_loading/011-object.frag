@@ -2,25 +2,28 @@
 
 out vec4 frag_color;
 
+// Colors of MTL material are used when maps are absent, shininess map scales `Ns`
 struct Material {
+    sampler2D ambient_map;
     sampler2D diffuse_map;
     sampler2D specular_map;
-    sampler2D normal_map;
+    sampler2D shininess_map;
+    sampler2D alpha_map;
+    bool has_ambient_map;
+    bool has_diffuse_map;
+    bool has_specular_map;
+    bool has_shininess_map;
+    bool has_alpha_map;
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+    vec3 emissive;
     float shininess;
+    float dissolve;
 };
 
-struct DirLight {
-    vec3 direction;
-    vec3 color;
-};  
+#include "../include/lights.glsl"
 
-struct PointLight {
-    vec3 position;
-    vec3 color;
-
-    float attenuation_linear;
-    float attenuation_quadratic;
-};  
 #define POINT_LIGHTS_NUM 4
 
 in vec3 normal;
@@ -32,8 +35,27 @@ uniform Material material;
 uniform DirLight dir_light;
 uniform PointLight point_lights[POINT_LIGHTS_NUM];
 
+vec3 ambient_color() {
+    return material.has_ambient_map ? vec3(texture(material.ambient_map, texture_coord)) : material.ambient;
+}
+
+vec3 diffuse_color() {
+    return material.has_diffuse_map ? vec3(texture(material.diffuse_map, texture_coord)) : material.diffuse;
+}
+
+vec3 specular_color() {
+    return material.has_specular_map ? vec3(texture(material.specular_map, texture_coord)) : material.specular;
+}
+
+float shininess() {
+    float p = material.shininess;
+    if (material.has_shininess_map)
+        p *= texture(material.shininess_map, texture_coord).r;
+    return max(p, 1.0);
+}
+
 vec3 blinn_phong_ambient_term() {
-    vec3 k_a = vec3(texture(material.diffuse_map, texture_coord));
+    vec3 k_a = ambient_color();
 
     vec3 ambient_intensity = vec3(0.3, 0.3, 0.3);
 
@@ -41,7 +63,7 @@ vec3 blinn_phong_ambient_term() {
 }
 
 vec3 blinn_phong_diffuse_term(vec3 light_dir, vec3 light_intensity, vec3 n) {
-    vec3 k_d = vec3(texture(material.diffuse_map, texture_coord));
+    vec3 k_d = diffuse_color();
 
     float cos_term = max(0.0, dot(n, light_dir));
 
@@ -49,9 +71,9 @@ vec3 blinn_phong_diffuse_term(vec3 light_dir, vec3 light_intensity, vec3 n) {
 }
 
 vec3 blinn_phong_specular_term(vec3 light_dir, vec3 light_intensity, vec3 n, vec3 view_dir) {
-    vec3 k_s = vec3(texture(material.specular_map, texture_coord));
+    vec3 k_s = specular_color();
 
-    float p = material.shininess;
+    float p = shininess();
     // vec3 reflect_vec = reflect(-light_dir, n);
     // float cos_term = pow(max(0.0, dot(view_dir, reflect_vec)), p); // Phong Model
     vec3 half_vec = normalize(light_dir + view_dir);
@@ -73,7 +95,7 @@ vec3 calc_point_light(PointLight light, vec3 n, vec3 view_dir) {
     vec3 light_dir = normalize(light.position - world_pos);
 
     float light_distance = distance(light.position, world_pos);
-    float attenuation = 1.0 / (1.0 + light.attenuation_linear * light_distance + light.attenuation_quadratic * (light_distance * light_distance));
+    float attenuation = attenuation_linear_quadratic(light_distance, light.attenuation_linear, light.attenuation_quadratic);
     vec3 light_intensity = light.color * attenuation;
 
     vec3 diffuse_term = blinn_phong_diffuse_term(light_dir, light_intensity, n);
@@ -86,11 +108,17 @@ void main() {
     vec3 n = normalize(normal);
     vec3 view_dir = normalize(camera_pos - world_pos);
 
-    vec3 rst;
+    vec3 rst = vec3(0.0);
     rst += blinn_phong_ambient_term();
     rst += calc_dir_light(dir_light, n, view_dir);
     for(int i = 0; i < POINT_LIGHTS_NUM; i++)
         rst += calc_point_light(point_lights[i], n, view_dir);
 
-    frag_color = vec4(rst, 1.0);
+    rst += material.emissive;
+
+    float alpha = material.dissolve;
+    if (material.has_alpha_map)
+        alpha *= texture(material.alpha_map, texture_coord).r;
+
+    frag_color = vec4(rst, alpha);
 }
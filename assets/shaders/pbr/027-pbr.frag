@@ -30,47 +30,8 @@ uniform samplerCube prefiltered_envmap;
 uniform sampler2D brdf_lut;
 const float MAX_PREFILTERED_MAP_LOD = 4.0;
 
-const float PI = 3.14159265359;
-const float gamma = 2.2;
-
-// D term use GGX Model
-float DistributionTerm(vec3 N, vec3 H, float roughness)
-{
-    float a2     = roughness * roughness;
-    float NdotH  = max(dot(N, H), 0.0);
-    float NdotH2 = NdotH * NdotH;
-
-    float nom    = a2;
-    float denom  = (NdotH2 * (a2 - 1.0) + 1.0);
-    denom        = PI * denom * denom;
-    return nom / denom;
-}
-
-// F term use Schlick’s approximation
-vec3 FresnelTerm(float cosTheta, vec3 F0)
-{
-    return F0 + (1.0 - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
-}
-
-// G term use The Smith shadowing-masking algorithm
-float GeometrySchlickGGX(float NdotV, float roughness)
-{
-    float r = (roughness + 1.0);
-    float k = (r*r) / 8.0;
-
-    float nom   = NdotV;
-    float denom = NdotV * (1.0 - k) + k;
-    return nom / denom;
-}
-float GeometryTerm(vec3 N, vec3 V, vec3 L, float roughness)
-{
-    float NdotV = max(dot(N, V), 0.0);
-    float NdotL = max(dot(N, L), 0.0);
-
-    float ggx_view = GeometrySchlickGGX(NdotV, roughness);
-    float ggx_light = GeometrySchlickGGX(NdotL, roughness);
-    return ggx_view * ggx_light;
-}
+#include "../include/attenuation.glsl"
+#include "../include/pbr_brdf.glsl"
 
 // Get tangent-normals to world-space.
 vec3 GetNormalFromMap()
@@ -102,7 +63,7 @@ void main()
     float ao = ao;
     if (enable_pbr_map) {
         N = GetNormalFromMap();
-        albedo = pow(texture(albedo_map, fs_in.texture_coord).rgb, vec3(2.2)); // convert albedo from sRGB to linear
+        albedo = texture(albedo_map, fs_in.texture_coord).rgb; // sRGB texture is converted to linear by sampling
         metallic = texture(metallic_map, fs_in.texture_coord).r;
         roughness = texture(roughness_map, fs_in.texture_coord).r;
         ao = texture(ao_map, fs_in.texture_coord).r;
@@ -121,7 +82,7 @@ void main()
         vec3 H = normalize(V + L);
 
         float distance = length(light_positions[i] - fs_in.world_pos);
-        float attenuation = 1.0 / (distance * distance);
+        float attenuation = attenuation_inverse_square(distance);
         vec3 Li = light_colors[i] * attenuation;
 
         /* Calc specular part */
@@ -167,8 +128,7 @@ void main()
 
     // Reinhard Tone Mapping
     Lo = Lo / (Lo + vec3(1.0));
-    // Gamma correct
-    Lo = pow(Lo, vec3(1.0 / gamma));
+    // Gamma correction is done by sRGB framebuffer
 
     frag_color = vec4(Lo, 1.0);
 }
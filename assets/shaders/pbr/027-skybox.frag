@@ -12,7 +12,6 @@ void main()
     vec3 env_color = textureLod(environment_map, normalize(world_pos), 1.2).rgb;
 
     env_color = env_color / (env_color + vec3(1.0)); // tone mapping
-    env_color = pow(env_color, vec3(1.0/2.2)); // gamma correction
 
     frag_color = vec4(env_color, 1.0);
 }
\ No newline at end of file
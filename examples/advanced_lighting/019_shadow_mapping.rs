@@ -8,7 +8,10 @@ use std::{ffi::CString, path::PathBuf};
 use anyhow::bail;
 use gl::types::*;
 
-use learn::{clear_color, set_clear_color, BufferBit, Camera, Model, ShaderProgram, WinitWindow};
+use learn::{
+    clear_color, set_clear_color, set_framebuffer_srgb, BufferBit, Camera, DepthStencilFormat,
+    Framebuffer, Model, RenderWindow, ShaderProgram, TextureUnit, WinitWindow,
+};
 use learn_opengl_rs as learn;
 
 use nalgebra as na;
@@ -25,8 +28,8 @@ const BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
 const CAMERA_POS: [f32; 3] = [0.0, 0.5, 2.0];
 
 /* Shaodw Map data */
-const SHADOW_MAP_WIDTH: i32 = 1024;
-const SHADOW_MAP_HEIGHT: i32 = 1024;
+const SHADOW_MAP_WIDTH: u32 = 1024;
+const SHADOW_MAP_HEIGHT: u32 = 1024;
 const SHADOW_MAP_NEAR: f32 = 1.0;
 const SHADOW_MAP_FAR: f32 = 7.5;
 const LIGHT_POS: [f32; 3] = [-2.0, 4.0, -1.0];
@@ -45,8 +48,7 @@ struct Renderer {
     cube_model: Model,
     plane_model: Model,
     object_shader: ShaderProgram,
-    shadow_map_texture: u32,
-    shadow_map_fbo: u32,
+    shadow_map_fbo: Framebuffer,
     shadow_map_shader: ShaderProgram,
     // debug_quad_vao: VertexArray,
     // debug_quad_shader: ShaderProgram,
@@ -56,6 +58,9 @@ impl Renderer {
     pub fn new() -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Configure global opengl state
         unsafe {
             // Enable Depth Test
@@ -69,60 +74,10 @@ impl Renderer {
 
         /* Shadow Map */
 
-        // Generate texture for framebuffer
-        let mut shadow_map_texture = 0;
-        unsafe {
-            gl::GenTextures(1, &mut shadow_map_texture);
-            gl::BindTexture(gl::TEXTURE_2D, shadow_map_texture);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::DEPTH_COMPONENT as GLint,
-                SHADOW_MAP_WIDTH,
-                SHADOW_MAP_HEIGHT,
-                0,
-                gl::DEPTH_COMPONENT,
-                gl::FLOAT,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_WRAP_S,
-                gl::CLAMP_TO_BORDER as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_WRAP_T,
-                gl::CLAMP_TO_BORDER as GLint,
-            );
-            let border_color: [GLfloat; 4] = [1.0, 1.0, 1.0, 1.0];
-            gl::TexParameterfv(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_BORDER_COLOR,
-                border_color.as_ptr(),
-            );
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-        }
-
-        // Create framebuffer object
-        let mut shadow_map_fbo = 0;
-        unsafe {
-            gl::GenFramebuffers(1, &mut shadow_map_fbo);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, shadow_map_fbo);
-            // Bind texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::DEPTH_ATTACHMENT,
-                gl::TEXTURE_2D,
-                shadow_map_texture,
-                0,
-            );
-            gl::DrawBuffer(gl::NONE);
-            gl::ReadBuffer(gl::NONE);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        // Create framebuffer object with only a depth texture
+        let shadow_map_fbo = Framebuffer::builder(SHADOW_MAP_WIDTH, SHADOW_MAP_HEIGHT)
+            .with_depth_texture(DepthStencilFormat::Depth24)
+            .build()?;
 
         /* Shaders */
 
@@ -185,7 +140,6 @@ impl Renderer {
             cube_model,
             plane_model,
             object_shader,
-            shadow_map_texture,
             shadow_map_fbo,
             shadow_map_shader,
             // debug_quad_vao,
@@ -195,7 +149,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -229,10 +183,7 @@ impl Renderer {
         /* Pass1 : Generate Shadow Map */
 
         // initialize sth. and bind framebuffer
-        unsafe {
-            gl::Viewport(0, 0, SHADOW_MAP_WIDTH, SHADOW_MAP_HEIGHT);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.shadow_map_fbo);
-        }
+        self.shadow_map_fbo.bind_with_viewport();
         clear_color(BufferBit::DepthBufferBit as GLenum);
 
         // Generate shadow map
@@ -252,15 +203,15 @@ impl Renderer {
         // self.debug_quad_shader.bind();
         // unsafe {
         //     gl::ActiveTexture(gl::TEXTURE0);
-        //     gl::BindTexture(gl::TEXTURE_2D, self.shadow_map_texture);
+        //     gl::BindTexture(gl::TEXTURE_2D, self.shadow_map_fbo.get_depth_texture().unwrap().id);
 
         //     gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
         // }
 
         /* Pass 2 : Draw object */
+        Framebuffer::bind_default();
         unsafe {
             gl::Viewport(0, 0, window_width as i32, window_height as i32);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
         }
         clear_color(
             (BufferBit::ColorBufferBit as GLenum | BufferBit::DepthBufferBit as GLenum)
@@ -280,9 +231,8 @@ impl Renderer {
             camera.get_pos().z,
         );
 
-        unsafe {
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.shadow_map_texture);
+        if let Some(shadow_map_texture) = self.shadow_map_fbo.get_depth_texture() {
+            shadow_map_texture.bind(TextureUnit::TEXTURE0);
         }
 
         self.render_scence(&self.object_shader)?;
@@ -344,6 +294,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(0.0, 1.0, 0.0);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new("Simple Triangle", SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
@@ -3,14 +3,14 @@
 // remove console window : https://rust-lang.github.io/rfcs/1665-windows-subsystem.html
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::{ffi::CString, path::PathBuf};
+use std::{cell::Cell, ffi::CString, path::PathBuf};
 
 use anyhow::bail;
 use gl::types::*;
 
 use learn::{
-    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera,
-    ShaderProgram, Texture, TextureType, TextureUnit, VertexArray, VertexDescription, WinitWindow,
+    clear_color, generate_tangents, set_clear_color, set_framebuffer_srgb, BufferBit, Camera, Mesh,
+    RenderWindow, ShaderProgram, Texture, TextureType, TextureUnit, Vertex, WinitWindow,
 };
 use learn_opengl_rs as learn;
 
@@ -57,17 +57,20 @@ lazy_static::lazy_static! {
 }
 
 struct Renderer {
-    wall_vertices: [[f32; 14]; 6],
-    wall_vao: VertexArray,
+    wall: Mesh,
     wall_diffuse_map: Texture,
     wall_normal_map: Texture,
     object_shader: ShaderProgram,
+    elapsed_time: Cell<f32>,
 }
 
 impl Renderer {
     pub fn new() -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Configure global opengl state
         unsafe {
             // Enable Depth Test
@@ -104,206 +107,43 @@ impl Renderer {
         )?;
         let wall_normal_map = Texture::create(
             PathBuf::from("assets/textures/brickwall_normal.jpg"),
-            Some(TextureType::BlinnDiffuse),
+            Some(TextureType::Normal),
         )?;
 
-        // Calculate TB(tangent & bitangent) vectors of Triangle 1
-        let mut tangent1: glm::Vec3;
-        let mut bitangent1: glm::Vec3;
-
-        let edge1: glm::Vec3 = WALL_POSITIONS[1] - WALL_POSITIONS[0];
-        let edge2: glm::Vec3 = WALL_POSITIONS[2] - WALL_POSITIONS[0];
-        let delta_uv1: glm::Vec2 = WALL_TEX_COORDS[1] - WALL_TEX_COORDS[0];
-        let delta_uv2: glm::Vec2 = WALL_TEX_COORDS[2] - WALL_TEX_COORDS[0];
-
-        let f: f32 = 1.0 / (delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y);
-
-        tangent1 = glm::vec3(
-            f * (delta_uv2.y * edge1.x - delta_uv1.y * edge2.x),
-            f * (delta_uv2.y * edge1.y - delta_uv1.y * edge2.y),
-            f * (delta_uv2.y * edge1.z - delta_uv1.y * edge2.z),
-        );
-        tangent1 = tangent1.normalize();
-        bitangent1 = glm::vec3(
-            f * (-delta_uv2.x * edge1.x + delta_uv1.x * edge2.x),
-            f * (-delta_uv2.x * edge1.y + delta_uv1.x * edge2.y),
-            f * (-delta_uv2.x * edge1.z + delta_uv1.x * edge2.z),
-        );
-        bitangent1 = bitangent1.normalize();
-
-        // Calculate TB(tangent & bitangent) vectors of Triangle 2
-        let mut tangent2: glm::Vec3;
-        let mut bitangent2: glm::Vec3;
-
-        let edge1: glm::Vec3 = WALL_POSITIONS[2] - WALL_POSITIONS[0];
-        let edge2: glm::Vec3 = WALL_POSITIONS[3] - WALL_POSITIONS[0];
-        let delta_uv1: glm::Vec2 = WALL_TEX_COORDS[2] - WALL_TEX_COORDS[0];
-        let delta_uv2: glm::Vec2 = WALL_TEX_COORDS[3] - WALL_TEX_COORDS[0];
-
-        let f: f32 = 1.0 / (delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y);
-
-        tangent2 = glm::vec3(
-            f * (delta_uv2.y * edge1.x - delta_uv1.y * edge2.x),
-            f * (delta_uv2.y * edge1.y - delta_uv1.y * edge2.y),
-            f * (delta_uv2.y * edge1.z - delta_uv1.y * edge2.z),
-        );
-        tangent2 = tangent2.normalize();
-        bitangent2 = glm::vec3(
-            f * (-delta_uv2.x * edge1.x + delta_uv1.x * edge2.x),
-            f * (-delta_uv2.x * edge1.y + delta_uv1.x * edge2.y),
-            f * (-delta_uv2.x * edge1.z + delta_uv1.x * edge2.z),
-        );
-        bitangent2 = bitangent2.normalize();
-
+        // Vertices of wall, whose tangents & bitangents are generated from normals & texture coords
+        let mut wall_vertices: Vec<Vertex> = WALL_POSITIONS
+            .iter()
+            .zip(WALL_TEX_COORDS.iter())
+            .map(|(position, texture_coords)| Vertex::new(*position, *WALL_NORMAL, *texture_coords))
+            .collect();
+        let mut wall_indices = vec![0, 1, 2, 0, 2, 3];
+        if !generate_tangents(&mut wall_vertices, &mut wall_indices) {
+            bail!("Failed to generate tangents of wall");
+        }
         tracing::info!(
-            "tangent1: {:?}, bitangent1: {:?}, tangent2: {:?}, bitangent2: {:?}",
-            tangent1,
-            bitangent1,
-            tangent2,
-            bitangent2
+            "tangent: {:?}, bitangent: {:?}",
+            wall_vertices[0].tangent,
+            wall_vertices[0].bitangent
         );
-
-        // Generate vertices data of wall
-        let wall_vertices: [[f32; 14]; 6] = [
-            // positions + normals + texcoords + tangent + bitangent
-            [
-                WALL_POSITIONS[0].x,
-                WALL_POSITIONS[0].y,
-                WALL_POSITIONS[0].z,
-                WALL_NORMAL.x,
-                WALL_NORMAL.y,
-                WALL_NORMAL.z,
-                WALL_TEX_COORDS[0].x,
-                WALL_TEX_COORDS[0].y,
-                tangent1.x,
-                tangent1.y,
-                tangent1.z,
-                bitangent1.x,
-                bitangent1.y,
-                bitangent1.z,
-            ],
-            [
-                WALL_POSITIONS[1].x,
-                WALL_POSITIONS[1].y,
-                WALL_POSITIONS[1].z,
-                WALL_NORMAL.x,
-                WALL_NORMAL.y,
-                WALL_NORMAL.z,
-                WALL_TEX_COORDS[1].x,
-                WALL_TEX_COORDS[1].y,
-                tangent1.x,
-                tangent1.y,
-                tangent1.z,
-                bitangent1.x,
-                bitangent1.y,
-                bitangent1.z,
-            ],
-            [
-                WALL_POSITIONS[2].x,
-                WALL_POSITIONS[2].y,
-                WALL_POSITIONS[2].z,
-                WALL_NORMAL.x,
-                WALL_NORMAL.y,
-                WALL_NORMAL.z,
-                WALL_TEX_COORDS[2].x,
-                WALL_TEX_COORDS[2].y,
-                tangent1.x,
-                tangent1.y,
-                tangent1.z,
-                bitangent1.x,
-                bitangent1.y,
-                bitangent1.z,
-            ],
-            [
-                WALL_POSITIONS[0].x,
-                WALL_POSITIONS[0].y,
-                WALL_POSITIONS[0].z,
-                WALL_NORMAL.x,
-                WALL_NORMAL.y,
-                WALL_NORMAL.z,
-                WALL_TEX_COORDS[0].x,
-                WALL_TEX_COORDS[0].y,
-                tangent2.x,
-                tangent2.y,
-                tangent2.z,
-                bitangent2.x,
-                bitangent2.y,
-                bitangent2.z,
-            ],
-            [
-                WALL_POSITIONS[2].x,
-                WALL_POSITIONS[2].y,
-                WALL_POSITIONS[2].z,
-                WALL_NORMAL.x,
-                WALL_NORMAL.y,
-                WALL_NORMAL.z,
-                WALL_TEX_COORDS[2].x,
-                WALL_TEX_COORDS[2].y,
-                tangent2.x,
-                tangent2.y,
-                tangent2.z,
-                bitangent2.x,
-                bitangent2.y,
-                bitangent2.z,
-            ],
-            [
-                WALL_POSITIONS[3].x,
-                WALL_POSITIONS[3].y,
-                WALL_POSITIONS[3].z,
-                WALL_NORMAL.x,
-                WALL_NORMAL.y,
-                WALL_NORMAL.z,
-                WALL_TEX_COORDS[3].x,
-                WALL_TEX_COORDS[3].y,
-                tangent2.x,
-                tangent2.y,
-                tangent2.z,
-                bitangent2.x,
-                bitangent2.y,
-                bitangent2.z,
-            ],
-        ];
-
-        // Generate VAO of wall
-        let wall_vao = VertexArray::new()?;
-        wall_vao.bind();
-
-        let wall_vbo = Buffer::new(BufferType::VertexBuffer)?;
-        wall_vbo.bind();
-        unsafe {
-            gl::BufferData(
-                wall_vbo.buffer_type as GLenum,
-                std::mem::size_of_val(&wall_vertices) as GLsizeiptr,
-                wall_vertices.as_ptr().cast(),
-                BufferUsage::StaticDraw as GLenum,
-            );
-        }
-
-        let mut wall_vertex_desc = VertexDescription::new();
-        wall_vertex_desc.add_attribute(gl::FLOAT, 3); // set NDC coord attribute
-        wall_vertex_desc.add_attribute(gl::FLOAT, 3); // set normal attribute
-        wall_vertex_desc.add_attribute(gl::FLOAT, 2); // set Texture coord attribute
-        wall_vertex_desc.add_attribute(gl::FLOAT, 3); // set tangent of TBN attribute
-        wall_vertex_desc.add_attribute(gl::FLOAT, 3); // set bitangent of TBN attribute
-        wall_vertex_desc.bind_to(&wall_vbo, Some(&wall_vao));
-        wall_vao.unbind();
-        wall_vbo.unbind();
+        let wall = Mesh::new(wall_vertices, wall_indices, None, None, None, Some(32.0))?;
 
         Ok(Self {
-            wall_vertices,
-            wall_vao,
+            wall,
             wall_diffuse_map,
             wall_normal_map,
             object_shader,
+            elapsed_time: Cell::new(0.0),
         })
     }
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
-        _delta_time: f32,
+        delta_time: f32,
     ) -> anyhow::Result<()> {
+        self.elapsed_time.set(self.elapsed_time.get() + delta_time);
+
         clear_color(
             (BufferBit::ColorBufferBit as GLenum | BufferBit::DepthBufferBit as GLenum)
                 as gl::types::GLbitfield,
@@ -347,9 +187,6 @@ impl Renderer {
     }
 
     pub fn render_scence(&self, shader: &ShaderProgram) -> anyhow::Result<()> {
-        lazy_static::lazy_static! {
-            static ref SYSTEM_TIME: std::time::SystemTime = std::time::SystemTime::now();
-        }
         /* Wall */
 
         // Setup shader uniform: model matrix
@@ -357,9 +194,7 @@ impl Renderer {
         let mut object_model_matrix = glm::Mat4::identity();
         object_model_matrix = glm::rotate(
             &object_model_matrix,
-            glm::radians(&glm::Vec1::new(
-                SYSTEM_TIME.elapsed().unwrap().as_secs_f32() * -20.0,
-            ))[0],
+            glm::radians(&glm::Vec1::new(self.elapsed_time.get() * -20.0))[0],
             &glm::normalize(&glm::vec3(1.0, 0.0, 1.0)),
         );
         let normal_matrix_name = CString::new("normal_matrix")?;
@@ -371,36 +206,22 @@ impl Renderer {
         shader.set_uniform_mat4fv(model_name.as_c_str(), &object_model_matrix); // Model Matrix
         shader.set_uniform_mat3fv(normal_matrix_name.as_c_str(), &object_normal_matrix); // Normal Matrix
 
-        // Setup shader uniform: shininess
         let material_uniform_name = "material";
-        shader.set_uniform_1f(
-            &CString::new(format!("{material_uniform_name}.shininess"))?,
-            32.0,
-        ); // Shininess
 
         // Setup shader uniform: diffuse map & normal map
-        let mut texture_unit = TextureUnit::TEXTURE10;
         shader.set_texture_unit(
             &CString::new(format!("{material_uniform_name}.diffuse_map"))?,
             &self.wall_diffuse_map,
-            texture_unit,
+            TextureUnit::TEXTURE10,
         );
-        texture_unit = texture_unit.increase();
         shader.set_texture_unit(
             &CString::new(format!("{material_uniform_name}.normal_map"))?,
             &self.wall_normal_map,
-            texture_unit,
+            TextureUnit::TEXTURE11,
         );
 
-        // Draw wall
-        self.wall_vao.bind();
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLES, 0, self.wall_vertices.len() as GLsizei);
-        }
-
-        // always good practice to set everything back to defaults once configured.
-        self.wall_vao.unbind();
-        Texture::active(TextureUnit::TEXTURE0);
+        // Draw wall, whose shininess is set by mesh
+        self.wall.draw(shader, material_uniform_name)?;
 
         Ok(())
     }
@@ -424,6 +245,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(CAMERA_UP[0], CAMERA_UP[1], CAMERA_UP[2]);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new(WINDOW_TITLE, SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
@@ -9,8 +9,9 @@ use anyhow::bail;
 use gl::types::*;
 
 use learn::{
-    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera, Model,
-    ShaderProgram, VertexArray, VertexDescription, WinitWindow,
+    clear_color, set_clear_color, set_framebuffer_srgb, Buffer, BufferBit, BufferType, BufferUsage,
+    Camera, ColorFormat, DepthStencilFormat, Framebuffer, Model, PrimitiveMode, RenderWindow,
+    ShaderProgram, TextureUnit, VertexArray, VertexDescription, WinitWindow,
 };
 use learn_opengl_rs as learn;
 
@@ -62,17 +63,19 @@ struct Renderer {
     cube_model: Model,
     object_shader: ShaderProgram,
 
-    color_texture: u32,
-    hdr_fbo: u32,
+    hdr_fbo: Framebuffer,
 
     screen_vao: VertexArray,
     tone_mapping_shader: ShaderProgram,
 }
 
 impl Renderer {
-    pub fn new(win: &WinitWindow) -> anyhow::Result<Self> {
+    pub fn new(win: &impl RenderWindow) -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Configure global opengl state
         unsafe {
             // Enable Depth Test
@@ -133,77 +136,16 @@ impl Renderer {
 
         /* Floating-point Framebuffer for HDR rendering */
 
-        // Create framebuffer
-        let mut hdr_fbo = 0;
-        unsafe {
-            gl::GenFramebuffers(1, &mut hdr_fbo);
-        }
-        // Create texture as color attachment
-        let mut color_texture = 0;
-        unsafe {
-            gl::GenTextures(1, &mut color_texture);
-            gl::BindTexture(gl::TEXTURE_2D, color_texture);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA16F as GLint, // use RGBA16F to restore HDR content
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-                0,
-                gl::RGBA,
-                gl::FLOAT,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-        }
-        // Create renderbuffer as depth attachment
-        let mut depth_rbo = 0;
-        unsafe {
-            gl::GenRenderbuffers(1, &mut depth_rbo);
-            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_rbo);
-            gl::RenderbufferStorage(
-                gl::RENDERBUFFER,
-                gl::DEPTH_COMPONENT,
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-            );
-            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
-        }
-        // Attach buffers to framebuffer
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, hdr_fbo);
-            // Attach color texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT0,
-                gl::TEXTURE_2D,
-                color_texture,
-                0,
-            );
-            // Attach depth rbo to framebuffer
-            gl::FramebufferRenderbuffer(
-                gl::FRAMEBUFFER,
-                gl::DEPTH_ATTACHMENT,
-                gl::RENDERBUFFER,
-                depth_rbo,
-            );
-        }
-        // Check framebuffer status
-        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
-        if status != gl::FRAMEBUFFER_COMPLETE {
-            bail!("Framebuffer is not complete!");
-        }
-        // Bind framebuffer to default
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        // Create framebuffer with RGBA16F color texture to restore HDR content
+        let (window_width, window_height) = win.get_window_size();
+        let hdr_fbo = Framebuffer::builder(window_width, window_height)
+            .with_color_texture(ColorFormat::RGBA16F, gl::LINEAR)
+            .with_depth_renderbuffer(DepthStencilFormat::Depth24)
+            .build()?;
 
         Ok(Self {
             cube_model,
             object_shader,
-            color_texture,
             hdr_fbo,
             screen_vao,
             tone_mapping_shader,
@@ -212,7 +154,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -237,9 +179,7 @@ impl Renderer {
         let projection_name = CString::new("projection")?;
 
         /* Pass 1 : Render scene */
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.hdr_fbo);
-        }
+        self.hdr_fbo.bind();
 
         clear_color(
             (BufferBit::ColorBufferBit as GLenum | BufferBit::DepthBufferBit as GLenum)
@@ -260,9 +200,7 @@ impl Renderer {
 
         self.render_scence(&self.object_shader)?;
 
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        Framebuffer::bind_default();
 
         /* Pass 2 : Draw to quad */
 
@@ -272,9 +210,8 @@ impl Renderer {
         );
 
         self.tone_mapping_shader.bind();
-        unsafe {
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.color_texture);
+        if let Some(color_texture) = self.hdr_fbo.get_color_texture(0) {
+            color_texture.bind(TextureUnit::TEXTURE0);
         }
         let tm_lock: std::sync::MutexGuard<'_, bool> = ENABLE_HDR.lock().unwrap();
         let ex_lock: std::sync::MutexGuard<'_, f32> = EXPOSURE.lock().unwrap();
@@ -286,9 +223,8 @@ impl Renderer {
             .set_uniform_1f(CString::new("exposure")?.as_c_str(), *ex_lock);
 
         self.screen_vao.bind();
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
-        }
+        self.screen_vao
+            .draw_arrays(PrimitiveMode::TriangleStrip, 0, 4);
         self.screen_vao.unbind();
 
         // Swap buffers of window
@@ -328,6 +264,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(CAMERA_UP[0], CAMERA_UP[1], CAMERA_UP[2]);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new(&ctx)?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new(WINDOW_TITLE, SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
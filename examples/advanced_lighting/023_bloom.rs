@@ -3,14 +3,16 @@
 // remove console window : https://rust-lang.github.io/rfcs/1665-windows-subsystem.html
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::{ffi::CString, path::PathBuf, sync::Mutex};
+use std::{ffi::CString, path::PathBuf, rc::Rc, sync::Mutex};
 
 use anyhow::bail;
 use gl::types::*;
 
 use learn::{
-    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera, Model,
-    ShaderProgram, VertexArray, VertexDescription, WinitWindow,
+    clear_color, set_clear_color, set_framebuffer_srgb, Buffer, BufferBit, BufferType, BufferUsage,
+    Camera, ColorFormat, DepthStencilFormat, Framebuffer, Model, PrimitiveMode, RenderWindow,
+    Scene, SceneNode, SceneObject, ShaderProgram, TextureUnit, VertexArray, VertexDescription,
+    WinitWindow,
 };
 use learn_opengl_rs as learn;
 
@@ -54,20 +56,30 @@ const LIGHT_COLOR: [[f32; 3]; 4] = [
     [0.0, 5.0, 0.0],  // right light
 ];
 
+// Translation, rotation degrees around axis (1, 0, 1) and scale of cubes
+const CUBE_TRANSFORMS: [([f32; 3], f32, [f32; 3]); 7] = [
+    ([0.0, -1.0, 0.0], 0.0, [12.5, 0.5, 12.5]), // floor
+    ([0.0, 1.5, 0.0], 0.0, [0.5, 0.5, 0.5]),
+    ([2.0, 0.0, 1.0], 0.0, [0.5, 0.5, 0.5]),
+    ([-1.0, -1.0, 2.0], 60.0, [1.0, 1.0, 1.0]),
+    ([0.0, 2.7, 4.0], 23.0, [1.25, 1.25, 1.25]),
+    ([-2.0, 1.0, -3.0], 124.0, [1.0, 1.0, 1.0]),
+    ([-3.0, 0.0, 0.0], 0.0, [0.5, 0.5, 0.5]),
+];
+
 /* Tone Mapping data */
 static ENABLE_BLOOM: Mutex<bool> = Mutex::new(true);
 static EXPOSURE: Mutex<f32> = Mutex::new(1.0);
 
 struct Renderer {
-    cube_model: Model,
+    cube_model: Rc<Model>,
+    scene: Scene,
 
-    color_textures: [u32; 2],
-    hdr_fbo: u32,
+    hdr_fbo: Framebuffer,
     object_shader: ShaderProgram,
     light_shader: ShaderProgram,
 
-    blur_fbos: [u32; 2],
-    blur_color_textures: [u32; 2],
+    blur_fbos: [Framebuffer; 2],
     blur_shader: ShaderProgram,
 
     screen_vao: VertexArray,
@@ -75,9 +87,12 @@ struct Renderer {
 }
 
 impl Renderer {
-    pub fn new(win: &WinitWindow) -> anyhow::Result<Self> {
+    pub fn new(win: &impl RenderWindow) -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Configure global opengl state
         unsafe {
             // Enable Depth Test
@@ -94,7 +109,23 @@ impl Renderer {
         /* Object Models */
 
         // cube object
-        let cube_model = Model::new(PathBuf::from("assets/models/cube_wood/cube.obj"))?;
+        let cube_model = Rc::new(Model::new(PathBuf::from(
+            "assets/models/cube_wood/cube.obj",
+        ))?);
+        // scene of cubes
+        let mut scene = Scene::new();
+        let rotation_axis = na::Unit::new_normalize(na::Vector3::new(1.0, 0.0, 1.0));
+        for (translation, rotation_degrees, scale) in CUBE_TRANSFORMS {
+            let cube_node = SceneNode::new()
+                .with_translation(translation.into())
+                .with_rotation(na::UnitQuaternion::from_axis_angle(
+                    &rotation_axis,
+                    rotation_degrees.to_radians(),
+                ))
+                .with_scale(scale.into())
+                .with_object(SceneObject::Model(cube_model.clone()));
+            scene.add_node(None, cube_node);
+        }
         // screen quad
         let screen_vao = VertexArray::new()?;
         let screen_vbo = Buffer::new(BufferType::VertexBuffer)?;
@@ -150,142 +181,31 @@ impl Renderer {
 
         /* Floating-point Framebuffer for HDR rendering */
 
-        // Create framebuffer
-        let mut hdr_fbo = 0;
-        unsafe {
-            gl::GenFramebuffers(1, &mut hdr_fbo);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, hdr_fbo);
-        }
-        // Create texture as color attachment
-        let mut color_textures: [u32; 2] = [0; 2];
-        unsafe {
-            // Create two color textures for color and brightness space result
-            gl::GenTextures(2, color_textures.as_mut_ptr());
-
-            for (i, color_texture) in color_textures.iter().enumerate() {
-                gl::BindTexture(gl::TEXTURE_2D, *color_texture);
-                gl::TexImage2D(
-                    gl::TEXTURE_2D,
-                    0,
-                    gl::RGB16F as GLint, // use RGB16F to restore HDR content
-                    win.get_window_size().0.try_into()?,
-                    win.get_window_size().1.try_into()?,
-                    0,
-                    gl::RGB,
-                    gl::FLOAT,
-                    core::ptr::null(),
-                );
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
-                // Attach color texture to framebuffer
-                gl::FramebufferTexture2D(
-                    gl::FRAMEBUFFER,
-                    gl::COLOR_ATTACHMENT0 + i as GLenum,
-                    gl::TEXTURE_2D,
-                    *color_texture,
-                    0,
-                );
-            }
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-        }
-        // Create renderbuffer as depth attachment
-        let mut depth_rbo = 0;
-        unsafe {
-            gl::GenRenderbuffers(1, &mut depth_rbo);
-            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_rbo);
-            gl::RenderbufferStorage(
-                gl::RENDERBUFFER,
-                gl::DEPTH_COMPONENT,
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-            );
-            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
-            // Attach depth rbo to framebuffer
-            gl::FramebufferRenderbuffer(
-                gl::FRAMEBUFFER,
-                gl::DEPTH_ATTACHMENT,
-                gl::RENDERBUFFER,
-                depth_rbo,
-            );
-        }
-        // Tell OpenGL which color attachments we'll use (of this framebuffer) for rendering
-        let attachments: [u32; 2] = [gl::COLOR_ATTACHMENT0, gl::COLOR_ATTACHMENT1];
-        unsafe {
-            gl::DrawBuffers(2, attachments.as_ptr());
-        }
-        // Check framebuffer status
-        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
-        if status != gl::FRAMEBUFFER_COMPLETE {
-            bail!("Framebuffer is not complete!");
-        }
-        // Bind framebuffer to default
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        // Create framebuffer with two RGB16F color textures for color and brightness space result
+        let (window_width, window_height) = win.get_window_size();
+        let hdr_fbo = Framebuffer::builder(window_width, window_height)
+            .with_color_texture(ColorFormat::RGB16F, gl::LINEAR)
+            .with_color_texture(ColorFormat::RGB16F, gl::LINEAR)
+            .with_depth_renderbuffer(DepthStencilFormat::Depth24)
+            .build()?;
 
         /* Framebuffers for Gaussian blur rendering */
-        let mut blur_fbos = [0; 2];
-        let mut blur_color_textures = [0; 2];
-        unsafe {
-            gl::GenFramebuffers(2, blur_fbos.as_mut_ptr());
-            gl::GenTextures(2, blur_color_textures.as_mut_ptr());
-
-            for i in 0..2 {
-                gl::BindFramebuffer(gl::FRAMEBUFFER, blur_fbos[i]);
-                gl::BindTexture(gl::TEXTURE_2D, blur_color_textures[i]);
-
-                gl::TexImage2D(
-                    gl::TEXTURE_2D,
-                    0,
-                    gl::RGB16F as GLint,
-                    win.get_window_size().0.try_into()?,
-                    win.get_window_size().1.try_into()?,
-                    0,
-                    gl::RGB,
-                    gl::FLOAT,
-                    core::ptr::null(),
-                );
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
-                gl::TexParameteri(
-                    gl::TEXTURE_2D,
-                    gl::TEXTURE_WRAP_S,
-                    gl::CLAMP_TO_EDGE as GLint,
-                );
-                gl::TexParameteri(
-                    gl::TEXTURE_2D,
-                    gl::TEXTURE_WRAP_T,
-                    gl::CLAMP_TO_EDGE as GLint,
-                );
-
-                // Attach color texture to framebuffer
-                gl::FramebufferTexture2D(
-                    gl::FRAMEBUFFER,
-                    gl::COLOR_ATTACHMENT0 as GLenum,
-                    gl::TEXTURE_2D,
-                    blur_color_textures[i],
-                    0,
-                );
-
-                // Check framebuffer status
-                let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
-                if status != gl::FRAMEBUFFER_COMPLETE {
-                    bail!("Gaussian Blur Framebuffer is not complete!");
-                }
-
-                gl::BindTexture(gl::TEXTURE_2D, 0);
-                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-            }
-        }
+        let blur_fbos = [
+            Framebuffer::builder(window_width, window_height)
+                .with_color_texture(ColorFormat::RGB16F, gl::LINEAR)
+                .build()?,
+            Framebuffer::builder(window_width, window_height)
+                .with_color_texture(ColorFormat::RGB16F, gl::LINEAR)
+                .build()?,
+        ];
 
         Ok(Self {
             cube_model,
-            color_textures,
+            scene,
             hdr_fbo,
             object_shader,
             light_shader,
             blur_fbos,
-            blur_color_textures,
             blur_shader,
             screen_vao,
             tone_mapping_shader,
@@ -294,7 +214,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -319,9 +239,7 @@ impl Renderer {
         let projection_name = CString::new("projection")?;
 
         /* Pass 1 : Render scene */
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.hdr_fbo);
-        }
+        self.hdr_fbo.bind();
 
         clear_color(
             (BufferBit::ColorBufferBit as GLenum | BufferBit::DepthBufferBit as GLenum)
@@ -379,30 +297,22 @@ impl Renderer {
         self.blur_shader.bind();
 
         for _ in 0..(blur_amount * 2) {
-            unsafe {
-                gl::BindFramebuffer(gl::FRAMEBUFFER, self.blur_fbos[horizontal_blur as usize]);
-            }
+            self.blur_fbos[horizontal_blur as usize].bind();
             // Prepare uniforms
             self.blur_shader.set_uniform_1i(
                 CString::new("horizontal_blur")?.as_c_str(),
                 horizontal_blur as i32,
             );
-            unsafe {
-                gl::ActiveTexture(gl::TEXTURE0);
-                gl::BindTexture(
-                    gl::TEXTURE_2D,
-                    if first_iteration {
-                        self.color_textures[1]
-                    } else {
-                        self.blur_color_textures[!horizontal_blur as usize]
-                    },
-                );
-            }
+            let source_texture = if first_iteration {
+                &self.hdr_fbo.get_color_textures()[1]
+            } else {
+                &self.blur_fbos[!horizontal_blur as usize].get_color_textures()[0]
+            };
+            source_texture.bind(TextureUnit::TEXTURE0);
             // Draw to color texture as a quad
             self.screen_vao.bind();
-            unsafe {
-                gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
-            }
+            self.screen_vao
+                .draw_arrays(PrimitiveMode::TriangleStrip, 0, 4);
             self.screen_vao.unbind();
             // Update vars
             horizontal_blur = !horizontal_blur;
@@ -412,9 +322,7 @@ impl Renderer {
         }
 
         /* Pass 4 : Draw to quad */
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        Framebuffer::bind_default();
 
         clear_color(
             (BufferBit::ColorBufferBit as GLenum | BufferBit::DepthBufferBit as GLenum)
@@ -423,25 +331,18 @@ impl Renderer {
 
         // Prepare uniforms
         self.tone_mapping_shader.bind();
-        unsafe {
-            // Set base color texture
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.color_textures[0]);
-            // Set gaussian blur texture
-            gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(
-                gl::TEXTURE_2D,
-                self.blur_color_textures[!horizontal_blur as usize],
-            );
-            let location: i32 = gl::GetUniformLocation(
-                self.tone_mapping_shader.id,
-                CString::new("bloom_blur_buffer")?
-                    .as_c_str()
-                    .as_ptr()
-                    .cast(),
-            );
-            gl::Uniform1i(location, 1);
-        }
+        // Set base color texture
+        self.tone_mapping_shader.set_texture_unit(
+            CString::new("hdr_buffer")?.as_c_str(),
+            &self.hdr_fbo.get_color_textures()[0],
+            TextureUnit::TEXTURE0,
+        );
+        // Set gaussian blur texture
+        self.tone_mapping_shader.set_texture_unit(
+            CString::new("bloom_blur_buffer")?.as_c_str(),
+            &self.blur_fbos[!horizontal_blur as usize].get_color_textures()[0],
+            TextureUnit::TEXTURE1,
+        );
         let tm_lock: std::sync::MutexGuard<'_, bool> = ENABLE_BLOOM.lock().unwrap();
         let ex_lock: std::sync::MutexGuard<'_, f32> = EXPOSURE.lock().unwrap();
         self.tone_mapping_shader
@@ -451,9 +352,8 @@ impl Renderer {
 
         // Draw final image
         self.screen_vao.bind();
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
-        }
+        self.screen_vao
+            .draw_arrays(PrimitiveMode::TriangleStrip, 0, 4);
         self.screen_vao.unbind();
 
         // Swap buffers of window
@@ -463,66 +363,8 @@ impl Renderer {
     }
 
     pub fn render_scence(&self, shader: &ShaderProgram) -> anyhow::Result<()> {
-        let model_name = CString::new("model")?;
-
         /* Draw cubes */
-        // Cube1
-        let mut object_model_matrix = na::Matrix4::identity();
-        object_model_matrix = glm::translate(&object_model_matrix, &glm::vec3(0.0, -1.0, 0.0));
-        object_model_matrix = glm::scale(&object_model_matrix, &glm::vec3(12.5, 0.5, 12.5));
-        shader.set_uniform_mat4fv(model_name.as_c_str(), &object_model_matrix);
-        self.cube_model.draw(shader, "material")?;
-        // Cube2
-        let mut object_model_matrix = na::Matrix4::identity();
-        object_model_matrix = glm::translate(&object_model_matrix, &glm::vec3(0.0, 1.5, 0.0));
-        object_model_matrix = glm::scale(&object_model_matrix, &glm::vec3(0.5, 0.5, 0.5));
-        shader.set_uniform_mat4fv(model_name.as_c_str(), &object_model_matrix);
-        self.cube_model.draw(shader, "material")?;
-        // Cube3
-        let mut object_model_matrix = na::Matrix4::identity();
-        object_model_matrix = glm::translate(&object_model_matrix, &glm::vec3(2.0, 0.0, 1.0));
-        object_model_matrix = glm::scale(&object_model_matrix, &glm::vec3(0.5, 0.5, 0.5));
-        shader.set_uniform_mat4fv(model_name.as_c_str(), &object_model_matrix);
-        self.cube_model.draw(shader, "material")?;
-        // Cube4
-        let mut object_model_matrix = na::Matrix4::identity();
-        object_model_matrix = glm::translate(&object_model_matrix, &glm::vec3(-1.0, -1.0, 2.0));
-        object_model_matrix = glm::rotate(
-            &object_model_matrix,
-            glm::radians(&glm::Vec1::new(60.0))[0],
-            &glm::vec3(1.0, 0.0, 1.0),
-        );
-        shader.set_uniform_mat4fv(model_name.as_c_str(), &object_model_matrix);
-        self.cube_model.draw(shader, "material")?;
-        // Cube5
-        let mut object_model_matrix = na::Matrix4::identity();
-        object_model_matrix = glm::translate(&object_model_matrix, &glm::vec3(0.0, 2.7, 4.0));
-        object_model_matrix = glm::rotate(
-            &object_model_matrix,
-            glm::radians(&glm::Vec1::new(23.0))[0],
-            &glm::vec3(1.0, 0.0, 1.0),
-        );
-        object_model_matrix = glm::scale(&object_model_matrix, &glm::vec3(1.25, 1.25, 1.25));
-        shader.set_uniform_mat4fv(model_name.as_c_str(), &object_model_matrix);
-        self.cube_model.draw(shader, "material")?;
-        // Cube6
-        let mut object_model_matrix = na::Matrix4::identity();
-        object_model_matrix = glm::translate(&object_model_matrix, &glm::vec3(-2.0, 1.0, -3.0));
-        object_model_matrix = glm::rotate(
-            &object_model_matrix,
-            glm::radians(&glm::Vec1::new(124.0))[0],
-            &glm::vec3(1.0, 0.0, 1.0),
-        );
-        shader.set_uniform_mat4fv(model_name.as_c_str(), &object_model_matrix);
-        self.cube_model.draw(shader, "material")?;
-        // Cube7
-        let mut object_model_matrix = na::Matrix4::identity();
-        object_model_matrix = glm::translate(&object_model_matrix, &glm::vec3(-3.0, 0.0, 0.0));
-        object_model_matrix = glm::scale(&object_model_matrix, &glm::vec3(0.5, 0.5, 0.5));
-        shader.set_uniform_mat4fv(model_name.as_c_str(), &object_model_matrix);
-        self.cube_model.draw(shader, "material")?;
-
-        Ok(())
+        self.scene.draw(shader, "material")
     }
 
     pub fn close(self) {
@@ -544,6 +386,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(CAMERA_UP[0], CAMERA_UP[1], CAMERA_UP[2]);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new(&ctx)?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new(WINDOW_TITLE, SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
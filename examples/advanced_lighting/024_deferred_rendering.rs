@@ -10,8 +10,9 @@ use gl::types::*;
 use rand::Rng;
 
 use learn::{
-    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera, Model,
-    ShaderProgram, VertexArray, VertexDescription, WinitWindow,
+    clear_color, set_clear_color, set_framebuffer_srgb, Buffer, BufferBit, BufferType, BufferUsage,
+    Camera, ColorFormat, DepthStencilFormat, Framebuffer, Model, PrimitiveMode, RenderWindow,
+    ShaderProgram, TextureUnit, VertexArray, VertexDescription, WinitWindow,
 };
 use learn_opengl_rs as learn;
 
@@ -59,10 +60,7 @@ const BACKPACK_POS: [[f32; 3]; 9] = [
 
 struct Renderer {
     backpack_model: Model,
-    g_buffer: u32,
-    g_position: u32,
-    g_normal: u32,
-    g_albedo_specular: u32,
+    g_buffer: Framebuffer,
     gbuffer_shader: ShaderProgram,
 
     screen_vao: VertexArray,
@@ -75,9 +73,12 @@ struct Renderer {
 }
 
 impl Renderer {
-    pub fn new(win: &WinitWindow) -> anyhow::Result<Self> {
+    pub fn new(win: &impl RenderWindow) -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Configure global opengl state
         unsafe {
             // Enable Depth Test
@@ -133,9 +134,9 @@ impl Renderer {
         )?;
 
         // Create shader of Lighting Pass
-        let lighting_pass_shader = ShaderProgram::create_from_source(
-            include_str!("../../assets/shaders/advanced_lighting/024-lighting-pass.vert"),
-            include_str!("../../assets/shaders/advanced_lighting/024-lighting-pass.frag"),
+        let lighting_pass_shader = ShaderProgram::create_from_file(
+            "assets/shaders/advanced_lighting/024-lighting-pass.vert",
+            "assets/shaders/advanced_lighting/024-lighting-pass.frag",
         )?;
         for i in 0..LIGHT_NUM {
             let light_name = format!("lights[{}].position", i);
@@ -162,141 +163,19 @@ impl Renderer {
 
         /* GBuffer */
 
-        // Create framebuffer
-        let mut g_buffer = 0;
-        unsafe {
-            gl::GenFramebuffers(1, &mut g_buffer);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, g_buffer);
-        }
-        // Create color texture to restore postion in GBuffer
-        let mut g_position = 0;
-        unsafe {
-            gl::GenTextures(1, &mut g_position);
-            gl::BindTexture(gl::TEXTURE_2D, g_position);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA16F as GLint, // use RGBA16F to restore position
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-                0,
-                gl::RGB,
-                gl::FLOAT,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-            // Attach color texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT0,
-                gl::TEXTURE_2D,
-                g_position,
-                0,
-            );
-        }
-        // Create color texture to restore normal in GBuffer
-        let mut g_normal = 0;
-        unsafe {
-            gl::GenTextures(1, &mut g_normal);
-            gl::BindTexture(gl::TEXTURE_2D, g_normal);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA16F as GLint, // use RGBA16F to restore normal vector
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-                0,
-                gl::RGB,
-                gl::FLOAT,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-            // Attach color texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT1,
-                gl::TEXTURE_2D,
-                g_normal,
-                0,
-            );
-        }
-        // Create color texture to restore albedo & specular in GBuffer
-        let mut g_albedo_specular = 0;
-        unsafe {
-            gl::GenTextures(1, &mut g_albedo_specular);
-            gl::BindTexture(gl::TEXTURE_2D, g_albedo_specular);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA as GLint, // use RGBA8 to restore albedo and specular
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-                0,
-                gl::RGBA,
-                gl::FLOAT,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-            // Attach color texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT2,
-                gl::TEXTURE_2D,
-                g_albedo_specular,
-                0,
-            );
-        }
-        // Tell OpenGL which color attachments we'll use (of this framebuffer) for rendering
-        let attachments: [u32; 3] = [
-            gl::COLOR_ATTACHMENT0,
-            gl::COLOR_ATTACHMENT1,
-            gl::COLOR_ATTACHMENT2,
-        ];
-        unsafe {
-            gl::DrawBuffers(3, attachments.as_ptr());
-        }
-        // Create renderbuffer as depth attachment
-        let mut depth_rbo = 0;
-        unsafe {
-            gl::GenRenderbuffers(1, &mut depth_rbo);
-            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_rbo);
-            gl::RenderbufferStorage(
-                gl::RENDERBUFFER,
-                gl::DEPTH_COMPONENT,
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-            );
-            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
-            // Attach depth rbo to framebuffer
-            gl::FramebufferRenderbuffer(
-                gl::FRAMEBUFFER,
-                gl::DEPTH_ATTACHMENT,
-                gl::RENDERBUFFER,
-                depth_rbo,
-            );
-        }
-        // Check framebuffer status
-        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
-        if status != gl::FRAMEBUFFER_COMPLETE {
-            bail!("Framebuffer is not complete!");
-        }
-        // Bind framebuffer to default
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        // Create framebuffer with position, normal and albedo & specular as color attachments
+        let (window_width, window_height) = win.get_window_size();
+        let g_buffer = Framebuffer::builder(window_width, window_height)
+            .with_color_texture(ColorFormat::RGBA16F, gl::NEAREST) // use RGBA16F to restore position
+            .with_color_texture(ColorFormat::RGBA16F, gl::NEAREST) // use RGBA16F to restore normal vector
+            .with_color_texture(ColorFormat::SRGB8Alpha8, gl::NEAREST) // use sRGB to restore albedo and specular precisely
+            // depth format must match default framebuffer's one, so that it can be blitted to default framebuffer
+            .with_depth_renderbuffer(DepthStencilFormat::Depth24Stencil8)
+            .build()?;
 
         Ok(Self {
             backpack_model,
             g_buffer,
-            g_position,
-            g_normal,
-            g_albedo_specular,
             gbuffer_shader,
             screen_vao,
             lighting_pass_shader,
@@ -309,7 +188,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -334,9 +213,7 @@ impl Renderer {
         let projection_name = CString::new("projection")?;
 
         /* Pass 1 : G-Buffer */
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.g_buffer);
-        }
+        self.g_buffer.bind();
 
         clear_color(
             (BufferBit::ColorBufferBit as GLenum | BufferBit::DepthBufferBit as GLenum)
@@ -353,9 +230,7 @@ impl Renderer {
 
         /* Pass 2 : Lighting Pass */
 
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        Framebuffer::bind_default();
 
         clear_color(
             (BufferBit::ColorBufferBit as GLenum | BufferBit::DepthBufferBit as GLenum)
@@ -369,51 +244,33 @@ impl Renderer {
             camera.get_pos().y,
             camera.get_pos().z,
         );
-        unsafe {
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.g_position);
-            gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_2D, self.g_normal);
-            let location: i32 = gl::GetUniformLocation(
-                self.lighting_pass_shader.id,
-                CString::new("g_normal")?.as_c_str().as_ptr().cast(),
-            );
-            gl::Uniform1i(location, 1);
-            gl::ActiveTexture(gl::TEXTURE2);
-            gl::BindTexture(gl::TEXTURE_2D, self.g_albedo_specular);
-            let location: i32 = gl::GetUniformLocation(
-                self.lighting_pass_shader.id,
-                CString::new("g_albedo_spec")?.as_c_str().as_ptr().cast(),
-            );
-            gl::Uniform1i(location, 2);
-        }
+        let g_textures = self.g_buffer.get_color_textures();
+        self.lighting_pass_shader.set_texture_unit(
+            CString::new("g_position")?.as_c_str(),
+            &g_textures[0],
+            TextureUnit::TEXTURE0,
+        );
+        self.lighting_pass_shader.set_texture_unit(
+            CString::new("g_normal")?.as_c_str(),
+            &g_textures[1],
+            TextureUnit::TEXTURE1,
+        );
+        self.lighting_pass_shader.set_texture_unit(
+            CString::new("g_albedo_spec")?.as_c_str(),
+            &g_textures[2],
+            TextureUnit::TEXTURE2,
+        );
 
         self.screen_vao.bind();
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
-        }
+        self.screen_vao
+            .draw_arrays(PrimitiveMode::TriangleStrip, 0, 4);
         self.screen_vao.unbind();
 
         /* Pass 3 : Draw light box with Forward Rendering */
 
         // Copy content of geometry's depth buffer to default framebuffer's depth buffer
-        unsafe {
-            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.g_buffer);
-            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0); // write to default framebuffer
-            gl::BlitFramebuffer(
-                0,
-                0,
-                window_width as i32,
-                window_height as i32,
-                0,
-                0,
-                window_width as i32,
-                window_height as i32,
-                gl::DEPTH_BUFFER_BIT,
-                gl::NEAREST,
-            ); // Blit to default framebuffer.
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        self.g_buffer
+            .blit_to(None, gl::DEPTH_BUFFER_BIT, gl::NEAREST); // Blit to default framebuffer.
 
         self.light_box_shader.bind();
         self.light_box_shader
@@ -480,6 +337,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(CAMERA_UP[0], CAMERA_UP[1], CAMERA_UP[2]);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new(&ctx)?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new(WINDOW_TITLE, SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
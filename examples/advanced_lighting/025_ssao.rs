@@ -7,11 +7,12 @@ use std::{ffi::CString, path::PathBuf, sync::Mutex};
 
 use anyhow::bail;
 use gl::types::*;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
 
 use learn::{
-    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera, Model,
-    ShaderProgram, VertexArray, VertexDescription, WinitWindow,
+    clear_color, set_clear_color, set_framebuffer_srgb, Buffer, BufferBit, BufferType, BufferUsage,
+    Camera, ColorFormat, DepthStencilFormat, Framebuffer, Model, PrimitiveMode, RenderWindow,
+    ShaderProgram, TextureUnit, VertexArray, VertexDescription, WinitWindow,
 };
 use learn_opengl_rs as learn;
 
@@ -55,20 +56,15 @@ struct Renderer {
     backpack_model: Model,
     cube_model: Model,
 
-    g_buffer: u32,
-    g_position: u32,
-    g_normal: u32,
-    g_albedo_specular: u32,
+    g_buffer: Framebuffer,
     gbuffer_shader: ShaderProgram,
 
     ssao_kernel: Vec<glm::Vec3>,
     ssao_noise_texture: u32,
-    ssao_fbo: u32,
-    ssao_color_texture: u32,
+    ssao_fbo: Framebuffer,
     ssao_shader: ShaderProgram,
 
-    ssao_denosing_fbo: u32,
-    ssao_denosing_color_texture: u32,
+    ssao_denosing_fbo: Framebuffer,
     ssao_denosing_shader: ShaderProgram,
 
     screen_vao: VertexArray,
@@ -76,9 +72,12 @@ struct Renderer {
 }
 
 impl Renderer {
-    pub fn new(win: &WinitWindow) -> anyhow::Result<Self> {
+    pub fn new(win: &impl RenderWindow) -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Configure global opengl state
         unsafe {
             // Enable Depth Test
@@ -130,156 +129,26 @@ impl Renderer {
         )?;
 
         // Create shader of Lighting Pass
-        let lighting_pass_shader = ShaderProgram::create_from_source(
-            include_str!("../../assets/shaders/advanced_lighting/025-lighting-pass.vert"),
-            include_str!("../../assets/shaders/advanced_lighting/025-lighting-pass.frag"),
+        let lighting_pass_shader = ShaderProgram::create_from_file(
+            "assets/shaders/advanced_lighting/025-lighting-pass.vert",
+            "assets/shaders/advanced_lighting/025-lighting-pass.frag",
         )?;
 
         /* GBuffer */
 
-        // Create framebuffer
-        let mut g_buffer = 0;
-        unsafe {
-            gl::GenFramebuffers(1, &mut g_buffer);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, g_buffer);
-        }
-        // Create color texture to restore postion in GBuffer
-        let mut g_position = 0;
-        unsafe {
-            gl::GenTextures(1, &mut g_position);
-            gl::BindTexture(gl::TEXTURE_2D, g_position);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA16F as GLint, // use RGBA16F to restore position
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-                0,
-                gl::RGBA,
-                gl::FLOAT,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_WRAP_S,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_WRAP_T,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-            // Attach color texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT0,
-                gl::TEXTURE_2D,
-                g_position,
-                0,
-            );
-        }
-        // Create color texture to restore normal in GBuffer
-        let mut g_normal = 0;
-        unsafe {
-            gl::GenTextures(1, &mut g_normal);
-            gl::BindTexture(gl::TEXTURE_2D, g_normal);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA16F as GLint, // use RGBA16F to restore normal vector
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-                0,
-                gl::RGB,
-                gl::FLOAT,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-            // Attach color texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT1,
-                gl::TEXTURE_2D,
-                g_normal,
-                0,
-            );
-        }
-        // Create color texture to restore albedo & specular in GBuffer
-        let mut g_albedo_specular = 0;
-        unsafe {
-            gl::GenTextures(1, &mut g_albedo_specular);
-            gl::BindTexture(gl::TEXTURE_2D, g_albedo_specular);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA as GLint, // use RGBA8 to restore albedo and specular
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-                0,
-                gl::RGBA,
-                gl::FLOAT,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-            // Attach color texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT2,
-                gl::TEXTURE_2D,
-                g_albedo_specular,
-                0,
-            );
-        }
-        // Tell OpenGL which color attachments we'll use (of this framebuffer) for rendering
-        let attachments: [u32; 3] = [
-            gl::COLOR_ATTACHMENT0,
-            gl::COLOR_ATTACHMENT1,
-            gl::COLOR_ATTACHMENT2,
-        ];
-        unsafe {
-            gl::DrawBuffers(3, attachments.as_ptr());
-        }
-        // Create renderbuffer as depth attachment
-        let mut depth_rbo = 0;
-        unsafe {
-            gl::GenRenderbuffers(1, &mut depth_rbo);
-            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_rbo);
-            gl::RenderbufferStorage(
-                gl::RENDERBUFFER,
-                gl::DEPTH_COMPONENT,
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-            );
-            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
-            // Attach depth rbo to framebuffer
-            gl::FramebufferRenderbuffer(
-                gl::FRAMEBUFFER,
-                gl::DEPTH_ATTACHMENT,
-                gl::RENDERBUFFER,
-                depth_rbo,
-            );
-        }
-        // Check framebuffer status
-        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
-        if status != gl::FRAMEBUFFER_COMPLETE {
-            bail!("Framebuffer is not complete!");
-        }
-        // Bind framebuffer to default
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        // Create framebuffer with position, normal and albedo & specular as color attachments
+        let (window_width, window_height) = win.get_window_size();
+        let g_buffer = Framebuffer::builder(window_width, window_height)
+            .with_color_texture(ColorFormat::RGBA16F, gl::NEAREST) // use RGBA16F to restore position
+            .with_color_texture(ColorFormat::RGBA16F, gl::NEAREST) // use RGBA16F to restore normal vector
+            .with_color_texture(ColorFormat::SRGB8Alpha8, gl::NEAREST) // use sRGB to restore albedo and specular precisely
+            .with_depth_renderbuffer(DepthStencilFormat::Depth24)
+            .build()?;
 
         /* SSAO data */
 
-        // Generate samples in a unit hemisphere
-        let mut rng = rand::thread_rng();
+        // Generate samples in a unit hemisphere, use fixed seed to make result reproducible
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
         let mut ssao_kernel: Vec<glm::Vec3> = Vec::with_capacity(SSAO_KERNEL_SIZE);
         for i in 0..SSAO_KERNEL_SIZE {
             let x = rng.gen_range(-1.0..=1.0);
@@ -326,103 +195,26 @@ impl Renderer {
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
 
-        // Create framebuffer for SSAO
-        let mut ssao_fbo = 0;
-        unsafe {
-            gl::GenFramebuffers(1, &mut ssao_fbo);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, ssao_fbo);
-        }
-        let mut ssao_color_texture = 0;
-        unsafe {
-            gl::GenTextures(1, &mut ssao_color_texture);
-            gl::BindTexture(gl::TEXTURE_2D, ssao_color_texture);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RED as GLint, // use RED to restore ambient occlusion
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-                0,
-                gl::RGB,
-                gl::FLOAT,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-            // Attach color texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT0,
-                gl::TEXTURE_2D,
-                ssao_color_texture,
-                0,
-            );
-        }
-        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
-        if status != gl::FRAMEBUFFER_COMPLETE {
-            bail!("Framebuffer is not complete!");
-        }
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        // Create framebuffer for SSAO, use RED to restore ambient occlusion
+        let ssao_fbo = Framebuffer::builder(window_width, window_height)
+            .with_color_texture(ColorFormat::R8, gl::NEAREST)
+            .build()?;
 
-        // Create framebuffer for SSAO Denosie
-        let mut ssao_denosing_fbo = 0;
-        unsafe {
-            gl::GenFramebuffers(1, &mut ssao_denosing_fbo);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, ssao_denosing_fbo);
-        }
-        let mut ssao_denosing_color_texture = 0;
-        unsafe {
-            gl::GenTextures(1, &mut ssao_denosing_color_texture);
-            gl::BindTexture(gl::TEXTURE_2D, ssao_denosing_color_texture);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RED as GLint, // use RED to restore ambient occlusion after denosing
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-                0,
-                gl::RGB,
-                gl::FLOAT,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-            // Attach color texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT0,
-                gl::TEXTURE_2D,
-                ssao_denosing_color_texture,
-                0,
-            );
-        }
-        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
-        if status != gl::FRAMEBUFFER_COMPLETE {
-            bail!("Framebuffer is not complete!");
-        }
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        // Create framebuffer for SSAO Denosie, use RED to restore ambient occlusion after denosing
+        let ssao_denosing_fbo = Framebuffer::builder(window_width, window_height)
+            .with_color_texture(ColorFormat::R8, gl::NEAREST)
+            .build()?;
 
         Ok(Self {
             backpack_model,
             cube_model,
             g_buffer,
-            g_position,
-            g_normal,
-            g_albedo_specular,
             gbuffer_shader,
             ssao_kernel,
             ssao_noise_texture,
             ssao_fbo,
-            ssao_color_texture,
             ssao_shader,
             ssao_denosing_fbo,
-            ssao_denosing_color_texture,
             ssao_denosing_shader,
             screen_vao,
             lighting_pass_shader,
@@ -431,7 +223,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -456,9 +248,7 @@ impl Renderer {
         let projection_name = CString::new("projection")?;
 
         /* Pass 1 : G-Buffer */
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.g_buffer);
-        }
+        self.g_buffer.bind();
 
         clear_color(
             (BufferBit::ColorBufferBit as GLenum | BufferBit::DepthBufferBit as GLenum)
@@ -475,22 +265,22 @@ impl Renderer {
 
         /* Pass 2 : SSAO */
 
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.ssao_fbo);
-        }
+        self.ssao_fbo.bind();
         clear_color(BufferBit::ColorBufferBit as gl::types::GLbitfield);
 
         self.ssao_shader.bind();
+        let g_textures = self.g_buffer.get_color_textures();
+        self.ssao_shader.set_texture_unit(
+            CString::new("g_position")?.as_c_str(),
+            &g_textures[0],
+            TextureUnit::TEXTURE0,
+        );
+        self.ssao_shader.set_texture_unit(
+            CString::new("g_normal")?.as_c_str(),
+            &g_textures[1],
+            TextureUnit::TEXTURE1,
+        );
         unsafe {
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.g_position);
-            gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_2D, self.g_normal);
-            let location: i32 = gl::GetUniformLocation(
-                self.ssao_shader.id,
-                CString::new("g_normal")?.as_c_str().as_ptr().cast(),
-            );
-            gl::Uniform1i(location, 1);
             gl::ActiveTexture(gl::TEXTURE2);
             gl::BindTexture(gl::TEXTURE_2D, self.ssao_noise_texture);
             let location: i32 = gl::GetUniformLocation(
@@ -512,35 +302,26 @@ impl Renderer {
             .set_uniform_1f(CString::new("ssao_bias")?.as_c_str(), SSAO_BIAS);
 
         self.screen_vao.bind();
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
-        }
+        self.screen_vao
+            .draw_arrays(PrimitiveMode::TriangleStrip, 0, 4);
         self.screen_vao.unbind();
 
         /* Pass 3 : SSAO Blur */
 
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.ssao_denosing_fbo);
-        }
+        self.ssao_denosing_fbo.bind();
         clear_color(BufferBit::ColorBufferBit as gl::types::GLbitfield);
 
         self.ssao_denosing_shader.bind();
-        unsafe {
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.ssao_color_texture);
-        }
+        self.ssao_fbo.get_color_textures()[0].bind(TextureUnit::TEXTURE0);
 
         self.screen_vao.bind();
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
-        }
+        self.screen_vao
+            .draw_arrays(PrimitiveMode::TriangleStrip, 0, 4);
         self.screen_vao.unbind();
 
         /* Pass 4 : Lighting Pass */
 
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        Framebuffer::bind_default();
 
         clear_color(
             (BufferBit::ColorBufferBit as GLenum | BufferBit::DepthBufferBit as GLenum)
@@ -567,36 +348,30 @@ impl Renderer {
             CString::new("enable_ssao")?.as_c_str(),
             *enable_ssao_lock as i32,
         );
-        unsafe {
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.g_position);
-            gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_2D, self.g_normal);
-            let location: i32 = gl::GetUniformLocation(
-                self.lighting_pass_shader.id,
-                CString::new("g_normal")?.as_c_str().as_ptr().cast(),
-            );
-            gl::Uniform1i(location, 1);
-            gl::ActiveTexture(gl::TEXTURE2);
-            gl::BindTexture(gl::TEXTURE_2D, self.g_albedo_specular);
-            let location: i32 = gl::GetUniformLocation(
-                self.lighting_pass_shader.id,
-                CString::new("g_albedo_spec")?.as_c_str().as_ptr().cast(),
-            );
-            gl::Uniform1i(location, 2);
-            gl::ActiveTexture(gl::TEXTURE3);
-            gl::BindTexture(gl::TEXTURE_2D, self.ssao_denosing_color_texture);
-            let location: i32 = gl::GetUniformLocation(
-                self.lighting_pass_shader.id,
-                CString::new("ssao")?.as_c_str().as_ptr().cast(),
-            );
-            gl::Uniform1i(location, 3);
-        }
+        self.lighting_pass_shader.set_texture_unit(
+            CString::new("g_position")?.as_c_str(),
+            &g_textures[0],
+            TextureUnit::TEXTURE0,
+        );
+        self.lighting_pass_shader.set_texture_unit(
+            CString::new("g_normal")?.as_c_str(),
+            &g_textures[1],
+            TextureUnit::TEXTURE1,
+        );
+        self.lighting_pass_shader.set_texture_unit(
+            CString::new("g_albedo_spec")?.as_c_str(),
+            &g_textures[2],
+            TextureUnit::TEXTURE2,
+        );
+        self.lighting_pass_shader.set_texture_unit(
+            CString::new("ssao")?.as_c_str(),
+            &self.ssao_denosing_fbo.get_color_textures()[0],
+            TextureUnit::TEXTURE3,
+        );
 
         self.screen_vao.bind();
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
-        }
+        self.screen_vao
+            .draw_arrays(PrimitiveMode::TriangleStrip, 0, 4);
         self.screen_vao.unbind();
 
         // Swap buffers of window
@@ -650,6 +425,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(CAMERA_UP[0], CAMERA_UP[1], CAMERA_UP[2]);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new(&ctx)?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new(WINDOW_TITLE, SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
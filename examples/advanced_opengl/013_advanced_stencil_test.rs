@@ -8,7 +8,10 @@ use std::{ffi::CString, path::PathBuf};
 use anyhow::bail;
 use gl::types::*;
 
-use learn::{clear_color, set_clear_color, BufferBit, Camera, Model, ShaderProgram, WinitWindow};
+use learn::{
+    clear_color, set_clear_color, set_framebuffer_srgb, BufferBit, Camera, Model, RenderWindow,
+    ShaderProgram, WinitWindow,
+};
 use learn_opengl_rs as learn;
 
 use nalgebra as na;
@@ -34,6 +37,9 @@ impl Renderer {
     pub fn new() -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Set clear color
         set_clear_color(
             BACKGROUND_COLOR[0],
@@ -80,7 +86,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -180,6 +186,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(0.0, 1.0, 0.0);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new("Simple Triangle", SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
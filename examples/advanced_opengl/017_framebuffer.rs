@@ -9,8 +9,9 @@ use anyhow::bail;
 use gl::types::*;
 
 use learn::{
-    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera, Model,
-    ShaderProgram, VertexArray, VertexDescription, WinitWindow,
+    clear_color, set_clear_color, set_framebuffer_srgb, Buffer, BufferBit, BufferType, BufferUsage,
+    Camera, ColorFormat, DepthStencilFormat, Framebuffer, Model, PrimitiveMode, RenderWindow,
+    ShaderProgram, TextureUnit, VertexArray, VertexDescription, WinitWindow,
 };
 use learn_opengl_rs as learn;
 
@@ -38,8 +39,7 @@ const SCREEN_VERTICES: [[f32; 4]; 6] = [
 ];
 
 struct Renderer {
-    framebuffer: GLuint,
-    color_texture: GLuint,
+    framebuffer: Framebuffer,
     screen_vao: VertexArray,
     cube_model: Model,
     plane_model: Model,
@@ -48,9 +48,12 @@ struct Renderer {
 }
 
 impl Renderer {
-    pub fn new(win: &WinitWindow) -> anyhow::Result<Self> {
+    pub fn new(win: &impl RenderWindow) -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Set clear color
         set_clear_color(
             BACKGROUND_COLOR[0],
@@ -100,81 +103,15 @@ impl Renderer {
 
         /* Framebuffer */
 
-        // Create framebuffer
-        let mut framebuffer = 0;
-        unsafe {
-            gl::GenFramebuffers(1, &mut framebuffer);
-        }
-
-        // Create texture as color attachment
-        let mut color_texture = 0;
-        unsafe {
-            gl::GenTextures(1, &mut color_texture);
-            gl::BindTexture(gl::TEXTURE_2D, color_texture);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGB as GLint,
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-                0,
-                gl::RGB,
-                gl::UNSIGNED_BYTE,
-                core::ptr::null(),
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-        }
-
-        // Create renderbuffer as depth and stencil attachment
-        let mut depth_stencil_rbo = 0;
-        unsafe {
-            gl::GenRenderbuffers(1, &mut depth_stencil_rbo);
-            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_stencil_rbo);
-            gl::RenderbufferStorage(
-                gl::RENDERBUFFER,
-                gl::DEPTH24_STENCIL8,
-                win.get_window_size().0.try_into()?,
-                win.get_window_size().1.try_into()?,
-            );
-            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
-        }
-
-        // Attach buffers to framebuffer
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
-            // Attach color texture to framebuffer
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT0,
-                gl::TEXTURE_2D,
-                color_texture,
-                0,
-            );
-            // Attach depth and stencil rbo to framebuffer
-            gl::FramebufferRenderbuffer(
-                gl::FRAMEBUFFER,
-                gl::DEPTH_STENCIL_ATTACHMENT,
-                gl::RENDERBUFFER,
-                depth_stencil_rbo,
-            )
-        }
-
-        // Check framebuffer status
-        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
-        if status != gl::FRAMEBUFFER_COMPLETE {
-            bail!("Framebuffer is not complete!");
-        }
-
-        // Bind framebuffer to default
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        // Create framebuffer with a texture as color attachment and a renderbuffer as depth and stencil attachment
+        let (window_width, window_height) = win.get_window_size();
+        let framebuffer = Framebuffer::builder(window_width, window_height)
+            .with_color_texture(ColorFormat::SRGB8, gl::LINEAR)
+            .with_depth_renderbuffer(DepthStencilFormat::Depth24Stencil8)
+            .build()?;
 
         Ok(Self {
             framebuffer,
-            color_texture,
             screen_vao,
             cube_model,
             plane_model,
@@ -185,7 +122,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -211,8 +148,8 @@ impl Renderer {
         /* Draw object */
 
         // Bind to offline framebuffer
+        self.framebuffer.bind();
         unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
             // Enable depth test
             gl::Enable(gl::DEPTH_TEST);
         }
@@ -236,8 +173,8 @@ impl Renderer {
         self.plane_model.draw(&self.object_shader, "material")?;
 
         // Bind to default framebuffer
+        Framebuffer::bind_default();
         unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
             // Disable depth test
             gl::Disable(gl::DEPTH_TEST);
         }
@@ -247,12 +184,10 @@ impl Renderer {
         // Draw screen
         self.screen_vao.bind();
         self.screen_shader.bind();
-        unsafe {
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.color_texture);
-
-            gl::DrawArrays(gl::TRIANGLES, 0, 6);
+        if let Some(color_texture) = self.framebuffer.get_color_texture(0) {
+            color_texture.bind(TextureUnit::TEXTURE0);
         }
+        self.screen_vao.draw_arrays(PrimitiveMode::Triangles, 0, 6);
 
         // Swap buffers of window
         win.swap_buffers()?;
@@ -279,6 +214,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(0.0, 1.0, 0.0);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new(&ctx)?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new("Simple Triangle", SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
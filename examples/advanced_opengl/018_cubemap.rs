@@ -3,15 +3,18 @@
 // remove console window : https://rust-lang.github.io/rfcs/1665-windows-subsystem.html
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::{ffi::CString, path::PathBuf};
+use std::{
+    ffi::CString,
+    path::{Path, PathBuf},
+};
 
 use anyhow::bail;
 use gl::types::*;
 
-use image::GenericImageView;
 use learn::{
-    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera, Model,
-    ShaderProgram, VertexArray, VertexDescription, WinitWindow,
+    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera, MipmapFilter,
+    Model, PrimitiveMode, RenderWindow, ShaderProgram, Texture, TextureOptions, TextureUnit,
+    VertexArray, VertexDescription, WinitWindow, WrapMode, SKYBOX_FACE_NAMES,
 };
 use learn_opengl_rs as learn;
 
@@ -72,7 +75,7 @@ struct Renderer {
     object_shader: ShaderProgram,
     skybox_vao: VertexArray,
     skybox_shader: ShaderProgram,
-    skybox_cubemap: GLuint,
+    skybox_cubemap: Texture,
 }
 
 impl Renderer {
@@ -126,7 +129,14 @@ impl Renderer {
         skybox_shader.set_uniform_1i(CString::new("skybox")?.as_c_str(), 0);
 
         // Load cubemap of skybox
-        let skybox_cubemap = Self::load_skybox_texture()?;
+        let skybox_cubemap = Texture::create_cubemap_from_dir(
+            Path::new("assets/textures/skybox"),
+            &SKYBOX_FACE_NAMES,
+            "jpg",
+            &TextureOptions::default()
+                .with_wrap(WrapMode::ClampToEdge)
+                .with_mipmap_filter(MipmapFilter::None),
+        )?;
 
         Ok(Self {
             object_model,
@@ -139,7 +149,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -192,9 +202,7 @@ impl Renderer {
             camera.get_pos().z,
         );
 
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.skybox_cubemap);
-        }
+        self.skybox_cubemap.bind(TextureUnit::TEXTURE0);
 
         self.object_model.draw(&self.object_shader, "material")?;
 
@@ -214,12 +222,10 @@ impl Renderer {
 
         unsafe {
             gl::DepthFunc(gl::LEQUAL);
-
-            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.skybox_cubemap);
-            gl::DrawArrays(gl::TRIANGLES, 0, 36);
-
-            gl::DepthFunc(gl::LESS);
         }
+        self.skybox_cubemap.bind(TextureUnit::TEXTURE0);
+        self.skybox_vao.draw_arrays(PrimitiveMode::Triangles, 0, 36);
+        unsafe { gl::DepthFunc(gl::LESS) };
 
         self.skybox_vao.unbind();
 
@@ -232,91 +238,6 @@ impl Renderer {
     pub fn close(self) {
         self.object_shader.close();
     }
-
-    pub fn load_skybox_texture() -> anyhow::Result<GLuint> {
-        let mut cubemap: u32 = 0;
-        unsafe {
-            gl::GenTextures(1, &mut cubemap);
-            gl::BindTexture(gl::TEXTURE_CUBE_MAP, cubemap);
-        }
-
-        // Load cubemap textures
-        let faces = [
-            "assets/textures/skybox/right.jpg",
-            "assets/textures/skybox/left.jpg",
-            "assets/textures/skybox/top.jpg",
-            "assets/textures/skybox/bottom.jpg",
-            "assets/textures/skybox/front.jpg",
-            "assets/textures/skybox/back.jpg",
-        ];
-
-        for (i, face) in faces.iter().enumerate() {
-            let img = image::open(face).unwrap();
-
-            let (width, height) = img.dimensions();
-            let img_format: GLenum;
-            let img_type: GLenum;
-            match img.color() {
-                image::ColorType::Rgb8 => {
-                    img_format = gl::RGB;
-                    img_type = gl::UNSIGNED_BYTE;
-                }
-                image::ColorType::Rgba8 => {
-                    img_format = gl::RGBA;
-                    img_type = gl::UNSIGNED_BYTE;
-                }
-                _ => {
-                    bail!("Unsupported image color type: {:?}", img.color())
-                }
-            }
-
-            let pixels = img.into_bytes();
-            unsafe {
-                gl::TexImage2D(
-                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
-                    0,
-                    img_format as GLint,
-                    width.try_into()?,
-                    height.try_into()?,
-                    0,
-                    img_format,
-                    img_type,
-                    pixels.as_ptr().cast(),
-                );
-            }
-
-            // Set Texture wrapping & filtering
-            unsafe {
-                gl::TexParameteri(
-                    gl::TEXTURE_CUBE_MAP,
-                    gl::TEXTURE_MIN_FILTER,
-                    gl::LINEAR as GLint,
-                );
-                gl::TexParameteri(
-                    gl::TEXTURE_CUBE_MAP,
-                    gl::TEXTURE_MAG_FILTER,
-                    gl::LINEAR as GLint,
-                );
-                gl::TexParameteri(
-                    gl::TEXTURE_CUBE_MAP,
-                    gl::TEXTURE_WRAP_S,
-                    gl::CLAMP_TO_EDGE as GLint,
-                );
-                gl::TexParameteri(
-                    gl::TEXTURE_CUBE_MAP,
-                    gl::TEXTURE_WRAP_T,
-                    gl::CLAMP_TO_EDGE as GLint,
-                );
-                gl::TexParameteri(
-                    gl::TEXTURE_CUBE_MAP,
-                    gl::TEXTURE_WRAP_R,
-                    gl::CLAMP_TO_EDGE as GLint,
-                );
-            }
-        }
-
-        Ok(cubemap)
-    }
 }
 
 #[allow(unreachable_code)]
@@ -333,6 +254,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(0.0, 1.0, 0.0);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new("Cubemap", SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
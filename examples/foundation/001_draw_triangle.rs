@@ -15,6 +15,10 @@ use tracing::debug;
 
 use learn_opengl_rs as learn;
 
+/* Vertex data */
+type Vertex = [f32; 3]; // x, y, z in Normalized Device Context (NDC) coordinates
+const VERTICES: [Vertex; 3] = [[-0.5, -0.5, 0.0], [0.5, -0.5, 0.0], [0.0, 0.5, 0.0]];
+
 fn check_shader_compile(shader_obj: u32) {
     let mut is_success = gl::FALSE as GLint;
     unsafe { gl::GetShaderiv(shader_obj, gl::COMPILE_STATUS, &mut is_success) }
@@ -73,129 +77,174 @@ fn check_shader_link(shader_program: u32) {
     }
 }
 
-fn main() -> anyhow::Result<()> {
-    let subscriber = tracing_subscriber::FmtSubscriber::builder()
-        .with_max_level(tracing::Level::TRACE)
-        .finish();
-    tracing::subscriber::set_global_default(subscriber).expect("Failed to set default subscriber");
+struct Renderer {
+    vao: GLuint,
+    shader_program: GLuint,
+}
 
-    /* Window */
-    let (mut win, mut event_loop) =
-        learn::GlfwWindow::new("Simple Triangle", 800, 600, glfw::WindowMode::Windowed)?;
+impl Renderer {
+    pub fn new() -> anyhow::Result<Self> {
+        /* Vertex Array Object */
+        // Generate VAO
+        let mut vao = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+        }
+        assert_ne!(vao, 0);
+        // Bind VAO
+        unsafe { gl::BindVertexArray(vao) }
 
-    /* Vertex data */
-    type Vertex = [f32; 3]; // x, y, z in Normalized Device Context (NDC) coordinates
-    const VERTICES: [Vertex; 3] = [[-0.5, -0.5, 0.0], [0.5, -0.5, 0.0], [0.0, 0.5, 0.0]];
+        /* Vertex Buffer Object */
+        // Generate a buffer
+        let mut vbo = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut vbo);
+        }
+        assert_ne!(vbo, 0);
+        unsafe {
+            // Bind buffer as ARRAY_BUFFER(VBO)
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            // Set buffer data
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                core::mem::size_of_val(&VERTICES) as isize,
+                VERTICES.as_ptr().cast(),
+                gl::STATIC_DRAW,
+            );
+        }
 
-    /* Vertex Array Object */
-    // Generate VAO
-    let mut vao = 0;
-    unsafe {
-        gl::GenVertexArrays(1, &mut vao);
-    }
-    assert_ne!(vao, 0);
-    // Bind VAO
-    unsafe { gl::BindVertexArray(vao) }
-
-    /* Vertex Buffer Object */
-    // Generate a buffer
-    let mut vbo = 0;
-    unsafe {
-        gl::GenBuffers(1, &mut vbo);
-    }
-    assert_ne!(vbo, 0);
-    unsafe {
-        // Bind buffer as ARRAY_BUFFER(VBO)
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-        // Set buffer data
-        gl::BufferData(
-            gl::ARRAY_BUFFER,
-            core::mem::size_of_val(&VERTICES) as isize,
-            VERTICES.as_ptr().cast(),
-            gl::STATIC_DRAW,
-        );
-    }
+        /* Vertex Attribute */
+        unsafe {
+            gl::VertexAttribPointer(
+                // attribute index 0 is the target
+                0,
+                // attribute is : 3 * float
+                3,
+                gl::FLOAT,
+                // coordinate already normalized
+                gl::FALSE,
+                // TODO: handle overflow
+                core::mem::size_of::<Vertex>().try_into()?,
+                // We have to convert the pointer location using usize values and then cast to a const pointer
+                // once we have our usize. We do not want to make a null pointer and then offset it with the `offset`
+                // method. That's gonna generate an out of bounds pointer, which is UB. We could try to remember to use the
+                // `wrapping_offset` method, or we could just do all the math in usize and then cast at the end.
+                // I prefer the latter option.
+                0 as _,
+            );
+            gl::EnableVertexAttribArray(0);
+        }
 
-    /* Vertex Attribute */
-    unsafe {
-        gl::VertexAttribPointer(
-            // attribute index 0 is the target
-            0,
-            // attribute is : 3 * float
-            3,
-            gl::FLOAT,
-            // coordinate already normalized
-            gl::FALSE,
-            // TODO: handle overflow
-            core::mem::size_of::<Vertex>().try_into()?,
-            // We have to convert the pointer location using usize values and then cast to a const pointer
-            // once we have our usize. We do not want to make a null pointer and then offset it with the `offset`
-            // method. That's gonna generate an out of bounds pointer, which is UB. We could try to remember to use the
-            // `wrapping_offset` method, or we could just do all the math in usize and then cast at the end.
-            // I prefer the latter option.
-            0 as _,
-        );
-        gl::EnableVertexAttribArray(0);
-    }
+        /* Shader */
+        const VERTEX_SHADER: &str = include_str!("../../assets/shaders/foundation/001-solid.vert");
+        const FRAGMENT_SHADER: &str =
+            include_str!("../../assets/shaders/foundation/001-solid.frag");
 
-    /* Shader */
-    const VERTEX_SHADER: &str = include_str!("../../assets/shaders/foundation/001-solid.vert");
-    const FRAGMENT_SHADER: &str = include_str!("../../assets/shaders/foundation/001-solid.frag");
-
-    // Create vertex & fragment shader
-    let vertex_shader = unsafe { gl::CreateShader(gl::VERTEX_SHADER) };
-    assert_ne!(vertex_shader, 0);
-    unsafe {
-        gl::ShaderSource(
-            vertex_shader,
-            1,
-            &(VERTEX_SHADER.as_bytes().as_ptr().cast()),
-            &(VERTEX_SHADER.len().try_into()?),
-        );
-    }
-    let fragment_shader = unsafe { gl::CreateShader(gl::FRAGMENT_SHADER) };
-    assert_ne!(fragment_shader, 0);
-    unsafe {
-        gl::ShaderSource(
-            fragment_shader,
-            1,
-            &(FRAGMENT_SHADER.as_bytes().as_ptr().cast()),
-            &(FRAGMENT_SHADER.len().try_into()?),
-        );
+        // Create vertex & fragment shader
+        let vertex_shader = unsafe { gl::CreateShader(gl::VERTEX_SHADER) };
+        assert_ne!(vertex_shader, 0);
+        unsafe {
+            gl::ShaderSource(
+                vertex_shader,
+                1,
+                &(VERTEX_SHADER.as_bytes().as_ptr().cast()),
+                &(VERTEX_SHADER.len().try_into()?),
+            );
+        }
+        let fragment_shader = unsafe { gl::CreateShader(gl::FRAGMENT_SHADER) };
+        assert_ne!(fragment_shader, 0);
+        unsafe {
+            gl::ShaderSource(
+                fragment_shader,
+                1,
+                &(FRAGMENT_SHADER.as_bytes().as_ptr().cast()),
+                &(FRAGMENT_SHADER.len().try_into()?),
+            );
+        }
+
+        unsafe {
+            // Compile vertex & fragment shader
+            gl::CompileShader(vertex_shader);
+            gl::CompileShader(fragment_shader);
+        }
+        // Check shader object compile result
+        check_shader_compile(vertex_shader);
+        check_shader_compile(fragment_shader);
+
+        /* Shader Program */
+        // Create/Attach/Link shader program
+        let shader_program = unsafe { gl::CreateProgram() };
+        unsafe {
+            gl::AttachShader(shader_program, vertex_shader);
+            gl::AttachShader(shader_program, fragment_shader);
+            gl::LinkProgram(shader_program);
+        }
+        // Check shader program link result
+        check_shader_link(shader_program);
+
+        unsafe {
+            // Delete shader object after link
+            gl::DeleteShader(vertex_shader);
+            gl::DeleteShader(fragment_shader);
+
+            // Set shader program
+            gl::UseProgram(shader_program);
+        }
+
+        /* Extra Settings */
+        // Specify clear color
+        unsafe { gl::ClearColor(0.2, 0.3, 0.3, 1.0) }
+
+        Ok(Self {
+            vao,
+            shader_program,
+        })
     }
 
-    unsafe {
-        // Compile vertex & fragment shader
-        gl::CompileShader(vertex_shader);
-        gl::CompileShader(fragment_shader);
+    pub fn redraw(&self) -> anyhow::Result<()> {
+        /* On Update (Drawing) */
+        unsafe {
+            // Clear bits
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+
+            // Bind shader program
+            gl::UseProgram(self.shader_program);
+
+            // Bind VAO
+            gl::BindVertexArray(self.vao);
+
+            // Draw call
+            gl::DrawArrays(gl::TRIANGLES, 0, VERTICES.len().try_into()?);
+        }
+
+        Ok(())
     }
-    // Check shader object compile result
-    check_shader_compile(vertex_shader);
-    check_shader_compile(fragment_shader);
-
-    /* Shader Program */
-    // Create/Attach/Link shader program
-    let shader_program = unsafe { gl::CreateProgram() };
-    unsafe {
-        gl::AttachShader(shader_program, vertex_shader);
-        gl::AttachShader(shader_program, fragment_shader);
-        gl::LinkProgram(shader_program);
+
+    pub fn close(self) {
+        unsafe { gl::DeleteProgram(self.shader_program) }
     }
-    // Check shader program link result
-    check_shader_link(shader_program);
+}
 
-    unsafe {
-        // Delete shader object after link
-        gl::DeleteShader(vertex_shader);
-        gl::DeleteShader(fragment_shader);
+fn main() -> anyhow::Result<()> {
+    let subscriber = tracing_subscriber::FmtSubscriber::builder()
+        .with_max_level(tracing::Level::TRACE)
+        .finish();
+    tracing::subscriber::set_global_default(subscriber).expect("Failed to set default subscriber");
 
-        // Set shader program
-        gl::UseProgram(shader_program);
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |_delta_time| renderer.redraw());
     }
 
-    /* Extra Settings */
-    // Specify clear color
-    unsafe { gl::ClearColor(0.2, 0.3, 0.3, 1.0) }
+    /* Window */
+    let (mut win, mut event_loop) =
+        learn::GlfwWindow::new("Simple Triangle", 800, 600, glfw::WindowMode::Windowed)?;
+
+    /* Renderer */
+    let renderer = Renderer::new()?;
 
     /* Main Loop */
     'main_loop: loop {
@@ -209,24 +258,12 @@ fn main() -> anyhow::Result<()> {
         }
 
         /* On Update (Drawing) */
-        unsafe {
-            // Clear bits
-            gl::Clear(gl::COLOR_BUFFER_BIT);
-
-            // Bind shader program
-            gl::UseProgram(shader_program);
-
-            // Bind VAO
-            gl::BindVertexArray(vao);
-
-            // Draw call
-            gl::DrawArrays(gl::TRIANGLES, 0, VERTICES.len().try_into()?);
-        }
+        renderer.redraw()?;
 
         win.swap_buffers();
     }
 
-    unsafe { gl::DeleteProgram(shader_program) }
+    renderer.close();
     win.close();
 
     Ok(())
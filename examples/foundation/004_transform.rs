@@ -6,8 +6,8 @@
 use std::{ffi::CString, path::PathBuf};
 
 use learn::{
-    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, ShaderProgram,
-    Texture, TextureUnit, VertexArray, VertexDescription,
+    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, IndexType,
+    PrimitiveMode, ShaderProgram, Texture, TextureUnit, VertexArray, VertexDescription,
 };
 use learn_opengl_rs as learn;
 use nalgebra as na;
@@ -15,12 +15,150 @@ use nalgebra as na;
 const SCREEN_WIDTH: u32 = 800;
 const SCREEN_HEIGHT: u32 = 600;
 
+/* Vertex data */
+type Vertex = [f32; 3 + 2]; // NDC coords(3) + texture coords(3)
+type TriIndexes = [u32; 3]; // vertex indexes for a triangle primitive
+const VERTICES: [Vertex; 4] = [
+    [0.5, 0.5, 0.0, 1.0, 1.0],
+    [0.5, -0.5, 0.0, 1.0, 0.0],
+    [-0.5, -0.5, 0.0, 0.0, 0.0],
+    [-0.5, 0.5, 0.0, 0.0, 1.0],
+];
+const INDICES: [TriIndexes; 2] = [[1, 2, 3], [0, 1, 3]];
+
+struct Renderer {
+    vao: VertexArray,
+    ibo: Buffer,
+    shader_program: ShaderProgram,
+}
+
+impl Renderer {
+    pub fn new() -> anyhow::Result<Self> {
+        /* Vertex Array Object */
+        let vao = VertexArray::new()?;
+
+        /* Vertex Buffer Object */
+        let vbo = Buffer::new(BufferType::VertexBuffer)?;
+        vbo.set_buffer_data(VERTICES.as_slice(), BufferUsage::StaticDraw);
+
+        /* Vertex Attribute description */
+        let mut vertex_desc = VertexDescription::new();
+        vertex_desc.add_attribute(gl::FLOAT, 3); // push NDC coords
+        vertex_desc.add_attribute(gl::FLOAT, 2); // push texture coords
+        vertex_desc.bind_to(&vbo, Some(&vao));
+
+        /* Index Buffer Object */
+        let ibo = Buffer::new(BufferType::IndexBuffer)?;
+        ibo.set_buffer_data(INDICES.as_slice(), BufferUsage::StaticDraw);
+
+        /* Shader */
+        let shader_program = ShaderProgram::create_from_source(
+            include_str!("../../assets/shaders/foundation/004-transform.vert"),
+            include_str!("../../assets/shaders/foundation/004-transform.frag"),
+        )?;
+
+        /* Transform Matrixes */
+        shader_program.bind();
+        // Model Matrix
+        let model_matrix = na::Rotation3::from_axis_angle(
+            &na::Vector3::x_axis(),
+            -std::f32::consts::PI * (55.0 / 180.0),
+        )
+        .to_homogeneous();
+        let model_loc = unsafe {
+            gl::GetUniformLocation(
+                shader_program.id,
+                CString::new("model")?.as_c_str().as_ptr(),
+            )
+        };
+        unsafe { gl::UniformMatrix4fv(model_loc, 1, gl::FALSE, model_matrix.as_ptr()) };
+
+        // View Matrix
+        let view_matrix = na::Translation3::new(0.0, 0.0, -3.0).to_homogeneous();
+        let view_loc = unsafe {
+            gl::GetUniformLocation(shader_program.id, CString::new("view")?.as_ptr().cast())
+        };
+        unsafe { gl::UniformMatrix4fv(view_loc, 1, gl::FALSE, view_matrix.as_ptr()) };
+
+        // Projection Matrix
+        let projection_matrix = na::Perspective3::new(
+            (SCREEN_WIDTH as f32) / (SCREEN_HEIGHT as f32),
+            std::f32::consts::FRAC_PI_4,
+            0.1,
+            100.0,
+        )
+        .to_homogeneous(); // Perspective projection
+        let projection_loc = unsafe {
+            gl::GetUniformLocation(
+                shader_program.id,
+                CString::new("projection")?.as_ptr().cast(),
+            )
+        };
+        unsafe { gl::UniformMatrix4fv(projection_loc, 1, gl::FALSE, projection_matrix.as_ptr()) };
+
+        /* Texture */
+        let texture_container =
+            Texture::create(PathBuf::from("assets/textures/container.jpg"), None)?;
+        let texture_face = Texture::create(PathBuf::from("assets/textures/awesomeface.png"), None)?;
+        shader_program.set_texture_unit(
+            &CString::new("t_container")?,
+            &texture_container,
+            TextureUnit::TEXTURE0,
+        );
+        shader_program.set_texture_unit(
+            &CString::new("t_face")?,
+            &texture_face,
+            TextureUnit::TEXTURE1,
+        );
+
+        /* Extra Settings */
+        set_clear_color(0.2, 0.3, 0.3, 1.0);
+
+        Ok(Self {
+            vao,
+            ibo,
+            shader_program,
+        })
+    }
+
+    pub fn redraw(&self) -> anyhow::Result<()> {
+        clear_color(BufferBit::ColorBufferBit as gl::types::GLbitfield);
+
+        self.shader_program.bind();
+
+        self.vao.bind();
+
+        self.ibo.bind();
+
+        self.vao.draw_elements(
+            PrimitiveMode::Triangles,
+            INDICES.len() * 3,
+            IndexType::UnsignedInt,
+            0,
+        );
+
+        Ok(())
+    }
+
+    pub fn close(self) {
+        self.shader_program.close();
+    }
+}
+
 fn main() -> anyhow::Result<()> {
     let subscriber = tracing_subscriber::FmtSubscriber::builder()
         .with_max_level(tracing::Level::TRACE)
         .finish();
     tracing::subscriber::set_global_default(subscriber)?;
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |_delta_time| renderer.redraw());
+    }
+
     /* Window */
     let (mut win, mut event_loop) = learn::GlfwWindow::new(
         "Transform Texture",
@@ -29,94 +167,8 @@ fn main() -> anyhow::Result<()> {
         glfw::WindowMode::Windowed,
     )?;
 
-    /* Vertex data */
-    type Vertex = [f32; 3 + 2]; // NDC coords(3) + texture coords(3)
-    type TriIndexes = [u32; 3]; // vertex indexes for a triangle primitive
-    const VERTICES: [Vertex; 4] = [
-        [0.5, 0.5, 0.0, 1.0, 1.0],
-        [0.5, -0.5, 0.0, 1.0, 0.0],
-        [-0.5, -0.5, 0.0, 0.0, 0.0],
-        [-0.5, 0.5, 0.0, 0.0, 1.0],
-    ];
-    const INDICES: [TriIndexes; 2] = [[1, 2, 3], [0, 1, 3]];
-
-    /* Vertex Array Object */
-    let vao = VertexArray::new()?;
-
-    /* Vertex Buffer Object */
-    let vbo = Buffer::new(BufferType::VertexBuffer)?;
-    vbo.set_buffer_data(VERTICES.as_slice(), BufferUsage::StaticDraw);
-
-    /* Vertex Attribute description */
-    let mut vertex_desc = VertexDescription::new();
-    vertex_desc.add_attribute(gl::FLOAT, 3); // push NDC coords
-    vertex_desc.add_attribute(gl::FLOAT, 2); // push texture coords
-    vertex_desc.bind_to(&vbo, Some(&vao));
-
-    /* Index Buffer Object */
-    let ibo = Buffer::new(BufferType::IndexBuffer)?;
-    ibo.set_buffer_data(INDICES.as_slice(), BufferUsage::StaticDraw);
-
-    /* Shader */
-    let shader_program = ShaderProgram::create_from_source(
-        include_str!("../../assets/shaders/foundation/004-transform.vert"),
-        include_str!("../../assets/shaders/foundation/004-transform.frag"),
-    )?;
-
-    /* Transform Matrixes */
-    shader_program.bind();
-    // Model Matrix
-    let model_matrix = na::Rotation3::from_axis_angle(
-        &na::Vector3::x_axis(),
-        -std::f32::consts::PI * (55.0 / 180.0),
-    )
-    .to_homogeneous();
-    let model_loc = unsafe {
-        gl::GetUniformLocation(
-            shader_program.id,
-            CString::new("model")?.as_c_str().as_ptr(),
-        )
-    };
-    unsafe { gl::UniformMatrix4fv(model_loc, 1, gl::FALSE, model_matrix.as_ptr()) };
-
-    // View Matrix
-    let view_matrix = na::Translation3::new(0.0, 0.0, -3.0).to_homogeneous();
-    let view_loc =
-        unsafe { gl::GetUniformLocation(shader_program.id, CString::new("view")?.as_ptr().cast()) };
-    unsafe { gl::UniformMatrix4fv(view_loc, 1, gl::FALSE, view_matrix.as_ptr()) };
-
-    // Projection Matrix
-    let projection_matrix = na::Perspective3::new(
-        (SCREEN_WIDTH as f32) / (SCREEN_HEIGHT as f32),
-        std::f32::consts::FRAC_PI_4,
-        0.1,
-        100.0,
-    )
-    .to_homogeneous(); // Perspective projection
-    let projection_loc = unsafe {
-        gl::GetUniformLocation(
-            shader_program.id,
-            CString::new("projection")?.as_ptr().cast(),
-        )
-    };
-    unsafe { gl::UniformMatrix4fv(projection_loc, 1, gl::FALSE, projection_matrix.as_ptr()) };
-
-    /* Texture */
-    let texture_container = Texture::create(PathBuf::from("assets/textures/container.jpg"), None)?;
-    let texture_face = Texture::create(PathBuf::from("assets/textures/awesomeface.png"), None)?;
-    shader_program.set_texture_unit(
-        &CString::new("t_container")?,
-        &texture_container,
-        TextureUnit::TEXTURE0,
-    );
-    shader_program.set_texture_unit(
-        &CString::new("t_face")?,
-        &texture_face,
-        TextureUnit::TEXTURE1,
-    );
-
-    /* Extra Settings */
-    set_clear_color(0.2, 0.3, 0.3, 1.0);
+    /* Renderer */
+    let renderer = Renderer::new()?;
 
     /* Main Loop */
     'main_loop: loop {
@@ -130,28 +182,13 @@ fn main() -> anyhow::Result<()> {
         }
 
         /* On Update (Drawing) */
-        clear_color(BufferBit::ColorBufferBit as gl::types::GLbitfield);
-
-        shader_program.bind();
-
-        vao.bind();
-
-        ibo.bind();
-
-        unsafe {
-            gl::DrawElements(
-                gl::TRIANGLES,
-                INDICES.len() as i32 * 3,
-                gl::UNSIGNED_INT,
-                std::ptr::null(),
-            );
-        }
+        renderer.redraw()?;
 
         // Swap buffers of window
         win.swap_buffers();
     }
 
-    shader_program.close();
+    renderer.close();
     win.close();
 
     Ok(())
@@ -13,7 +13,8 @@ use winit::event::Event;
 
 use learn::{
     clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera,
-    ShaderProgram, Texture, TextureUnit, VertexArray, VertexDescription, WinitWindow,
+    InstanceBuffer, ModelInstance, PrimitiveMode, RenderWindow, ShaderProgram, Texture,
+    TextureUnit, VertexArray, VertexDescription, WinitWindow, MESH_INSTANCE_LOCATION,
 };
 use learn_opengl_rs as learn;
 
@@ -83,6 +84,7 @@ const CUBE_POSTIONS: [na::Vector3<f32>; 10] = [
 struct Renderer {
     shader_program: ShaderProgram,
     vao: VertexArray,
+    cube_instances: InstanceBuffer<ModelInstance>,
 }
 
 impl Renderer {
@@ -100,9 +102,14 @@ impl Renderer {
         vertex_desc.add_attribute(gl::FLOAT, 2); // push texture coords
         vertex_desc.bind_to(&vbo, Some(&vao));
 
+        /* Instance Buffer Object */
+        // Model matrices of all cubes, which are updated every frame and drawn in one draw call.
+        let cube_instances = InstanceBuffer::new(&[], BufferUsage::StreamDraw)?;
+        cube_instances.bind_to(&vao, MESH_INSTANCE_LOCATION);
+
         /* Shader */
         let shader_program = ShaderProgram::create_from_source(
-            include_str!("../../assets/shaders/foundation/004-transform.vert"),
+            include_str!("../../assets/shaders/foundation/006-camera.vert"),
             include_str!("../../assets/shaders/foundation/004-transform.frag"),
         )?;
 
@@ -129,12 +136,13 @@ impl Renderer {
         Ok(Self {
             shader_program,
             vao,
+            cube_instances,
         })
     }
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -165,24 +173,24 @@ impl Renderer {
         self.shader_program
             .set_uniform_mat4fv(projection_name.as_c_str(), &projection_matrix);
 
-        for cube_position in CUBE_POSTIONS {
-            // Model Matrix: Create and Send to shader
-            let model_matrix_rotation = na::Rotation3::from_axis_angle(
-                &na::Unit::new_normalize(na::Vector3::new(0.5, 1.0, 0.0)),
-                -std::f32::consts::PI / 3.0 * delta_time,
-            )
-            .to_homogeneous();
-            let model_matrix_transform = na::Translation3::from(cube_position).to_homogeneous();
-            let model_matrix = model_matrix_transform * model_matrix_rotation;
-            let model_name = CString::new("model")?;
-            self.shader_program
-                .set_uniform_mat4fv(model_name.as_c_str(), &model_matrix);
-
-            // Draw
-            unsafe {
-                gl::DrawArrays(gl::TRIANGLES, 0, 36);
-            }
-        }
+        // Model Matrix: Create for each cube and send to instance buffer
+        let cube_instances: Vec<ModelInstance> = CUBE_POSTIONS
+            .iter()
+            .map(|&cube_position| {
+                let model_matrix_rotation = na::Rotation3::from_axis_angle(
+                    &na::Unit::new_normalize(na::Vector3::new(0.5, 1.0, 0.0)),
+                    -std::f32::consts::PI / 3.0 * delta_time,
+                )
+                .to_homogeneous();
+                let model_matrix_transform = na::Translation3::from(cube_position).to_homogeneous();
+                ModelInstance::new(model_matrix_transform * model_matrix_rotation)
+            })
+            .collect();
+        self.cube_instances.update(&cube_instances);
+
+        // Draw all cubes at once
+        self.vao
+            .draw_arrays_instanced(PrimitiveMode::Triangles, 0, 36, self.cube_instances.len());
 
         // Swap buffers of window
         win.swap_buffers()?;
@@ -209,6 +217,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::y();
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new("Simple Triangle", SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
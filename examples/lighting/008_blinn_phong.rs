@@ -3,14 +3,12 @@
 // remove console window : https://rust-lang.github.io/rfcs/1665-windows-subsystem.html
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::ffi::CString;
-
 use anyhow::bail;
 use gl::types::*;
 
 use learn::{
     clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera,
-    ShaderProgram, VertexArray, VertexDescription, WinitWindow,
+    PrimitiveMode, RenderWindow, ShaderProgram, VertexArray, VertexDescription, WinitWindow,
 };
 use learn_opengl_rs as learn;
 
@@ -108,19 +106,9 @@ impl Renderer {
             include_str!("../../assets/shaders/lighting/008-cube.vert"),
             include_str!("../../assets/shaders/lighting/008-cube.frag"),
         )?;
-        cube_shader.set_uniform_3f(CString::new("object_color")?.as_c_str(), 1.0, 0.5, 0.31);
-        cube_shader.set_uniform_3f(
-            CString::new("light_color")?.as_c_str(),
-            LIGHT_COLOR[0],
-            LIGHT_COLOR[1],
-            LIGHT_COLOR[2],
-        );
-        cube_shader.set_uniform_3f(
-            CString::new("light_pos")?.as_c_str(),
-            LIGHT_POS[0],
-            LIGHT_POS[1],
-            LIGHT_POS[2],
-        );
+        cube_shader.try_set_uniform("object_color", &na::Vector3::new(1.0, 0.5, 0.31))?;
+        cube_shader.try_set_uniform("light_color", &na::Vector3::from(LIGHT_COLOR))?;
+        cube_shader.try_set_uniform("light_pos", &na::Vector3::from(LIGHT_POS))?;
 
         /* Lighting */
         let light_vao = VertexArray::new()?;
@@ -139,12 +127,7 @@ impl Renderer {
             include_str!("../../assets/shaders/lighting/007-lighting.vert"),
             include_str!("../../assets/shaders/lighting/007-lighting.frag"),
         )?;
-        light_shader.set_uniform_3f(
-            CString::new("light_color")?.as_c_str(),
-            LIGHT_COLOR[0],
-            LIGHT_COLOR[1],
-            LIGHT_COLOR[2],
-        );
+        light_shader.try_set_uniform("light_color", &na::Vector3::from(LIGHT_COLOR))?;
 
         Ok(Self {
             cube_shader,
@@ -156,7 +139,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -165,11 +148,7 @@ impl Renderer {
                 as gl::types::GLbitfield,
         );
 
-        // Model Matrix
-        let model_name = CString::new("model")?;
-
         // View Matrix
-        let view_name = CString::new("view")?;
         let cube_view_matrix = camera.get_lookat_matrix();
 
         // Projection Matrix
@@ -181,7 +160,6 @@ impl Renderer {
             100.0,
         )
         .to_homogeneous(); // Perspective projection
-        let projection_name = CString::new("projection")?;
 
         /* Draw cube */
 
@@ -194,26 +172,18 @@ impl Renderer {
             .try_inverse()
             .unwrap()
             .transpose();
-        let normal_matrix_name = CString::new("normal_matrix")?;
 
+        // Uniforms are checked by name & type, mistakes are warned in log.
+        self.cube_shader.set_uniform("model", &cube_model_matrix);
+        self.cube_shader.set_uniform("view", &cube_view_matrix);
         self.cube_shader
-            .set_uniform_mat4fv(model_name.as_c_str(), &cube_model_matrix);
+            .set_uniform("projection", &projection_matrix);
         self.cube_shader
-            .set_uniform_mat4fv(view_name.as_c_str(), &cube_view_matrix);
+            .set_uniform("normal_matrix", &cube_normal_matrix);
         self.cube_shader
-            .set_uniform_mat4fv(projection_name.as_c_str(), &projection_matrix);
-        self.cube_shader
-            .set_uniform_mat3fv(normal_matrix_name.as_c_str(), &cube_normal_matrix);
-        self.cube_shader.set_uniform_3f(
-            CString::new("camera_pos")?.as_c_str(),
-            camera.get_pos().x,
-            camera.get_pos().y,
-            camera.get_pos().z,
-        );
+            .set_uniform("camera_pos", &camera.get_pos());
 
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        }
+        self.cube_vao.draw_arrays(PrimitiveMode::Triangles, 0, 36);
 
         /* Draw lighting */
 
@@ -226,16 +196,13 @@ impl Renderer {
             LIGHT_POS[1],
             LIGHT_POS[2],
         ));
+        self.light_shader.set_uniform("model", &light_model_matrix);
         self.light_shader
-            .set_uniform_mat4fv(CString::new("model")?.as_c_str(), &light_model_matrix);
-        self.light_shader
-            .set_uniform_mat4fv(view_name.as_c_str(), &camera.get_lookat_matrix());
+            .set_uniform("view", &camera.get_lookat_matrix());
         self.light_shader
-            .set_uniform_mat4fv(projection_name.as_c_str(), &projection_matrix);
+            .set_uniform("projection", &projection_matrix);
 
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        }
+        self.light_vao.draw_arrays(PrimitiveMode::Triangles, 0, 36);
 
         // Swap buffers of window
         win.swap_buffers()?;
@@ -263,6 +230,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(0.0, 1.0, 0.0);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new("Simple Triangle", SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
@@ -9,9 +9,9 @@ use anyhow::bail;
 use gl::types::*;
 
 use learn::{
+    PrimitiveMode,
     Buffer, BufferBit, BufferType, BufferUsage, Camera, MaterialPhong, ShaderProgram, VertexArray,
-    VertexDescription, WinitWindow,
-};
+    VertexDescription, WinitWindow, RenderWindow};
 use learn_opengl_rs as learn;
 
 use nalgebra as na;
@@ -169,7 +169,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -224,9 +224,7 @@ impl Renderer {
             camera.get_camera_pos().z,
         );
 
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        }
+        self.cube_vao.draw_arrays(PrimitiveMode::Triangles, 0, 36);
 
         /* Draw lighting */
 
@@ -246,9 +244,7 @@ impl Renderer {
         self.light_shader
             .set_uniform_mat4fv(projection_name.as_c_str(), &projection_matrix);
 
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        }
+        self.light_vao.draw_arrays(PrimitiveMode::Triangles, 0, 36);
 
         // Swap buffers of window
         win.swap_buffers()?;
@@ -276,6 +272,14 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(0.0, 1.0, 0.0);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| renderer.redraw(&ctx, &camera, delta_time));
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new("Simple Triangle", SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
@@ -10,8 +10,9 @@ use gl::types::*;
 
 use learn::{
     clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera,
-    DirectionalLight, FlashLight, MaterialPhong, PointLight, ShaderProgram, Texture, VertexArray,
-    VertexDescription, WinitWindow,
+    DirectionalLight, FlashLight, FrameUniforms, MaterialPhong, PointLight, PrimitiveMode,
+    RenderWindow, ShaderPreprocessor, ShaderProgram, Texture, UniformBuffer, VertexArray,
+    VertexDescription, WinitWindow, FRAME_UNIFORMS_BINDING,
 };
 use learn_opengl_rs as learn;
 
@@ -106,6 +107,7 @@ struct Renderer {
     cube_vao: VertexArray,
     light_shader: ShaderProgram,
     light_vao: VertexArray,
+    frame_ubo: UniformBuffer<FrameUniforms>,
 }
 
 impl Renderer {
@@ -153,9 +155,9 @@ impl Renderer {
         cube_vertex_desc.bind_to(&lighting_vbo, Some(&light_vao));
 
         // Prepare shader of light
-        let light_shader = ShaderProgram::create_from_source(
-            include_str!("../../assets/shaders/lighting/010-lighting.vert"),
-            include_str!("../../assets/shaders/lighting/010-lighting.frag"),
+        let light_shader = ShaderProgram::create_from_file(
+            "assets/shaders/lighting/010-lighting.vert",
+            "assets/shaders/lighting/010-lighting.frag",
         )?;
         light_shader.set_uniform_3f(
             CString::new("light_color")?.as_c_str(),
@@ -190,9 +192,11 @@ impl Renderer {
         cube_vertex_desc.bind_to(&cube_vbo, Some(&cube_vao));
 
         // Prepare shader of cube
-        let cube_shader = ShaderProgram::create_from_source(
-            include_str!("../../assets/shaders/lighting/010-cube.vert"),
-            include_str!("../../assets/shaders/lighting/010-cube.frag"),
+        // Number of point lights is injected into shader as macro
+        let cube_shader = ShaderProgram::create_from_file_with(
+            "assets/shaders/lighting/010-cube.vert",
+            "assets/shaders/lighting/010-cube.frag",
+            &ShaderPreprocessor::new().with_define("POINT_LIGHTS_NUM", POINT_LIGHT_POS.len()),
         )?;
 
         cube_shader.set_uniform_material_phong(String::from("material"), &cube_material)?;
@@ -201,17 +205,23 @@ impl Renderer {
             cube_shader.set_uniform_point_light(format!("point_lights[{i}]"), point_light)?;
         }
 
+        /* Per-frame uniforms */
+
+        // Camera is shared by both shaders through `FrameUniforms` block, which is updated once per frame.
+        let frame_ubo = UniformBuffer::new(FRAME_UNIFORMS_BINDING, &FrameUniforms::default())?;
+
         Ok(Self {
             cube_shader,
             cube_vao,
             light_shader,
             light_vao,
+            frame_ubo,
         })
     }
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -232,10 +242,6 @@ impl Renderer {
         let model_name = CString::new("model")?;
         let normal_matrix_name = CString::new("normal_matrix")?;
 
-        // View Matrix
-        let view_name = CString::new("view")?;
-        let cube_view_matrix = camera.get_lookat_matrix();
-
         // Projection Matrix
         let (window_width, window_height) = win.get_window_size();
         let projection_matrix = na::Perspective3::new(
@@ -245,23 +251,16 @@ impl Renderer {
             100.0,
         )
         .to_homogeneous(); // Perspective projection
-        let projection_name = CString::new("projection")?;
+
+        // View & Projection Matrix and camera position of all shaders
+        let frame_uniforms = FrameUniforms::new(camera, projection_matrix, 0.0);
+        self.frame_ubo.update(&frame_uniforms);
 
         /* Draw cube */
 
         self.cube_vao.bind();
         self.cube_shader.bind();
 
-        self.cube_shader
-            .set_uniform_mat4fv(view_name.as_c_str(), &cube_view_matrix);
-        self.cube_shader
-            .set_uniform_mat4fv(projection_name.as_c_str(), &projection_matrix);
-        self.cube_shader.set_uniform_3f(
-            CString::new("camera_pos")?.as_c_str(),
-            camera.get_pos().x,
-            camera.get_pos().y,
-            camera.get_pos().z,
-        );
         self.cube_shader.set_uniform_flash_light(
             String::from("spot_light"),
             &flash_light,
@@ -289,9 +288,7 @@ impl Renderer {
                 .set_uniform_mat3fv(normal_matrix_name.as_c_str(), &cube_normal_matrix);
 
             // Draw
-            unsafe {
-                gl::DrawArrays(gl::TRIANGLES, 0, 36);
-            }
+            self.cube_vao.draw_arrays(PrimitiveMode::Triangles, 0, 36);
         }
 
         /* Draw lighting */
@@ -299,11 +296,6 @@ impl Renderer {
         self.light_vao.bind();
         self.light_shader.bind();
 
-        self.light_shader
-            .set_uniform_mat4fv(view_name.as_c_str(), &camera.get_lookat_matrix());
-        self.light_shader
-            .set_uniform_mat4fv(projection_name.as_c_str(), &projection_matrix);
-
         for point_light_pos in POINT_LIGHT_POS {
             // Model matrix of light
             let light_model_matrix_scale = na::Matrix4::new_scaling(0.2);
@@ -311,9 +303,7 @@ impl Renderer {
             self.light_shader
                 .set_uniform_mat4fv(model_name.as_c_str(), &light_model_matrix);
 
-            unsafe {
-                gl::DrawArrays(gl::TRIANGLES, 0, 36);
-            }
+            self.light_vao.draw_arrays(PrimitiveMode::Triangles, 0, 36);
         }
 
         // Swap buffers of window
@@ -342,6 +332,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(0.0, 1.0, 0.0);
     let mut camera = Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new("Simple Triangle", SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
@@ -9,8 +9,8 @@ use anyhow::bail;
 use gl::types::*;
 
 use learn::{
-    clear_color, set_clear_color, BufferBit, Camera, DirectionalLight, Model, PointLight,
-    ShaderProgram, WinitWindow,
+    clear_color, set_clear_color, set_framebuffer_srgb, BufferBit, Camera, DirectionalLight, Model,
+    PointLight, RenderWindow, ShaderProgram, WinitWindow,
 };
 use learn_opengl_rs as learn;
 
@@ -47,6 +47,9 @@ impl Renderer {
     pub fn new() -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Set clear color
         set_clear_color(
             BACKGROUND_COLOR[0],
@@ -63,9 +66,9 @@ impl Renderer {
         let object_model = Model::new(PathBuf::from("assets/models/nanosuit/nanosuit.obj"))?;
 
         // Prepare shader of object
-        let object_shader = ShaderProgram::create_from_source(
-            include_str!("../../assets/shaders/model_loading/011-object.vert"),
-            include_str!("../../assets/shaders/model_loading/011-object.frag"),
+        let object_shader = ShaderProgram::create_from_file(
+            "assets/shaders/model_loading/011-object.vert",
+            "assets/shaders/model_loading/011-object.frag",
         )?;
 
         /* Lighting */
@@ -97,7 +100,7 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -150,7 +153,13 @@ impl Renderer {
             camera.get_pos().z,
         );
 
-        self.object_model.draw(&self.object_shader, "material")?;
+        // Transparent parts (`d < 1` in MTL) are blended after opaque ones
+        self.object_model.draw_sorted(
+            &self.object_shader,
+            "material",
+            &object_model_matrix,
+            &camera.get_pos(),
+        )?;
 
         // Swap buffers of window
         win.swap_buffers()?;
@@ -177,6 +186,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(0.0, 1.0, 0.0);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new("Simple Triangle", SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
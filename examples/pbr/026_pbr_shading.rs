@@ -9,8 +9,8 @@ use anyhow::bail;
 use gl::types::*;
 
 use learn::{
-    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera,
-    ShaderProgram, Texture, TextureType, VertexArray, VertexDescription, WinitWindow,
+    clear_color, primitives, set_clear_color, set_framebuffer_srgb, BufferBit, Camera, Mesh,
+    ReloadableShaderProgram, RenderWindow, Texture, TextureType, WinitWindow,
 };
 use learn_opengl_rs as learn;
 
@@ -53,10 +53,9 @@ const POINT_LIGHT_COLOR: [glm::Vec3; 4] = [
 ];
 
 struct Renderer {
-    pbr_shader: ShaderProgram,
+    pbr_shader: ReloadableShaderProgram,
 
-    sphere_vao: VertexArray,
-    sphere_index_len: usize,
+    sphere: Mesh,
 
     albedo_map: Texture,
     normal_map: Texture,
@@ -69,6 +68,9 @@ impl Renderer {
     pub fn new() -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Configure global opengl state
         unsafe {
             // Enable Depth Test
@@ -106,88 +108,19 @@ impl Renderer {
 
         /* Shaders */
 
-        // Create shader of PBR
-        let pbr_shader = ShaderProgram::create_from_source(
-            include_str!("../../assets/shaders/pbr/026-pbr.vert"),
-            include_str!("../../assets/shaders/pbr/026-pbr.frag"),
+        // Create shader of PBR, which is reloaded once shader files are modified
+        let pbr_shader = ReloadableShaderProgram::create_from_file(
+            "assets/shaders/pbr/026-pbr.vert",
+            "assets/shaders/pbr/026-pbr.frag",
         )?;
 
         /* Object Models */
 
-        let sphere_vao = VertexArray::new()?;
-        let sphere_vbo = Buffer::new(BufferType::VertexBuffer)?;
-        let sphere_ibo = Buffer::new(BufferType::IndexBuffer)?;
-
-        // Prepare vao data
-        let mut positions: Vec<glm::Vec3> = Vec::new();
-        let mut uvs: Vec<glm::Vec2> = Vec::new();
-        let mut normals: Vec<glm::Vec3> = Vec::new();
-        let mut indices: Vec<u32> = Vec::new();
-        const X_SEGMENTS: u32 = 64;
-        const Y_SEGMENTS: u32 = 64;
-        for x in 0..=X_SEGMENTS {
-            for y in 0..=Y_SEGMENTS {
-                let x_segment = x as f32 / X_SEGMENTS as f32;
-                let y_segment = y as f32 / Y_SEGMENTS as f32;
-
-                let x_pos = (x_segment * 2.0 * std::f32::consts::PI).cos()
-                    * (y_segment * std::f32::consts::PI).sin();
-                let y_pos = (y_segment * std::f32::consts::PI).cos();
-                let z_pos = (x_segment * 2.0 * std::f32::consts::PI).sin()
-                    * (y_segment * std::f32::consts::PI).sin();
-
-                positions.push(glm::vec3(x_pos, y_pos, z_pos));
-                uvs.push(glm::vec2(x_segment, y_segment));
-                normals.push(glm::vec3(x_pos, y_pos, z_pos));
-            }
-        }
-        let mut odd_row = false;
-        for y in 0..Y_SEGMENTS {
-            if !odd_row {
-                for x in 0..=X_SEGMENTS {
-                    indices.push(y * (X_SEGMENTS + 1) + x);
-                    indices.push((y + 1) * (X_SEGMENTS + 1) + x);
-                }
-            } else {
-                for x in (0..=X_SEGMENTS).rev() {
-                    indices.push((y + 1) * (X_SEGMENTS + 1) + x);
-                    indices.push(y * (X_SEGMENTS + 1) + x);
-                }
-            }
-            odd_row = !odd_row;
-        }
-        let sphere_index_len = indices.len();
-
-        let mut vertex_data: Vec<f32> = Vec::new();
-        for i in 0..positions.len() {
-            vertex_data.push(positions[i].x);
-            vertex_data.push(positions[i].y);
-            vertex_data.push(positions[i].z);
-            if normals.len() > i {
-                vertex_data.push(normals[i].x);
-                vertex_data.push(normals[i].y);
-                vertex_data.push(normals[i].z);
-            }
-            if uvs.len() > i {
-                vertex_data.push(uvs[i].x);
-                vertex_data.push(uvs[i].y);
-            }
-        }
-
-        // Set vao data
-        sphere_vao.bind();
-        sphere_vbo.set_buffer_data(vertex_data.as_slice(), BufferUsage::StaticDraw);
-        let mut vertex_desc = VertexDescription::new();
-        vertex_desc.add_attribute(gl::FLOAT, 3); // push NDC coords
-        vertex_desc.add_attribute(gl::FLOAT, 3); // push normal
-        vertex_desc.add_attribute(gl::FLOAT, 2); // push texture coords
-        vertex_desc.bind_to(&sphere_vbo, Some(&sphere_vao));
-        sphere_ibo.set_buffer_data(indices.as_slice(), BufferUsage::StaticDraw);
+        let sphere = primitives::uv_sphere(1.0, 64, 64)?;
 
         Ok(Self {
             pbr_shader,
-            sphere_vao,
-            sphere_index_len,
+            sphere,
             albedo_map,
             normal_map,
             metallic_map,
@@ -196,9 +129,14 @@ impl Renderer {
         })
     }
 
+    /// Recompile shaders whose files have changed.
+    pub fn update(&mut self) {
+        self.pbr_shader.reload_if_changed();
+    }
+
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
@@ -325,7 +263,7 @@ impl Renderer {
                         .set_uniform_1i(CString::new("enable_pbr_map").unwrap().as_c_str(), 0);
                 }
 
-                self.render_sphere()?;
+                self.sphere.draw(&self.pbr_shader, "material")?;
             }
         }
 
@@ -343,7 +281,7 @@ impl Renderer {
                 )),
             );
 
-            self.render_sphere()?;
+            self.sphere.draw(&self.pbr_shader, "material")?;
         }
 
         // Swap buffers of window
@@ -352,21 +290,6 @@ impl Renderer {
         Ok(())
     }
 
-    pub fn render_sphere(&self) -> anyhow::Result<()> {
-        // Draw sphere
-        self.sphere_vao.bind();
-        unsafe {
-            gl::DrawElements(
-                gl::TRIANGLE_STRIP,
-                self.sphere_index_len as i32,
-                gl::UNSIGNED_INT,
-                std::ptr::null(),
-            );
-        }
-
-        Ok(())
-    }
-
     pub fn close(self) {
         self.pbr_shader.close();
     }
@@ -386,6 +309,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(CAMERA_UP[0], CAMERA_UP[1], CAMERA_UP[2]);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new(WINDOW_TITLE, SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
@@ -396,7 +329,7 @@ fn main() -> anyhow::Result<()> {
     };
 
     /* Renderer */
-    let renderer = match Renderer::new() {
+    let mut renderer = match Renderer::new() {
         Ok(renderer) => renderer,
         Err(e) => {
             bail!("Failed to create renderer: {}", e);
@@ -425,6 +358,7 @@ fn main() -> anyhow::Result<()> {
                 last_time = current_time;
 
                 /* Do REDRAW */
+                renderer.update();
                 if let Err(e) = renderer.redraw(&win, &camera, delta_time) {
                     error!("Failed to redraw: {}", e);
                     control_flow.set_exit();
@@ -9,14 +9,15 @@ use anyhow::bail;
 use gl::types::*;
 
 use learn::{
-    clear_color, set_clear_color, Buffer, BufferBit, BufferType, BufferUsage, Camera, Model,
-    ShaderProgram, Texture, TextureType, TextureUnit, VertexArray, VertexDescription, WinitWindow,
+    clear_color, primitives, set_clear_color, set_framebuffer_srgb, Buffer, BufferBit, BufferType,
+    BufferUsage, Camera, ColorFormat, DepthStencilFormat, Framebuffer, Mesh, MipmapFilter, Model,
+    PrimitiveMode, RenderWindow, ShaderProgram, Texture, TextureOptions, TextureType, TextureUnit,
+    VertexArray, VertexDescription, WinitWindow, WrapMode,
 };
 use learn_opengl_rs as learn;
 
 use nalgebra as na;
 use nalgebra_glm as glm;
-use stb_image::stb_image;
 use tracing::error;
 use winit::event::Event;
 
@@ -63,6 +64,7 @@ const POINT_LIGHT_COLOR: [glm::Vec3; 4] = [
 /* IBL values */
 const CUBEMAP_WIDTH: i32 = 512;
 const CUBEMAP_HEIGHT: i32 = 512;
+const CUBEMAP_MIPMAP_LEVELS: u32 = 10;
 const IRRADIANCE_MAP_WIDTH: i32 = 32;
 const IRRADIANCE_MAP_HEIGHT: i32 = 32;
 const PREFILTERED_MAP_WIDTH: i32 = 128;
@@ -72,8 +74,7 @@ const BRDF_LUT_WIDTH: i32 = 512;
 const BRDF_LUT_HEIGHT: i32 = 512;
 
 struct Renderer {
-    sphere_vao: VertexArray,
-    sphere_index_len: usize,
+    sphere: Mesh,
 
     pbr_shader: ShaderProgram,
     albedo_map: Texture,
@@ -94,6 +95,9 @@ impl Renderer {
     pub fn new() -> anyhow::Result<Self> {
         /* Extra Settings */
 
+        // Gamma correction: linear colors are encoded to sRGB when written to framebuffer
+        set_framebuffer_srgb(true);
+
         // Configure global opengl state
         unsafe {
             // Enable Depth Test
@@ -134,69 +138,21 @@ impl Renderer {
         )?;
 
         // Load HDRI as a texture
-        let hdri_map = Texture::new(
+        let hdri_map = Texture::create_hdr(
             PathBuf::from("assets/textures/hdr/newport_loft.hdr"),
-            TextureType::Unknown,
+            None,
+            ColorFormat::RGB16F,
+            &TextureOptions::default()
+                .with_wrap(WrapMode::ClampToEdge)
+                .with_mipmap_filter(MipmapFilter::None),
         )?;
-        let mut hdri_width: i32 = 0;
-        let mut hdri_height: i32 = 0;
-        let mut hdri_channels_in_file: i32 = 0;
-        let hdri_img_data = unsafe {
-            stb_image::stbi_set_flip_vertically_on_load(1);
-            stb_image::stbi_loadf(
-                "assets/textures/hdr/newport_loft.hdr".as_ptr().cast(),
-                &mut hdri_width,
-                &mut hdri_height,
-                &mut hdri_channels_in_file,
-                0,
-            )
-        };
-        if hdri_img_data.is_null() {
-            bail!("Failed to load HDR image.");
-        }
-        tracing::debug!(
-            "HDR image loaded. path: {:?}, channels: {:?}, size: {:?}x{:?}",
-            hdri_map.path,
-            hdri_channels_in_file,
-            hdri_width,
-            hdri_height
-        );
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, hdri_map.id);
-            gl::TexParameteri(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_WRAP_S,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_WRAP_T,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGB16F as GLint,
-                hdri_width,
-                hdri_height,
-                0,
-                gl::RGB,
-                gl::FLOAT,
-                hdri_img_data.cast(),
-            );
-        }
-        unsafe {
-            stb_image::stbi_image_free(hdri_img_data.cast());
-        }
 
         /* Shaders */
 
         // Create shader of PBR
-        let pbr_shader = ShaderProgram::create_from_source(
-            include_str!("../../assets/shaders/pbr/027-pbr.vert"),
-            include_str!("../../assets/shaders/pbr/027-pbr.frag"),
+        let pbr_shader = ShaderProgram::create_from_file(
+            "assets/shaders/pbr/027-pbr.vert",
+            "assets/shaders/pbr/027-pbr.frag",
         )?;
         let equirectangular_to_cubemap_shader = ShaderProgram::create_from_source(
             include_str!("../../assets/shaders/pbr/027-cubemap.vert"),
@@ -222,141 +178,21 @@ impl Renderer {
         /* Object Models */
         let cube_model: Model = Model::new(PathBuf::from("assets/models/cube_wood/cube.obj"))?;
 
-        let sphere_vao = VertexArray::new()?;
-        let sphere_vbo = Buffer::new(BufferType::VertexBuffer)?;
-        let sphere_ibo = Buffer::new(BufferType::IndexBuffer)?;
-        // Prepare vao data
-        let mut positions: Vec<glm::Vec3> = Vec::new();
-        let mut uvs: Vec<glm::Vec2> = Vec::new();
-        let mut normals: Vec<glm::Vec3> = Vec::new();
-        let mut indices: Vec<u32> = Vec::new();
-        const X_SEGMENTS: u32 = 64;
-        const Y_SEGMENTS: u32 = 64;
-        for x in 0..=X_SEGMENTS {
-            for y in 0..=Y_SEGMENTS {
-                let x_segment = x as f32 / X_SEGMENTS as f32;
-                let y_segment = y as f32 / Y_SEGMENTS as f32;
-
-                let x_pos = (x_segment * 2.0 * std::f32::consts::PI).cos()
-                    * (y_segment * std::f32::consts::PI).sin();
-                let y_pos = (y_segment * std::f32::consts::PI).cos();
-                let z_pos = (x_segment * 2.0 * std::f32::consts::PI).sin()
-                    * (y_segment * std::f32::consts::PI).sin();
-
-                positions.push(glm::vec3(x_pos, y_pos, z_pos));
-                uvs.push(glm::vec2(x_segment, y_segment));
-                normals.push(glm::vec3(x_pos, y_pos, z_pos));
-            }
-        }
-        let mut odd_row = false;
-        for y in 0..Y_SEGMENTS {
-            if !odd_row {
-                for x in 0..=X_SEGMENTS {
-                    indices.push(y * (X_SEGMENTS + 1) + x);
-                    indices.push((y + 1) * (X_SEGMENTS + 1) + x);
-                }
-            } else {
-                for x in (0..=X_SEGMENTS).rev() {
-                    indices.push((y + 1) * (X_SEGMENTS + 1) + x);
-                    indices.push(y * (X_SEGMENTS + 1) + x);
-                }
-            }
-            odd_row = !odd_row;
-        }
-        let sphere_index_len = indices.len();
-
-        let mut vertex_data: Vec<f32> = Vec::new();
-        for i in 0..positions.len() {
-            vertex_data.push(positions[i].x);
-            vertex_data.push(positions[i].y);
-            vertex_data.push(positions[i].z);
-            if normals.len() > i {
-                vertex_data.push(normals[i].x);
-                vertex_data.push(normals[i].y);
-                vertex_data.push(normals[i].z);
-            }
-            if uvs.len() > i {
-                vertex_data.push(uvs[i].x);
-                vertex_data.push(uvs[i].y);
-            }
-        }
-        // Set vao data
-        sphere_vao.bind();
-        sphere_vbo.set_buffer_data(vertex_data.as_slice(), BufferUsage::StaticDraw);
-        let mut vertex_desc = VertexDescription::new();
-        vertex_desc.add_attribute(gl::FLOAT, 3); // push NDC coords
-        vertex_desc.add_attribute(gl::FLOAT, 3); // push normal
-        vertex_desc.add_attribute(gl::FLOAT, 2); // push texture coords
-        vertex_desc.bind_to(&sphere_vbo, Some(&sphere_vao));
-        sphere_ibo.set_buffer_data(indices.as_slice(), BufferUsage::StaticDraw);
+        let sphere = primitives::uv_sphere(1.0, 64, 64)?;
 
         /* Get environment cubemap */
 
-        // Prepare cubemap framebuffer
-        let mut env_cubemap_fbo = 0;
-        let mut env_cubemap_rbo = 0;
-        unsafe {
-            gl::GenFramebuffers(1, &mut env_cubemap_fbo);
-            gl::GenRenderbuffers(1, &mut env_cubemap_rbo);
-
-            gl::BindFramebuffer(gl::FRAMEBUFFER, env_cubemap_fbo);
-            gl::BindRenderbuffer(gl::RENDERBUFFER, env_cubemap_rbo);
-            gl::RenderbufferStorage(
-                gl::RENDERBUFFER,
-                gl::DEPTH_COMPONENT24,
-                CUBEMAP_WIDTH,
-                CUBEMAP_HEIGHT,
-            );
-            gl::FramebufferRenderbuffer(
-                gl::FRAMEBUFFER,
-                gl::DEPTH_ATTACHMENT,
-                gl::RENDERBUFFER,
-                env_cubemap_rbo,
-            );
-        }
+        // Prepare cubemap framebuffer, faces of cubemap will be attached as color attachment later
+        let mut env_cubemap_fbo = Framebuffer::builder(CUBEMAP_WIDTH as u32, CUBEMAP_HEIGHT as u32)
+            .with_depth_renderbuffer(DepthStencilFormat::Depth24)
+            .build()?;
         // Prepare cubemap texture
-        let env_cubemap = Texture::new(PathBuf::new(), TextureType::Cubemap)?;
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_CUBE_MAP, env_cubemap.id);
-            for i in 0..6 {
-                gl::TexImage2D(
-                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i,
-                    0,
-                    gl::RGB16F as GLint,
-                    CUBEMAP_WIDTH,
-                    CUBEMAP_HEIGHT,
-                    0,
-                    gl::RGB,
-                    gl::FLOAT,
-                    core::ptr::null(),
-                );
-            }
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_WRAP_R,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_WRAP_S,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_WRAP_T,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_MIN_FILTER,
-                gl::LINEAR_MIPMAP_LINEAR as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_MAG_FILTER,
-                gl::LINEAR as GLint,
-            );
-        }
+        let env_cubemap = Texture::create_empty_cubemap(
+            CUBEMAP_WIDTH as u32,
+            ColorFormat::RGB16F,
+            CUBEMAP_MIPMAP_LEVELS,
+            &TextureOptions::default().with_wrap(WrapMode::ClampToEdge),
+        )?;
 
         // Convert HDR equirectangular environment map to cubemap equivalent
         let env_cubemap_projection =
@@ -403,21 +239,17 @@ impl Renderer {
             &hdri_map,
             TextureUnit::TEXTURE0,
         );
-        unsafe {
-            gl::Viewport(0, 0, CUBEMAP_WIDTH, CUBEMAP_HEIGHT);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, env_cubemap_fbo);
-        }
+        env_cubemap_fbo.bind_with_viewport();
         for (i, view_matrix) in env_cubemap_views.iter().enumerate() {
             equirectangular_to_cubemap_shader
                 .set_uniform_mat4fv(CString::new("view").unwrap().as_c_str(), view_matrix);
+            env_cubemap_fbo.attach_color_target(
+                0,
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
+                &env_cubemap,
+                0,
+            );
             unsafe {
-                gl::FramebufferTexture2D(
-                    gl::FRAMEBUFFER,
-                    gl::COLOR_ATTACHMENT0,
-                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
-                    env_cubemap.id,
-                    0,
-                );
                 gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
             }
             cube_model.draw(&equirectangular_to_cubemap_shader, "material")?;
@@ -431,60 +263,17 @@ impl Renderer {
 
         /* Prefilter Environment Cubemap on hemisphere */
 
-        let irradiance_map = Texture::new(PathBuf::new(), TextureType::Cubemap)?;
-        unsafe {
-            // Prepare prefiltered environment cubemap texture
-            gl::BindTexture(gl::TEXTURE_CUBE_MAP, irradiance_map.id);
-            for i in 0..6 {
-                gl::TexImage2D(
-                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i,
-                    0,
-                    gl::RGB16F as GLint,
-                    IRRADIANCE_MAP_WIDTH,
-                    IRRADIANCE_MAP_HEIGHT,
-                    0,
-                    gl::RGB,
-                    gl::FLOAT,
-                    core::ptr::null(),
-                );
-            }
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_WRAP_R,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_WRAP_S,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_WRAP_T,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_MIN_FILTER,
-                gl::LINEAR as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_MAG_FILTER,
-                gl::LINEAR as GLint,
-            );
+        let irradiance_map = Texture::create_empty_cubemap(
+            IRRADIANCE_MAP_WIDTH as u32,
+            ColorFormat::RGB16F,
+            1,
+            &TextureOptions::default()
+                .with_wrap(WrapMode::ClampToEdge)
+                .with_mipmap_filter(MipmapFilter::None),
+        )?;
 
-            // Scale fbo to prefilter cubemap size
-            gl::BindFramebuffer(gl::FRAMEBUFFER, env_cubemap_fbo);
-            gl::BindRenderbuffer(gl::RENDERBUFFER, env_cubemap_rbo);
-            gl::RenderbufferStorage(
-                gl::RENDERBUFFER,
-                gl::DEPTH_COMPONENT24,
-                IRRADIANCE_MAP_WIDTH,
-                IRRADIANCE_MAP_HEIGHT,
-            );
-            gl::Viewport(0, 0, IRRADIANCE_MAP_WIDTH, IRRADIANCE_MAP_HEIGHT);
-        }
+        // Scale fbo to prefilter cubemap size
+        env_cubemap_fbo.resize(IRRADIANCE_MAP_WIDTH as u32, IRRADIANCE_MAP_HEIGHT as u32)?;
 
         // Do prefiltering
         irradiance_map_shader.bind();
@@ -497,20 +286,17 @@ impl Renderer {
             &env_cubemap,
             TextureUnit::TEXTURE0,
         );
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, env_cubemap_fbo);
-        }
+        env_cubemap_fbo.bind_with_viewport();
         for (i, view_matrix) in env_cubemap_views.iter().enumerate() {
             irradiance_map_shader
                 .set_uniform_mat4fv(CString::new("view").unwrap().as_c_str(), view_matrix);
+            env_cubemap_fbo.attach_color_target(
+                0,
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
+                &irradiance_map,
+                0,
+            );
             unsafe {
-                gl::FramebufferTexture2D(
-                    gl::FRAMEBUFFER,
-                    gl::COLOR_ATTACHMENT0,
-                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
-                    irradiance_map.id,
-                    0,
-                );
                 gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
             }
             cube_model.draw(&irradiance_map_shader, "material")?;
@@ -518,54 +304,13 @@ impl Renderer {
 
         /* Prefilter Environment Cubemap (The Split Sum) */
 
-        // Prepare prefiltered environment cubemap
-        let prefiltered_envmap = Texture::new(PathBuf::new(), TextureType::Cubemap)?;
-        unsafe {
-            // Prepare prefiltered environment cubemap texture
-            gl::BindTexture(gl::TEXTURE_CUBE_MAP, prefiltered_envmap.id);
-            for i in 0..6 {
-                gl::TexImage2D(
-                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i,
-                    0,
-                    gl::RGB16F as GLint,
-                    PREFILTERED_MAP_WIDTH,
-                    PREFILTERED_MAP_HEIGHT,
-                    0,
-                    gl::RGB,
-                    gl::FLOAT,
-                    core::ptr::null(),
-                );
-            }
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_WRAP_R,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_WRAP_S,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_WRAP_T,
-                gl::CLAMP_TO_EDGE as GLint,
-            );
-            // Make sure to set minification filter to mip_linear
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_MIN_FILTER,
-                gl::LINEAR_MIPMAP_LINEAR as GLint,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_CUBE_MAP,
-                gl::TEXTURE_MAG_FILTER,
-                gl::LINEAR as GLint,
-            );
-
-            // Generate mipmaps for the cubemap so OpenGL automatically allocates the required memory.
-            gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
-        }
+        // Trilinear filtering between mipmap levels, which are rendered by prefiltering below
+        let prefiltered_envmap = Texture::create_empty_cubemap(
+            PREFILTERED_MAP_WIDTH as u32,
+            ColorFormat::RGB16F,
+            PREFILTERED_MAP_MIPMAP_LEVELS as u32,
+            &TextureOptions::default().with_wrap(WrapMode::ClampToEdge),
+        )?;
 
         // Prefiltering the environment cubemap of all mipmap levels
         prefilter_envmap_shader.bind();
@@ -580,19 +325,10 @@ impl Renderer {
         );
         for mip_level in 0..PREFILTERED_MAP_MIPMAP_LEVELS {
             // 1. reisze framebuffer according to mip-level size.
-            let mip_width = (PREFILTERED_MAP_WIDTH as f32 * (0.5_f32).powi(mip_level)) as i32;
-            let mip_height = (PREFILTERED_MAP_HEIGHT as f32 * (0.5_f32).powi(mip_level)) as i32;
-            unsafe {
-                gl::BindFramebuffer(gl::FRAMEBUFFER, env_cubemap_fbo);
-                gl::BindRenderbuffer(gl::RENDERBUFFER, env_cubemap_rbo);
-                gl::RenderbufferStorage(
-                    gl::RENDERBUFFER,
-                    gl::DEPTH_COMPONENT24,
-                    mip_width,
-                    mip_height,
-                );
-                gl::Viewport(0, 0, mip_width, mip_height);
-            }
+            let mip_width = (PREFILTERED_MAP_WIDTH as f32 * (0.5_f32).powi(mip_level)) as u32;
+            let mip_height = (PREFILTERED_MAP_HEIGHT as f32 * (0.5_f32).powi(mip_level)) as u32;
+            env_cubemap_fbo.resize(mip_width, mip_height)?;
+            env_cubemap_fbo.bind_with_viewport();
             // 2. Set the roughness of this mipmap level : level1 is 0, level2 is 1/4, ...
             let target_roughness = mip_level as f32 / (PREFILTERED_MAP_MIPMAP_LEVELS - 1) as f32;
             // 3. Do prefiltering
@@ -603,14 +339,13 @@ impl Renderer {
             for (i, view_matrix) in env_cubemap_views.iter().enumerate() {
                 prefilter_envmap_shader
                     .set_uniform_mat4fv(CString::new("view").unwrap().as_c_str(), view_matrix);
+                env_cubemap_fbo.attach_color_target(
+                    0,
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
+                    &prefiltered_envmap,
+                    mip_level, // set the level of prefiltered environment map
+                );
                 unsafe {
-                    gl::FramebufferTexture2D(
-                        gl::FRAMEBUFFER,
-                        gl::COLOR_ATTACHMENT0,
-                        gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
-                        prefiltered_envmap.id,
-                        mip_level, // set the level of prefiltered environment map
-                    );
                     gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
                 }
                 cube_model.draw(&prefilter_envmap_shader, "material")?;
@@ -651,24 +386,9 @@ impl Renderer {
         }
 
         // Reconfigure framebuffer
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, env_cubemap_fbo);
-            gl::BindRenderbuffer(gl::RENDERBUFFER, env_cubemap_rbo);
-            gl::RenderbufferStorage(
-                gl::RENDERBUFFER,
-                gl::DEPTH_COMPONENT24,
-                BRDF_LUT_WIDTH,
-                BRDF_LUT_HEIGHT,
-            );
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT0,
-                gl::TEXTURE_2D,
-                brdf_lut_map.id,
-                0,
-            );
-            gl::Viewport(0, 0, BRDF_LUT_WIDTH, BRDF_LUT_HEIGHT);
-        }
+        env_cubemap_fbo.resize(BRDF_LUT_WIDTH as u32, BRDF_LUT_HEIGHT as u32)?;
+        env_cubemap_fbo.bind_with_viewport();
+        env_cubemap_fbo.attach_color_target(0, gl::TEXTURE_2D, &brdf_lut_map, 0);
 
         // Generate 2D LUT from the BRDF equations used.
         prefilter_brdf_shader.bind();
@@ -684,21 +404,18 @@ impl Renderer {
         quad_vertex_desc.add_attribute(gl::FLOAT, 3); // set coords attribute
         quad_vertex_desc.add_attribute(gl::FLOAT, 2); // set Texture coord attribute
         quad_vertex_desc.bind_to(&quad_vbo, Some(&quad_vao));
-        unsafe {
-            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
-        }
+        quad_vao.draw_arrays(PrimitiveMode::TriangleStrip, 0, 4);
         quad_vao.unbind();
 
+        Framebuffer::bind_default();
         unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
             gl::Viewport(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
         }
 
         Ok(Self {
             skybox_shader,
             pbr_shader,
-            sphere_vao,
-            sphere_index_len,
+            sphere,
             albedo_map,
             normal_map,
             metallic_map,
@@ -714,13 +431,11 @@ impl Renderer {
 
     pub fn redraw(
         &self,
-        win: &WinitWindow,
+        win: &impl RenderWindow,
         camera: &Camera,
         _delta_time: f32,
     ) -> anyhow::Result<()> {
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+        Framebuffer::bind_default();
         clear_color(
             (BufferBit::ColorBufferBit as GLenum | BufferBit::DepthBufferBit as GLenum)
                 as gl::types::GLbitfield,
@@ -859,7 +574,7 @@ impl Renderer {
                         .set_uniform_1i(CString::new("enable_pbr_map").unwrap().as_c_str(), 0);
                 }
 
-                self.render_sphere()?;
+                self.sphere.draw(&self.pbr_shader, "material")?;
             }
         }
 
@@ -879,7 +594,7 @@ impl Renderer {
                 )),
             );
 
-            self.render_sphere()?;
+            self.sphere.draw(&self.pbr_shader, "material")?;
         }
 
         /* Pass 3 : render skybox */
@@ -902,21 +617,6 @@ impl Renderer {
         Ok(())
     }
 
-    pub fn render_sphere(&self) -> anyhow::Result<()> {
-        // Draw sphere
-        self.sphere_vao.bind();
-        unsafe {
-            gl::DrawElements(
-                gl::TRIANGLE_STRIP,
-                self.sphere_index_len as i32,
-                gl::UNSIGNED_INT,
-                std::ptr::null(),
-            );
-        }
-
-        Ok(())
-    }
-
     pub fn close(self) {
         self.pbr_shader.close();
     }
@@ -936,6 +636,16 @@ fn main() -> anyhow::Result<()> {
     let camera_up = na::Vector3::new(CAMERA_UP[0], CAMERA_UP[1], CAMERA_UP[2]);
     let mut camera = learn::Camera::new(camera_pos, camera_look_at, camera_up);
 
+    /* Golden image capture */
+    #[cfg(not(target_os = "macos"))]
+    if let Some(golden) = learn::GoldenRun::from_env() {
+        let ctx = golden.create_context()?;
+        let renderer = Renderer::new()?;
+        return golden.run(&ctx, |delta_time| {
+            renderer.redraw(&ctx, &camera, delta_time)
+        });
+    }
+
     /* Window */
     let (win, event_loop) = match WinitWindow::new(WINDOW_TITLE, SCREEN_WIDTH, SCREEN_HEIGHT) {
         Ok((win, event_loop)) => (win, event_loop),
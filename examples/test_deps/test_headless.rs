@@ -0,0 +1,43 @@
+//! Render without any window, e.g. `cargo run --example test_headless` in CI with Mesa llvmpipe.
+
+use anyhow::bail;
+use gl::types::*;
+
+use learn::{clear_color, set_clear_color, BufferBit, HeadlessContext};
+use learn_opengl_rs as learn;
+
+use tracing::info;
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 64;
+const CLEAR_COLOR: [f32; 4] = [1.0, 0.5, 0.0, 1.0];
+
+fn main() -> anyhow::Result<()> {
+    let subscriber = tracing_subscriber::FmtSubscriber::builder()
+        .with_max_level(tracing::Level::TRACE)
+        .finish();
+    tracing::subscriber::set_global_default(subscriber)?;
+
+    let ctx = HeadlessContext::new(WIDTH, HEIGHT)?;
+
+    ctx.bind();
+    set_clear_color(
+        CLEAR_COLOR[0],
+        CLEAR_COLOR[1],
+        CLEAR_COLOR[2],
+        CLEAR_COLOR[3],
+    );
+    clear_color(BufferBit::ColorBufferBit as GLenum as GLbitfield);
+    ctx.finish();
+
+    // Read back offscreen framebuffer
+    let image = ctx.get_framebuffer().read_color_attachment(0)?.to_rgba8();
+    let pixel = image.get_pixel(WIDTH / 2, HEIGHT / 2).0;
+    info!("Center pixel: {:?}", pixel);
+
+    if pixel[0] != 255 || pixel[2] != 0 || pixel[3] != 255 {
+        bail!("Unexpected pixel: {:?}", pixel);
+    }
+
+    Ok(())
+}
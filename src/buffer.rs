@@ -1,7 +1,11 @@
 use gl::types::*;
 use std::mem;
+use std::ops::{Deref, DerefMut};
 
-use crate::{get_gl_error, Vertex};
+use anyhow::bail;
+use tracing::warn;
+
+use crate::{get_gl_error, Index, Vertex};
 
 /// Enum of Buffer Object types.
 /// TODO: complete all bindings
@@ -11,6 +15,8 @@ pub enum BufferType {
     VertexBuffer = gl::ARRAY_BUFFER as isize,
     /// Index/Element Buffer Object, include Vertex array indices
     IndexBuffer = gl::ELEMENT_ARRAY_BUFFER as isize,
+    /// Uniform Buffer Object, backing storage of uniform blocks
+    UniformBuffer = gl::UNIFORM_BUFFER as isize,
 }
 
 /// Enum of Buffer Bit for `glClear()`.
@@ -25,14 +31,45 @@ pub enum BufferBit {
     StencilBufferBit = gl::STENCIL_BUFFER_BIT as isize,
 }
 
-/// Enum of Buffer Object usage.
-/// TODO: complete all bindings
-#[derive(Debug, Clone, Copy)]
+/// Enum of Buffer Object usage, which is a hint of how data store will be accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BufferUsage {
-    // STATIC : The data store contents will be modified once and used many times.
-    // DRAW   : The data store contents are modified by the application, and used as the source for GL drawing and
-    //          image specification commands.
+    // STREAM  : The data store contents will be modified once and used at most a few times.
+    // STATIC  : The data store contents will be modified once and used many times.
+    // DYNAMIC : The data store contents will be modified repeatedly and used many times.
+    //
+    // DRAW : The data store contents are modified by the application, and used as the source for GL drawing and
+    //        image specification commands.
+    // READ : The data store contents are modified by reading data from the GL, and used to return that data when
+    //        queried by the application.
+    // COPY : The data store contents are modified by reading data from the GL, and used as the source for GL drawing
+    //        and image specification commands.
+    StreamDraw = gl::STREAM_DRAW as isize,
+    StreamRead = gl::STREAM_READ as isize,
+    StreamCopy = gl::STREAM_COPY as isize,
     StaticDraw = gl::STATIC_DRAW as isize,
+    StaticRead = gl::STATIC_READ as isize,
+    StaticCopy = gl::STATIC_COPY as isize,
+    DynamicDraw = gl::DYNAMIC_DRAW as isize,
+    DynamicRead = gl::DYNAMIC_READ as isize,
+    DynamicCopy = gl::DYNAMIC_COPY as isize,
+}
+
+/// Enum of access bits for `Buffer::map_range()`, combine them by `|` as `GLbitfield`.
+#[derive(Debug, Clone, Copy)]
+pub enum MapAccessBit {
+    /// Mapped range may be read.
+    Read = gl::MAP_READ_BIT as isize,
+    /// Mapped range may be written.
+    Write = gl::MAP_WRITE_BIT as isize,
+    /// Previous contents of the range may be discarded, can't be used with `Read`.
+    InvalidateRange = gl::MAP_INVALIDATE_RANGE_BIT as isize,
+    /// Previous contents of the whole buffer may be discarded, can't be used with `Read`.
+    InvalidateBuffer = gl::MAP_INVALIDATE_BUFFER_BIT as isize,
+    /// Modified sub-ranges must be flushed by `MappedBuffer::flush_range()` explicitly, requires `Write`.
+    FlushExplicit = gl::MAP_FLUSH_EXPLICIT_BIT as isize,
+    /// GL won't wait for pending commands using the buffer, so synchronization is up to the application.
+    Unsynchronized = gl::MAP_UNSYNCHRONIZED_BIT as isize,
 }
 
 /// Wrapper of [Buffer Object](https://www.khronos.org/opengl/wiki/Buffer_Object)
@@ -91,10 +128,120 @@ impl Buffer {
         }
     }
 
+    /// Allocate `size` bytes of uninitialized data store, it'll call `bind()` automatically.
+    ///
+    /// wrap `glBufferData`
+    pub fn allocate(&self, size: usize, usage: BufferUsage) {
+        self.bind();
+
+        unsafe {
+            gl::BufferData(
+                self.buffer_type as GLenum,
+                size as GLsizeiptr,
+                std::ptr::null(),
+                usage as GLenum,
+            );
+        }
+    }
+
+    /// Update part of Buffer Object data starting from `offset` bytes, it'll call `bind()` automatically.
+    ///
+    /// Data store must be large enough, see `set_buffer_data()` or `allocate()`.
+    ///
+    /// wrap `glBufferSubData`
+    pub fn set_buffer_sub_data<T>(&self, offset: usize, data: &[T]) {
+        self.bind();
+
+        unsafe {
+            gl::BufferSubData(
+                self.buffer_type as GLenum,
+                offset as GLintptr,
+                mem::size_of_val(data) as GLsizeiptr,
+                data.as_ptr().cast(),
+            );
+        }
+    }
+
+    /// Re-allocate data store of the same size & usage, so that GL can hand out new memory while commands still
+    /// using the old contents are in flight, instead of stalling on the next update. Contents become undefined.
+    ///
+    /// wrap `glBufferData` with null data
+    pub fn orphan(&self) {
+        let size = self.get_size();
+        let mut usage = 0;
+        unsafe {
+            gl::GetBufferParameteriv(self.buffer_type as GLenum, gl::BUFFER_USAGE, &mut usage);
+            gl::BufferData(
+                self.buffer_type as GLenum,
+                size as GLsizeiptr,
+                std::ptr::null(),
+                usage as GLenum,
+            );
+        }
+    }
+
+    /// Get size of data store in bytes, it'll call `bind()` automatically.
+    ///
+    /// wrap `glGetBufferParameteriv` with `GL_BUFFER_SIZE`
+    pub fn get_size(&self) -> usize {
+        self.bind();
+
+        let mut size = 0;
+        unsafe { gl::GetBufferParameteriv(self.buffer_type as GLenum, gl::BUFFER_SIZE, &mut size) };
+        size as usize
+    }
+
+    /// Map `count` elements of `T` starting from `offset` bytes into client memory, with `access` of
+    /// `MapAccessBit`s. It'll call `bind()` automatically.
+    ///
+    /// The range is unmapped when returned `MappedBuffer` is dropped. Buffer can't be used by GL commands while it's
+    /// mapped.
+    ///
+    /// wrap `glMapBufferRange`
+    pub fn map_range<T: Copy>(
+        &self,
+        offset: usize,
+        count: usize,
+        access: GLbitfield,
+    ) -> anyhow::Result<MappedBuffer<'_, T>> {
+        if !offset.is_multiple_of(mem::align_of::<T>()) {
+            bail!(
+                "Offset {offset} of mapped range is not aligned to {} bytes",
+                mem::align_of::<T>()
+            );
+        }
+        let length = count * mem::size_of::<T>();
+        let size = self.get_size();
+        if offset + length > size {
+            bail!(
+                "Mapped range {offset}..{} exceeds buffer of {size} bytes",
+                offset + length
+            );
+        }
+
+        let ptr = unsafe {
+            gl::MapBufferRange(
+                self.buffer_type as GLenum,
+                offset as GLintptr,
+                length as GLsizeiptr,
+                access,
+            )
+        };
+        if ptr.is_null() {
+            return Err(get_gl_error().unwrap().into());
+        }
+
+        Ok(MappedBuffer {
+            buffer: self,
+            ptr: ptr.cast(),
+            count,
+        })
+    }
+
     /// Set indices to IndexBuffer, it'll call `bind()` automatically.
     ///
     /// wrap `glBufferData`
-    pub fn set_indices(&self, indices: &[u32], usage: BufferUsage) {
+    pub fn set_indices<I: Index>(&self, indices: &[I], usage: BufferUsage) {
         assert_eq!(self.buffer_type, BufferType::IndexBuffer);
 
         self.set_buffer_data(indices, usage);
@@ -108,6 +255,69 @@ impl Buffer {
 
         self.set_buffer_data(vertices, usage);
     }
+
+    /// Bind this Buffer Object to indexed binding point `index` of its buffer type, e.g. uniform buffer binding point.
+    ///
+    /// wrap `glBindBufferBase`
+    pub fn bind_base(&self, index: GLuint) {
+        unsafe { gl::BindBufferBase(self.buffer_type as GLenum, index, self.id) }
+    }
+}
+
+/// Range of a `Buffer` mapped into client memory as `[T]`, which is unmapped on drop.
+pub struct MappedBuffer<'a, T> {
+    buffer: &'a Buffer,
+    ptr: *mut T,
+    count: usize,
+}
+
+impl<T> MappedBuffer<'_, T> {
+    /// Flush modified `count` elements starting from element `start`, for range mapped with
+    /// `MapAccessBit::FlushExplicit`.
+    ///
+    /// wrap `glFlushMappedBufferRange`
+    pub fn flush_range(&self, start: usize, count: usize) {
+        assert!(start + count <= self.count);
+
+        self.buffer.bind();
+        unsafe {
+            gl::FlushMappedBufferRange(
+                self.buffer.buffer_type as GLenum,
+                (start * mem::size_of::<T>()) as GLintptr,
+                (count * mem::size_of::<T>()) as GLsizeiptr,
+            );
+        }
+    }
+}
+
+impl<T> Deref for MappedBuffer<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.count) }
+    }
+}
+
+impl<T> DerefMut for MappedBuffer<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.count) }
+    }
+}
+
+impl<T> Drop for MappedBuffer<'_, T> {
+    /// wrap `glUnmapBuffer`
+    fn drop(&mut self) {
+        // Another buffer may have been bound to the same target meanwhile.
+        self.buffer.bind();
+        let ok = unsafe { gl::UnmapBuffer(self.buffer.buffer_type as GLenum) };
+        if ok == gl::FALSE {
+            // e.g. screen mode changed, contents of data store become undefined
+            warn!(
+                "Data store of buffer {} is corrupted while mapped",
+                self.buffer.id
+            );
+        }
+    }
 }
 
 /// wrap `glClearColor`
@@ -0,0 +1,296 @@
+use std::mem;
+
+use gl::types::*;
+
+use crate::VertexArray;
+
+/// Enum of primitive topologies of draw calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimitiveMode {
+    Points = gl::POINTS as isize,
+    Lines = gl::LINES as isize,
+    LineStrip = gl::LINE_STRIP as isize,
+    LineLoop = gl::LINE_LOOP as isize,
+    Triangles = gl::TRIANGLES as isize,
+    TriangleStrip = gl::TRIANGLE_STRIP as isize,
+    TriangleFan = gl::TRIANGLE_FAN as isize,
+    /// Lines with adjacent vertices for geometry shader, requires OpenGL 3.2
+    LinesAdjacency = gl::LINES_ADJACENCY as isize,
+    /// Requires OpenGL 3.2
+    LineStripAdjacency = gl::LINE_STRIP_ADJACENCY as isize,
+    /// Triangles with adjacent vertices for geometry shader, requires OpenGL 3.2
+    TrianglesAdjacency = gl::TRIANGLES_ADJACENCY as isize,
+    /// Requires OpenGL 3.2
+    TriangleStripAdjacency = gl::TRIANGLE_STRIP_ADJACENCY as isize,
+    /// Patches for tessellation shaders, see `set_patch_vertices()`. Requires OpenGL 4.0
+    Patches = gl::PATCHES as isize,
+}
+
+/// Enum of index types of indexed draw calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexType {
+    UnsignedByte = gl::UNSIGNED_BYTE as isize,
+    UnsignedShort = gl::UNSIGNED_SHORT as isize,
+    UnsignedInt = gl::UNSIGNED_INT as isize,
+}
+
+impl IndexType {
+    /// Size of an index in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            IndexType::UnsignedByte => mem::size_of::<u8>(),
+            IndexType::UnsignedShort => mem::size_of::<u16>(),
+            IndexType::UnsignedInt => mem::size_of::<u32>(),
+        }
+    }
+
+    /// Max value of the type, which is conventionally used as primitive restart index.
+    pub fn max_index(&self) -> GLuint {
+        match self {
+            IndexType::UnsignedByte => u8::MAX as GLuint,
+            IndexType::UnsignedShort => u16::MAX as GLuint,
+            IndexType::UnsignedInt => u32::MAX,
+        }
+    }
+}
+
+/// Type of indices in Index Buffer Object: `u8`, `u16` or `u32`.
+pub trait Index: Copy {
+    const INDEX_TYPE: IndexType;
+}
+
+impl Index for u8 {
+    const INDEX_TYPE: IndexType = IndexType::UnsignedByte;
+}
+
+impl Index for u16 {
+    const INDEX_TYPE: IndexType = IndexType::UnsignedShort;
+}
+
+impl Index for u32 {
+    const INDEX_TYPE: IndexType = IndexType::UnsignedInt;
+}
+
+/// Restart primitive (e.g. a new triangle strip) whenever `index` appears in indices of indexed draw calls, or disable
+/// restarting by `None`. Usually `index` is `IndexType::max_index()`.
+///
+/// wrap `glEnable`/`glDisable` with `GL_PRIMITIVE_RESTART`, `glPrimitiveRestartIndex`
+pub fn set_primitive_restart(index: Option<GLuint>) {
+    unsafe {
+        match index {
+            Some(index) => {
+                gl::Enable(gl::PRIMITIVE_RESTART);
+                gl::PrimitiveRestartIndex(index);
+            }
+            None => gl::Disable(gl::PRIMITIVE_RESTART),
+        }
+    }
+}
+
+/// Byte offset of index `first` in Index Buffer Object, as pointer argument of `glDrawElements*`.
+fn index_offset(index_type: IndexType, first: usize) -> *const GLvoid {
+    (first * index_type.size()) as *const GLvoid
+}
+
+/// Draw calls, which read vertices from this Vertex Array Object and indices from its bound Index Buffer Object.
+///
+/// All of them call `bind()` automatically. `first` of indexed draw calls is counted in indices, not bytes.
+impl VertexArray {
+    /// Draw `count` vertices starting from vertex `first`.
+    ///
+    /// wrap `glDrawArrays`
+    pub fn draw_arrays(&self, mode: PrimitiveMode, first: usize, count: usize) {
+        self.bind();
+        unsafe { gl::DrawArrays(mode as GLenum, first as GLint, count as GLsizei) };
+    }
+
+    /// Draw `instance_count` instances of `count` vertices starting from vertex `first`.
+    ///
+    /// wrap `glDrawArraysInstanced`
+    pub fn draw_arrays_instanced(
+        &self,
+        mode: PrimitiveMode,
+        first: usize,
+        count: usize,
+        instance_count: usize,
+    ) {
+        self.bind();
+        unsafe {
+            gl::DrawArraysInstanced(
+                mode as GLenum,
+                first as GLint,
+                count as GLsizei,
+                instance_count as GLsizei,
+            )
+        };
+    }
+
+    /// Draw `count` indices starting from index `first`.
+    ///
+    /// wrap `glDrawElements`
+    pub fn draw_elements(
+        &self,
+        mode: PrimitiveMode,
+        count: usize,
+        index_type: IndexType,
+        first: usize,
+    ) {
+        self.bind();
+        unsafe {
+            gl::DrawElements(
+                mode as GLenum,
+                count as GLsizei,
+                index_type as GLenum,
+                index_offset(index_type, first),
+            )
+        };
+    }
+
+    /// Draw `instance_count` instances of `count` indices starting from index `first`.
+    ///
+    /// wrap `glDrawElementsInstanced`
+    pub fn draw_elements_instanced(
+        &self,
+        mode: PrimitiveMode,
+        count: usize,
+        index_type: IndexType,
+        first: usize,
+        instance_count: usize,
+    ) {
+        self.bind();
+        unsafe {
+            gl::DrawElementsInstanced(
+                mode as GLenum,
+                count as GLsizei,
+                index_type as GLenum,
+                index_offset(index_type, first),
+                instance_count as GLsizei,
+            )
+        };
+    }
+
+    /// Draw `count` indices starting from index `first`, and `base_vertex` is added to each index. So meshes packed in
+    /// the same buffers can keep their own indices. Requires OpenGL 3.2
+    ///
+    /// wrap `glDrawElementsBaseVertex`
+    pub fn draw_elements_base_vertex(
+        &self,
+        mode: PrimitiveMode,
+        count: usize,
+        index_type: IndexType,
+        first: usize,
+        base_vertex: i32,
+    ) {
+        self.bind();
+        unsafe {
+            gl::DrawElementsBaseVertex(
+                mode as GLenum,
+                count as GLsizei,
+                index_type as GLenum,
+                index_offset(index_type, first),
+                base_vertex,
+            )
+        };
+    }
+
+    /// Instanced version of `draw_elements_base_vertex()`. Requires OpenGL 3.2
+    ///
+    /// wrap `glDrawElementsInstancedBaseVertex`
+    pub fn draw_elements_instanced_base_vertex(
+        &self,
+        mode: PrimitiveMode,
+        count: usize,
+        index_type: IndexType,
+        first: usize,
+        instance_count: usize,
+        base_vertex: i32,
+    ) {
+        self.bind();
+        unsafe {
+            gl::DrawElementsInstancedBaseVertex(
+                mode as GLenum,
+                count as GLsizei,
+                index_type as GLenum,
+                index_offset(index_type, first),
+                instance_count as GLsizei,
+                base_vertex,
+            )
+        };
+    }
+
+    /// Draw multiple ranges of vertices in one call, range `i` is `counts[i]` vertices from vertex `firsts[i]`.
+    ///
+    /// wrap `glMultiDrawArrays`
+    pub fn multi_draw_arrays(&self, mode: PrimitiveMode, firsts: &[GLint], counts: &[GLsizei]) {
+        assert_eq!(firsts.len(), counts.len());
+
+        self.bind();
+        unsafe {
+            gl::MultiDrawArrays(
+                mode as GLenum,
+                firsts.as_ptr(),
+                counts.as_ptr(),
+                counts.len() as GLsizei,
+            )
+        };
+    }
+
+    /// Draw multiple ranges of indices in one call, range `i` is `counts[i]` indices from index `firsts[i]`.
+    ///
+    /// wrap `glMultiDrawElements`
+    pub fn multi_draw_elements(
+        &self,
+        mode: PrimitiveMode,
+        counts: &[GLsizei],
+        index_type: IndexType,
+        firsts: &[usize],
+    ) {
+        assert_eq!(firsts.len(), counts.len());
+        let offsets: Vec<_> = firsts
+            .iter()
+            .map(|&first| index_offset(index_type, first))
+            .collect();
+
+        self.bind();
+        unsafe {
+            gl::MultiDrawElements(
+                mode as GLenum,
+                counts.as_ptr(),
+                index_type as GLenum,
+                offsets.as_ptr(),
+                counts.len() as GLsizei,
+            )
+        };
+    }
+
+    /// Same as `multi_draw_elements()`, and `base_vertices[i]` is added to indices of range `i`. Requires OpenGL 3.2
+    ///
+    /// wrap `glMultiDrawElementsBaseVertex`
+    pub fn multi_draw_elements_base_vertex(
+        &self,
+        mode: PrimitiveMode,
+        counts: &[GLsizei],
+        index_type: IndexType,
+        firsts: &[usize],
+        base_vertices: &[GLint],
+    ) {
+        assert_eq!(firsts.len(), counts.len());
+        assert_eq!(base_vertices.len(), counts.len());
+        let offsets: Vec<_> = firsts
+            .iter()
+            .map(|&first| index_offset(index_type, first))
+            .collect();
+
+        self.bind();
+        unsafe {
+            gl::MultiDrawElementsBaseVertex(
+                mode as GLenum,
+                counts.as_ptr(),
+                index_type as GLenum,
+                offsets.as_ptr(),
+                counts.len() as GLsizei,
+                base_vertices.as_ptr(),
+            )
+        };
+    }
+}
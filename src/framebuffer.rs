@@ -0,0 +1,674 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use anyhow::bail;
+use gl::types::*;
+use image::{DynamicImage, Rgb32FImage};
+use thiserror::Error;
+
+use crate::{get_gl_error, read_pixels, read_pixels_hdr, Texture, TextureType};
+
+/// Id of framebuffer which is treated as the default one. It's 0 (framebuffer of window) unless a headless context
+/// redirects it to its offscreen framebuffer.
+static DEFAULT_FRAMEBUFFER: AtomicU32 = AtomicU32::new(0);
+
+/// Error of [Framebuffer Completeness](https://www.khronos.org/opengl/wiki/Framebuffer_Object#Framebuffer_Completeness),
+/// mapped from the result of `glCheckFramebufferStatus`.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramebufferError {
+    /// 0x8219, Given when the specified framebuffer is the default read or draw framebuffer, but the default framebuffer
+    /// does not exist.
+    #[error("(0x8219)Default framebuffer does not exist")]
+    Undefined,
+    /// 0x8CD6, Given when any of the framebuffer attachment points are framebuffer incomplete.
+    #[error("(0x8CD6)Some attachment is incomplete")]
+    IncompleteAttachment,
+    /// 0x8CD7, Given when the framebuffer does not have at least one image attached to it.
+    #[error("(0x8CD7)No image is attached to framebuffer")]
+    MissingAttachment,
+    /// 0x8CDB, Given when the value of `GL_FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE` is `GL_NONE` for any color attachment
+    /// point(s) named by `GL_DRAW_BUFFERi`.
+    #[error("(0x8CDB)Draw buffer names an attachment point without image")]
+    IncompleteDrawBuffer,
+    /// 0x8CDC, Given when `GL_READ_BUFFER` is not `GL_NONE` and the value of `GL_FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE` is
+    /// `GL_NONE` for the color attachment point named by `GL_READ_BUFFER`.
+    #[error("(0x8CDC)Read buffer names an attachment point without image")]
+    IncompleteReadBuffer,
+    /// 0x8CDD, Given when the combination of internal formats of the attached images violates an implementation-dependent
+    /// set of restrictions.
+    #[error("(0x8CDD)Combination of attachment formats is unsupported")]
+    Unsupported,
+    /// 0x8D56, Given when the number of samples is not the same for all attached images.
+    #[error("(0x8D56)Attachments have different sample counts")]
+    IncompleteMultisample,
+    /// 0x8DA8, Given when any framebuffer attachment is layered, and any populated attachment is not layered, or if all
+    /// populated color attachments are not from textures of the same target.
+    #[error("(0x8DA8)Attachments are not layered consistently")]
+    IncompleteLayerTargets,
+    /// Status not listed above, e.g. 0 returned by `glCheckFramebufferStatus` when an error occurs.
+    #[error("(0x{0:X})Unknown framebuffer status")]
+    Unknown(GLenum),
+}
+
+/// Enum of internal formats for color attachments and float textures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorFormat {
+    R8,
+    RG8,
+    RGB8,
+    RGBA8,
+    /// sRGB encoded on writes when `set_framebuffer_srgb(true)`, and decoded when sampled.
+    SRGB8,
+    /// sRGB encoded on writes when `set_framebuffer_srgb(true)`, and decoded when sampled. Alpha is linear.
+    SRGB8Alpha8,
+    R16F,
+    RG16F,
+    RGB16F,
+    RGBA16F,
+    R32F,
+    RG32F,
+    RGB32F,
+    RGBA32F,
+}
+
+impl ColorFormat {
+    /// Get (internal format, pixel format, pixel type) for `glTexImage2D`.
+    pub fn gl_formats(&self) -> (GLenum, GLenum, GLenum) {
+        match self {
+            ColorFormat::R8 => (gl::R8, gl::RED, gl::UNSIGNED_BYTE),
+            ColorFormat::RG8 => (gl::RG8, gl::RG, gl::UNSIGNED_BYTE),
+            ColorFormat::RGB8 => (gl::RGB8, gl::RGB, gl::UNSIGNED_BYTE),
+            ColorFormat::RGBA8 => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
+            ColorFormat::SRGB8 => (gl::SRGB8, gl::RGB, gl::UNSIGNED_BYTE),
+            ColorFormat::SRGB8Alpha8 => (gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE),
+            ColorFormat::R16F => (gl::R16F, gl::RED, gl::FLOAT),
+            ColorFormat::RG16F => (gl::RG16F, gl::RG, gl::FLOAT),
+            ColorFormat::RGB16F => (gl::RGB16F, gl::RGB, gl::FLOAT),
+            ColorFormat::RGBA16F => (gl::RGBA16F, gl::RGBA, gl::FLOAT),
+            ColorFormat::R32F => (gl::R32F, gl::RED, gl::FLOAT),
+            ColorFormat::RG32F => (gl::RG32F, gl::RG, gl::FLOAT),
+            ColorFormat::RGB32F => (gl::RGB32F, gl::RGB, gl::FLOAT),
+            ColorFormat::RGBA32F => (gl::RGBA32F, gl::RGBA, gl::FLOAT),
+        }
+    }
+}
+
+/// Enable or disable linear to sRGB conversion of colors written to sRGB framebuffers, i.e. attachments of
+/// `ColorFormat::SRGB8`/`SRGB8Alpha8` and default framebuffers of windows & headless context. Shaders then output
+/// linear colors without gamma correction.
+///
+/// wrap `glEnable`/`glDisable` with `GL_FRAMEBUFFER_SRGB`
+pub fn set_framebuffer_srgb(enabled: bool) {
+    unsafe {
+        if enabled {
+            gl::Enable(gl::FRAMEBUFFER_SRGB);
+        } else {
+            gl::Disable(gl::FRAMEBUFFER_SRGB);
+        }
+    }
+}
+
+/// Enum of internal formats for depth and/or stencil attachments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthStencilFormat {
+    Depth16,
+    Depth24,
+    Depth32F,
+    Depth24Stencil8,
+    Depth32FStencil8,
+    Stencil8,
+}
+
+impl DepthStencilFormat {
+    /// Get (internal format, pixel format, pixel type) for `glTexImage2D`.
+    pub fn gl_formats(&self) -> (GLenum, GLenum, GLenum) {
+        match self {
+            DepthStencilFormat::Depth16 => (gl::DEPTH_COMPONENT16, gl::DEPTH_COMPONENT, gl::FLOAT),
+            DepthStencilFormat::Depth24 => (gl::DEPTH_COMPONENT24, gl::DEPTH_COMPONENT, gl::FLOAT),
+            DepthStencilFormat::Depth32F => {
+                (gl::DEPTH_COMPONENT32F, gl::DEPTH_COMPONENT, gl::FLOAT)
+            }
+            DepthStencilFormat::Depth24Stencil8 => (
+                gl::DEPTH24_STENCIL8,
+                gl::DEPTH_STENCIL,
+                gl::UNSIGNED_INT_24_8,
+            ),
+            DepthStencilFormat::Depth32FStencil8 => (
+                gl::DEPTH32F_STENCIL8,
+                gl::DEPTH_STENCIL,
+                gl::FLOAT_32_UNSIGNED_INT_24_8_REV,
+            ),
+            DepthStencilFormat::Stencil8 => {
+                (gl::STENCIL_INDEX8, gl::STENCIL_INDEX, gl::UNSIGNED_BYTE)
+            }
+        }
+    }
+
+    /// Get the framebuffer attachment point which matches this format.
+    pub fn attachment_point(&self) -> GLenum {
+        match self {
+            DepthStencilFormat::Depth16
+            | DepthStencilFormat::Depth24
+            | DepthStencilFormat::Depth32F => gl::DEPTH_ATTACHMENT,
+            DepthStencilFormat::Depth24Stencil8 | DepthStencilFormat::Depth32FStencil8 => {
+                gl::DEPTH_STENCIL_ATTACHMENT
+            }
+            DepthStencilFormat::Stencil8 => gl::STENCIL_ATTACHMENT,
+        }
+    }
+}
+
+/// Wrapper of [Renderbuffer Object](https://www.khronos.org/opengl/wiki/Renderbuffer_Object)
+pub struct Renderbuffer {
+    pub id: GLuint,
+    pub format: DepthStencilFormat,
+}
+
+impl Renderbuffer {
+    /// Try to create a Renderbuffer Object and allocate its storage.
+    ///
+    /// wrap `glGenRenderbuffers` & `glRenderbufferStorage`
+    pub fn new(format: DepthStencilFormat, width: u32, height: u32) -> anyhow::Result<Self> {
+        let mut rbo = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut rbo);
+        }
+
+        if rbo == 0 {
+            return Err(get_gl_error().unwrap().into());
+        }
+
+        let renderbuffer = Self { id: rbo, format };
+        renderbuffer.set_storage(width, height)?;
+
+        Ok(renderbuffer)
+    }
+
+    /// (Re)allocate storage of this Renderbuffer Object.
+    ///
+    /// wrap `glRenderbufferStorage`
+    pub fn set_storage(&self, width: u32, height: u32) -> anyhow::Result<()> {
+        unsafe {
+            gl::BindRenderbuffer(gl::RENDERBUFFER, self.id);
+            gl::RenderbufferStorage(
+                gl::RENDERBUFFER,
+                self.format.gl_formats().0,
+                width.try_into()?,
+                height.try_into()?,
+            );
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteRenderbuffers(1, &self.id) }
+    }
+}
+
+/// Description of a color attachment which will be created by [`FramebufferBuilder`].
+#[derive(Debug, Clone, Copy)]
+struct ColorAttachmentDesc {
+    format: ColorFormat,
+    filter: GLenum,
+}
+
+/// Description of a depth/stencil attachment which will be created by [`FramebufferBuilder`].
+#[derive(Debug, Clone, Copy)]
+enum DepthStencilAttachmentDesc {
+    Texture(DepthStencilFormat),
+    Renderbuffer(DepthStencilFormat),
+}
+
+/// Depth/stencil attachment owned by a [`Framebuffer`].
+enum DepthStencilAttachment {
+    Texture(Texture, DepthStencilFormat),
+    Renderbuffer(Renderbuffer),
+}
+
+/// Builder of [`Framebuffer`].
+///
+/// ```ignore
+/// let gbuffer = Framebuffer::builder(width, height)
+///     .with_color_texture(ColorFormat::RGBA16F, gl::NEAREST) // position
+///     .with_color_texture(ColorFormat::RGBA16F, gl::NEAREST) // normal
+///     .with_color_texture(ColorFormat::RGBA8, gl::NEAREST) // albedo & specular
+///     .with_depth_renderbuffer(DepthStencilFormat::Depth24)
+///     .build()?;
+/// ```
+pub struct FramebufferBuilder {
+    width: u32,
+    height: u32,
+    color_attachments: Vec<ColorAttachmentDesc>,
+    depth_stencil_attachment: Option<DepthStencilAttachmentDesc>,
+}
+
+impl FramebufferBuilder {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            color_attachments: Vec::new(),
+            depth_stencil_attachment: None,
+        }
+    }
+
+    /// Add a texture as next color attachment (`GL_COLOR_ATTACHMENT0 + n`).
+    ///
+    /// `filter` is used as both min & mag filter, wrapping is `CLAMP_TO_EDGE`.
+    pub fn with_color_texture(mut self, format: ColorFormat, filter: GLenum) -> Self {
+        self.color_attachments
+            .push(ColorAttachmentDesc { format, filter });
+        self
+    }
+
+    /// Use a texture as depth/stencil attachment, so that it can be sampled later (e.g. shadow map).
+    ///
+    /// Texture is created with `NEAREST` filter and `CLAMP_TO_BORDER` wrapping with a white border, which means
+    /// "farthest depth" outside of the texture.
+    pub fn with_depth_texture(mut self, format: DepthStencilFormat) -> Self {
+        self.depth_stencil_attachment = Some(DepthStencilAttachmentDesc::Texture(format));
+        self
+    }
+
+    /// Use a renderbuffer as depth/stencil attachment, which is write-only but faster.
+    pub fn with_depth_renderbuffer(mut self, format: DepthStencilFormat) -> Self {
+        self.depth_stencil_attachment = Some(DepthStencilAttachmentDesc::Renderbuffer(format));
+        self
+    }
+
+    /// Create Framebuffer Object with all attachments and check its completeness.
+    ///
+    /// It'll bind default framebuffer back after building.
+    pub fn build(self) -> anyhow::Result<Framebuffer> {
+        let mut fbo = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+        }
+        if fbo == 0 {
+            return Err(get_gl_error().unwrap().into());
+        }
+
+        let mut framebuffer = Framebuffer {
+            id: fbo,
+            width: self.width,
+            height: self.height,
+            color_textures: Vec::with_capacity(self.color_attachments.len()),
+            color_formats: Vec::with_capacity(self.color_attachments.len()),
+            depth_stencil: None,
+        };
+        framebuffer.bind();
+
+        // Create & Attach color textures
+        for (index, desc) in self.color_attachments.iter().enumerate() {
+            let texture = Texture::new(PathBuf::new(), TextureType::Unknown)?;
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, texture.id);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, desc.filter as GLint);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, desc.filter as GLint);
+                gl::TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_WRAP_S,
+                    gl::CLAMP_TO_EDGE as GLint,
+                );
+                gl::TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_WRAP_T,
+                    gl::CLAMP_TO_EDGE as GLint,
+                );
+            }
+            Framebuffer::alloc_texture_2d(
+                &texture,
+                desc.format.gl_formats(),
+                self.width,
+                self.height,
+            )?;
+            unsafe {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0 + index as GLenum,
+                    gl::TEXTURE_2D,
+                    texture.id,
+                    0,
+                );
+            }
+
+            framebuffer.color_textures.push(texture);
+            framebuffer.color_formats.push(desc.format);
+        }
+
+        // Create & Attach depth/stencil buffer
+        match self.depth_stencil_attachment {
+            Some(DepthStencilAttachmentDesc::Texture(format)) => {
+                let texture = Texture::new(PathBuf::new(), TextureType::Unknown)?;
+                unsafe {
+                    gl::BindTexture(gl::TEXTURE_2D, texture.id);
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
+                    gl::TexParameteri(
+                        gl::TEXTURE_2D,
+                        gl::TEXTURE_WRAP_S,
+                        gl::CLAMP_TO_BORDER as GLint,
+                    );
+                    gl::TexParameteri(
+                        gl::TEXTURE_2D,
+                        gl::TEXTURE_WRAP_T,
+                        gl::CLAMP_TO_BORDER as GLint,
+                    );
+                    let border_color: [GLfloat; 4] = [1.0, 1.0, 1.0, 1.0];
+                    gl::TexParameterfv(
+                        gl::TEXTURE_2D,
+                        gl::TEXTURE_BORDER_COLOR,
+                        border_color.as_ptr(),
+                    );
+                }
+                Framebuffer::alloc_texture_2d(
+                    &texture,
+                    format.gl_formats(),
+                    self.width,
+                    self.height,
+                )?;
+                unsafe {
+                    gl::FramebufferTexture2D(
+                        gl::FRAMEBUFFER,
+                        format.attachment_point(),
+                        gl::TEXTURE_2D,
+                        texture.id,
+                        0,
+                    );
+                }
+
+                framebuffer.depth_stencil = Some(DepthStencilAttachment::Texture(texture, format));
+            }
+            Some(DepthStencilAttachmentDesc::Renderbuffer(format)) => {
+                let renderbuffer = Renderbuffer::new(format, self.width, self.height)?;
+                unsafe {
+                    gl::FramebufferRenderbuffer(
+                        gl::FRAMEBUFFER,
+                        format.attachment_point(),
+                        gl::RENDERBUFFER,
+                        renderbuffer.id,
+                    );
+                }
+
+                framebuffer.depth_stencil =
+                    Some(DepthStencilAttachment::Renderbuffer(renderbuffer));
+            }
+            None => (),
+        }
+
+        // Tell OpenGL which color attachments we'll use (of this framebuffer) for rendering
+        match framebuffer.color_textures.len() {
+            0 => unsafe {
+                // Depth-only framebuffer (e.g. shadow map) has no color buffer to draw or read.
+                gl::DrawBuffer(gl::NONE);
+                gl::ReadBuffer(gl::NONE);
+            },
+            1 => (),
+            n => {
+                let attachments: Vec<GLenum> = (0..n as GLenum)
+                    .map(|i| gl::COLOR_ATTACHMENT0 + i)
+                    .collect();
+                unsafe {
+                    gl::DrawBuffers(attachments.len() as GLsizei, attachments.as_ptr());
+                }
+            }
+        }
+
+        // Check framebuffer status
+        let status = framebuffer.check_status();
+        Framebuffer::bind_default();
+        status?;
+
+        Ok(framebuffer)
+    }
+}
+
+/// Wrapper of [Framebuffer Object](https://www.khronos.org/opengl/wiki/Framebuffer_Object)
+///
+/// Framebuffer owns all of its attachments, they'll be deleted together with the Framebuffer Object.
+pub struct Framebuffer {
+    pub id: GLuint,
+    width: u32,
+    height: u32,
+    color_textures: Vec<Texture>,
+    color_formats: Vec<ColorFormat>,
+    depth_stencil: Option<DepthStencilAttachment>,
+}
+
+impl Framebuffer {
+    /// Start building a Framebuffer with given size of all attachments.
+    pub fn builder(width: u32, height: u32) -> FramebufferBuilder {
+        FramebufferBuilder::new(width, height)
+    }
+
+    /// Bind this Framebuffer Object as both read & draw framebuffer.
+    ///
+    /// wrap `glBindFramebuffer`
+    pub fn bind(&self) {
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, self.id) }
+    }
+
+    /// Bind this Framebuffer Object and set viewport to its size.
+    ///
+    /// wrap `glBindFramebuffer` & `glViewport`
+    pub fn bind_with_viewport(&self) {
+        self.bind();
+        unsafe { gl::Viewport(0, 0, self.width as GLsizei, self.height as GLsizei) }
+    }
+
+    /// Bind default framebuffer (of window, or offscreen framebuffer of headless context) back.
+    ///
+    /// wrap `glBindFramebuffer`
+    pub fn bind_default() {
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, Self::default_id()) }
+    }
+
+    /// Get id of default framebuffer.
+    pub fn default_id() -> GLuint {
+        DEFAULT_FRAMEBUFFER.load(Ordering::Relaxed)
+    }
+
+    /// Redirect default framebuffer to `id`, it's used by headless context.
+    pub(crate) fn set_default_id(id: GLuint) {
+        DEFAULT_FRAMEBUFFER.store(id, Ordering::Relaxed);
+    }
+
+    /// Check completeness of this framebuffer, it'll call `bind()` automatically.
+    ///
+    /// wrap `glCheckFramebufferStatus`
+    pub fn check_status(&self) -> Result<(), FramebufferError> {
+        self.bind();
+        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        match status {
+            gl::FRAMEBUFFER_COMPLETE => Ok(()),
+            gl::FRAMEBUFFER_UNDEFINED => Err(FramebufferError::Undefined),
+            gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => Err(FramebufferError::IncompleteAttachment),
+            gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => {
+                Err(FramebufferError::MissingAttachment)
+            }
+            gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => Err(FramebufferError::IncompleteDrawBuffer),
+            gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => Err(FramebufferError::IncompleteReadBuffer),
+            gl::FRAMEBUFFER_UNSUPPORTED => Err(FramebufferError::Unsupported),
+            gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => Err(FramebufferError::IncompleteMultisample),
+            gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => {
+                Err(FramebufferError::IncompleteLayerTargets)
+            }
+            _ => Err(FramebufferError::Unknown(status)),
+        }
+    }
+
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Get texture of color attachment `GL_COLOR_ATTACHMENT0 + index`.
+    pub fn get_color_texture(&self, index: usize) -> Option<&Texture> {
+        self.color_textures.get(index)
+    }
+
+    /// Get all textures of color attachments.
+    pub fn get_color_textures(&self) -> &[Texture] {
+        &self.color_textures
+    }
+
+    /// Get texture of depth/stencil attachment, `None` if it's a renderbuffer or not exists.
+    pub fn get_depth_texture(&self) -> Option<&Texture> {
+        match &self.depth_stencil {
+            Some(DepthStencilAttachment::Texture(texture, _)) => Some(texture),
+            _ => None,
+        }
+    }
+
+    /// Attach an image of other texture (e.g. a face or mip level of cubemap) to color attachment `index`.
+    /// It'll call `bind()` automatically.
+    ///
+    /// Note: texture which is attached by this method is still owned by caller. If this framebuffer is built without
+    /// color attachments, draw buffer will be switched to the new attachment.
+    ///
+    /// wrap `glFramebufferTexture2D`
+    pub fn attach_color_target(
+        &self,
+        index: u32,
+        tex_target: GLenum,
+        texture: &Texture,
+        level: GLint,
+    ) {
+        self.bind();
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0 + index,
+                tex_target,
+                texture.id,
+                level,
+            );
+            if self.color_textures.is_empty() {
+                gl::DrawBuffer(gl::COLOR_ATTACHMENT0 + index);
+            }
+        }
+    }
+
+    /// Reallocate all owned attachments with new size, e.g. when window is resized.
+    /// Content of attachments is undefined after resizing.
+    pub fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        for (texture, format) in self.color_textures.iter().zip(self.color_formats.iter()) {
+            Self::alloc_texture_2d(texture, format.gl_formats(), width, height)?;
+        }
+        match &self.depth_stencil {
+            Some(DepthStencilAttachment::Texture(texture, format)) => {
+                Self::alloc_texture_2d(texture, format.gl_formats(), width, height)?;
+            }
+            Some(DepthStencilAttachment::Renderbuffer(renderbuffer)) => {
+                renderbuffer.set_storage(width, height)?;
+            }
+            None => (),
+        }
+
+        self.width = width;
+        self.height = height;
+
+        Ok(())
+    }
+
+    /// Read color attachment `index` back as 8-bit RGBA image, e.g. for screenshots or image comparison.
+    ///
+    /// wrap `glReadBuffer` & `glReadPixels`
+    pub fn read_color_attachment(&self, index: u32) -> anyhow::Result<DynamicImage> {
+        self.bind_read_buffer(index);
+        let image = read_pixels(0, 0, self.width, self.height);
+        Self::unbind_read_buffer()?;
+
+        Ok(image)
+    }
+
+    /// Read color attachment `index` back as 32-bit float RGB image, which keeps HDR values of float attachments.
+    ///
+    /// wrap `glReadBuffer` & `glReadPixels`
+    pub fn read_color_attachment_hdr(&self, index: u32) -> anyhow::Result<Rgb32FImage> {
+        self.bind_read_buffer(index);
+        let image = read_pixels_hdr(0, 0, self.width, self.height);
+        Self::unbind_read_buffer()?;
+
+        Ok(image)
+    }
+
+    fn bind_read_buffer(&self, index: u32) {
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.id);
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0 + index);
+        }
+    }
+
+    fn unbind_read_buffer() -> anyhow::Result<()> {
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, Self::default_id());
+        }
+        if let Some(e) = get_gl_error() {
+            bail!("Failed to read color attachment: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Copy a block of pixels from this framebuffer to other framebuffer (`None` means the default framebuffer).
+    /// The whole area of this framebuffer is copied to the same area of target.
+    ///
+    /// wrap `glBlitFramebuffer`
+    pub fn blit_to(&self, target: Option<&Framebuffer>, mask: GLbitfield, filter: GLenum) {
+        let (width, height) = (self.width as GLint, self.height as GLint);
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.id);
+            gl::BindFramebuffer(
+                gl::DRAW_FRAMEBUFFER,
+                target.map_or(Self::default_id(), |fb| fb.id),
+            );
+            gl::BlitFramebuffer(0, 0, width, height, 0, 0, width, height, mask, filter);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, Self::default_id());
+        }
+    }
+
+    /// Allocate storage of 2D texture without data.
+    fn alloc_texture_2d(
+        texture: &Texture,
+        (internal_format, format, pixel_type): (GLenum, GLenum, GLenum),
+        width: u32,
+        height: u32,
+    ) -> anyhow::Result<()> {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, texture.id);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal_format as GLint,
+                width.try_into()?,
+                height.try_into()?,
+                0,
+                format,
+                pixel_type,
+                core::ptr::null(),
+            );
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        let texture_ids: Vec<GLuint> = self
+            .color_textures
+            .iter()
+            .chain(self.get_depth_texture())
+            .map(|texture| texture.id)
+            .collect();
+        unsafe {
+            gl::DeleteTextures(texture_ids.len() as GLsizei, texture_ids.as_ptr());
+            gl::DeleteFramebuffers(1, &self.id);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Golden-image regression testing: render examples headlessly and compare them with reference images.
+//!
+//! An example renders into a headless context instead of window when `LEARN_GOLDEN_OUTPUT` is set, see `GoldenRun`.
+//! The harness in `tests/golden.rs` runs all examples in this mode and compares results by `compare_images`.
+
+use std::path::PathBuf;
+
+use anyhow::bail;
+use image::{Rgba, RgbaImage};
+use tracing::info;
+
+use crate::HeadlessContext;
+
+/// Env var of path where the captured frame is saved. Examples run in golden mode if it's set.
+pub const GOLDEN_OUTPUT_ENV: &str = "LEARN_GOLDEN_OUTPUT";
+/// Env var of number of frames rendered before capturing, default is `GOLDEN_DEFAULT_FRAMES`.
+pub const GOLDEN_FRAMES_ENV: &str = "LEARN_GOLDEN_FRAMES";
+
+pub const GOLDEN_DEFAULT_FRAMES: u32 = 3;
+/// Fixed delta time of each frame, so that animations are deterministic.
+pub const GOLDEN_FRAME_TIME: f32 = 1.0 / 60.0;
+/// Fixed size of offscreen render target.
+pub const GOLDEN_WIDTH: u32 = 400;
+pub const GOLDEN_HEIGHT: u32 = 300;
+
+/// Render a fixed number of frames into headless context, then save the last one as image.
+pub struct GoldenRun {
+    output: PathBuf,
+    frames: u32,
+}
+
+impl GoldenRun {
+    /// Get golden run settings from env vars, return `None` if it's not in golden mode.
+    pub fn from_env() -> Option<Self> {
+        let output = PathBuf::from(std::env::var_os(GOLDEN_OUTPUT_ENV)?);
+        let frames = std::env::var(GOLDEN_FRAMES_ENV)
+            .ok()
+            .and_then(|frames| frames.parse().ok())
+            .unwrap_or(GOLDEN_DEFAULT_FRAMES);
+
+        Some(Self { output, frames })
+    }
+
+    /// Create headless context with fixed size.
+    pub fn create_context(&self) -> anyhow::Result<HeadlessContext> {
+        HeadlessContext::new(GOLDEN_WIDTH, GOLDEN_HEIGHT)
+    }
+
+    /// Call `redraw` with fixed delta time for each frame, then save content of offscreen framebuffer to output path.
+    pub fn run<F>(&self, ctx: &HeadlessContext, mut redraw: F) -> anyhow::Result<()>
+    where
+        F: FnMut(f32) -> anyhow::Result<()>,
+    {
+        for _ in 0..self.frames {
+            ctx.bind();
+            redraw(GOLDEN_FRAME_TIME)?;
+        }
+
+        let image = ctx.get_framebuffer().read_color_attachment(0)?;
+        if let Some(dir) = self.output.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        image.save(&self.output)?;
+        info!("Save golden frame to {}", self.output.display());
+
+        Ok(())
+    }
+}
+
+/// Difference between two images.
+pub struct ImageDiff {
+    /// Root-mean-square error of each RGBA channel, in range [0, 1].
+    pub rmse: [f32; 4],
+    /// Amplified absolute difference of each pixel, for visual inspection.
+    pub diff_image: RgbaImage,
+}
+
+impl ImageDiff {
+    /// Max RMSE among all channels.
+    pub fn max_rmse(&self) -> f32 {
+        self.rmse.iter().copied().fold(0.0, f32::max)
+    }
+}
+
+/// Compare two images of the same size by per-channel RMSE.
+pub fn compare_images(actual: &RgbaImage, expected: &RgbaImage) -> anyhow::Result<ImageDiff> {
+    if actual.dimensions() != expected.dimensions() {
+        bail!(
+            "Image size mismatch: actual={:?}, expected={:?}",
+            actual.dimensions(),
+            expected.dimensions()
+        );
+    }
+
+    let mut sum_sq = [0f64; 4];
+    let mut diff_image = RgbaImage::new(actual.width(), actual.height());
+    for ((a, e), d) in actual
+        .pixels()
+        .zip(expected.pixels())
+        .zip(diff_image.pixels_mut())
+    {
+        let mut diff = [0u8; 4];
+        for c in 0..4 {
+            let delta = (a[c] as i32 - e[c] as i32).unsigned_abs();
+            sum_sq[c] += (delta as f64 / 255.0).powi(2);
+            diff[c] = (delta * 4).min(255) as u8;
+        }
+        *d = Rgba([diff[0], diff[1], diff[2], 255]);
+    }
+
+    let pixel_count = (actual.width() * actual.height()).max(1) as f64;
+    let rmse = sum_sq.map(|sum| (sum / pixel_count).sqrt() as f32);
+
+    Ok(ImageDiff { rmse, diff_image })
+}
@@ -0,0 +1,110 @@
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::mem;
+
+use gl::types::*;
+use nalgebra as na;
+
+use crate::{impl_vertex_layout, Buffer, BufferType, BufferUsage, VertexArray, VertexLayout};
+
+/// Location of first per-instance attribute of `Mesh` & `Model`, leaving room for attributes of `Vertex`.
+pub const MESH_INSTANCE_LOCATION: GLuint = 8;
+
+/// Vertex Buffer Object of per-instance attributes, whose layout is `T::vertex_description()` with divisor 1.
+///
+/// Attach it to Vertex Array Objects by `bind_to()`, then update it whenever instances change, e.g. every frame.
+pub struct InstanceBuffer<T: VertexLayout> {
+    buffer: Buffer,
+    len: Cell<usize>,
+    /// Number of instances the data store can hold.
+    capacity: Cell<usize>,
+    usage: BufferUsage,
+    _marker: PhantomData<T>,
+}
+
+impl<T: VertexLayout> InstanceBuffer<T> {
+    pub fn new(instances: &[T], usage: BufferUsage) -> anyhow::Result<Self> {
+        let buffer = Buffer::new(BufferType::VertexBuffer)?;
+        buffer.set_buffer_data(instances, usage);
+
+        Ok(Self {
+            buffer,
+            len: Cell::new(instances.len()),
+            capacity: Cell::new(instances.len()),
+            usage,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Replace all instances.
+    ///
+    /// Data store is re-allocated only if it's too small, otherwise it's orphaned and updated in place, so that
+    /// pending draws of previous instances don't stall.
+    ///
+    /// wrap `glBufferData`, `glBufferSubData`
+    pub fn update(&self, instances: &[T]) {
+        if instances.len() > self.capacity.get() {
+            self.buffer.set_buffer_data(instances, self.usage);
+            self.capacity.set(instances.len());
+        } else {
+            self.buffer.orphan();
+            self.buffer.set_buffer_sub_data(0, instances);
+        }
+        self.len.set(instances.len());
+    }
+
+    /// Set per-instance attributes of `vao` at locations from `first_location`, see `MESH_INSTANCE_LOCATION`.
+    pub fn bind_to(&self, vao: &VertexArray, first_location: GLuint) {
+        let mut desc = T::vertex_description();
+        desc.set_first_location(first_location);
+        desc.set_divisor(1);
+        desc.bind_to(&self.buffer, Some(vao));
+        vao.unbind();
+    }
+
+    /// Number of instances.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// Size of data store in bytes.
+    pub fn get_size(&self) -> usize {
+        self.capacity.get() * mem::size_of::<T>()
+    }
+
+    pub fn get_buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+}
+
+/// Common per-instance attributes: model matrix and color.
+///
+/// In shader, they are `layout (location = 8) in mat4 instance_model;` (location 8-11) and
+/// `layout (location = 12) in vec4 instance_color;` when bound at `MESH_INSTANCE_LOCATION`.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ModelInstance {
+    pub model: na::Matrix4<f32>,
+    pub color: na::Vector4<f32>,
+}
+
+impl_vertex_layout!(ModelInstance { model, color });
+
+impl ModelInstance {
+    /// Create instance of white color.
+    pub fn new(model: na::Matrix4<f32>) -> Self {
+        Self {
+            model,
+            color: na::Vector4::new(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn with_color(mut self, color: na::Vector4<f32>) -> Self {
+        self.color = color;
+        self
+    }
+}
@@ -1,25 +1,64 @@
 mod buffer;
 mod camera;
+mod draw;
+mod framebuffer;
+#[cfg(not(target_os = "macos"))]
+mod golden;
+mod instance_buffer;
 mod light;
+mod material_mtl;
+mod material_pbr;
 mod material_phong;
 mod mesh;
 mod model;
+pub mod primitives;
+mod ring_buffer;
+mod sampler;
+mod scene;
+mod screenshot;
 mod shader;
+mod shader_reload;
+mod std140;
+mod tangent;
 mod texture;
+mod uniform;
+mod uniform_buffer;
 mod utils;
 mod vertex;
+mod window;
 mod window_glfw;
+#[cfg(not(target_os = "macos"))]
+mod window_headless;
 mod window_winit;
 
 pub use buffer::*;
 pub use camera::*;
+pub use draw::*;
+pub use framebuffer::*;
+#[cfg(not(target_os = "macos"))]
+pub use golden::*;
+pub use instance_buffer::*;
 pub use light::*;
+pub use material_mtl::*;
+pub use material_pbr::*;
 pub use material_phong::*;
 pub use mesh::*;
 pub use model::*;
+pub use ring_buffer::*;
+pub use sampler::*;
+pub use scene::*;
+pub use screenshot::*;
 pub use shader::*;
+pub use shader_reload::*;
+pub use std140::*;
+pub use tangent::*;
 pub use texture::*;
+pub use uniform::*;
+pub use uniform_buffer::*;
 pub use utils::*;
 pub use vertex::*;
+pub use window::*;
 pub use window_glfw::*;
+#[cfg(not(target_os = "macos"))]
+pub use window_headless::*;
 pub use window_winit::*;
@@ -0,0 +1,72 @@
+use nalgebra as na;
+
+use crate::Texture;
+
+/// Shininess of materials without `Ns`.
+pub(crate) const DEFAULT_SHININESS: f32 = 128.0;
+
+/// Blinn-Phong material of Wavefront MTL file, e.g. loaded with OBJ model.
+///
+/// Colors are used by shaders alone when the corresponding maps are absent.
+#[derive(Debug, Clone)]
+pub struct MaterialMtl {
+    pub name: String,
+    /// `Ka`
+    pub ambient: na::Vector3<f32>,
+    /// `Kd`
+    pub diffuse: na::Vector3<f32>,
+    /// `Ks`
+    pub specular: na::Vector3<f32>,
+    /// `Ke`
+    pub emissive: na::Vector3<f32>,
+    /// `Ns`
+    pub shininess: f32,
+    /// `d` or `1 - Tr`, material is transparent if it's less than 1.
+    pub dissolve: f32,
+    /// `Ni`, index of refraction.
+    pub optical_density: f32,
+    /// `illum`
+    pub illumination_model: Option<u8>,
+    /// `map_Ka`
+    pub ambient_map: Option<Texture>,
+    /// `map_Kd`
+    pub diffuse_map: Option<Texture>,
+    /// `map_Ks`
+    pub specular_map: Option<Texture>,
+    /// `map_Bump` or `bump`
+    pub normal_map: Option<Texture>,
+    /// `map_Ns`
+    pub shininess_map: Option<Texture>,
+    /// `map_d`
+    pub alpha_map: Option<Texture>,
+}
+
+impl MaterialMtl {
+    /// Whether the material should be blended after opaque ones, see `Model::draw_sorted()`.
+    pub fn is_transparent(&self) -> bool {
+        self.dissolve < 1.0 || self.alpha_map.is_some()
+    }
+}
+
+/// Defaults of MTL specification, except `shininess`.
+impl Default for MaterialMtl {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            ambient: na::Vector3::new(0.2, 0.2, 0.2),
+            diffuse: na::Vector3::new(0.8, 0.8, 0.8),
+            specular: na::Vector3::new(1.0, 1.0, 1.0),
+            emissive: na::Vector3::zeros(),
+            shininess: DEFAULT_SHININESS,
+            dissolve: 1.0,
+            optical_density: 1.0,
+            illumination_model: None,
+            ambient_map: None,
+            diffuse_map: None,
+            specular_map: None,
+            normal_map: None,
+            shininess_map: None,
+            alpha_map: None,
+        }
+    }
+}
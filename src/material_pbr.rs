@@ -0,0 +1,36 @@
+use nalgebra as na;
+
+use crate::Texture;
+
+/// Metallic-roughness PBR material, e.g. loaded from glTF.
+///
+/// Factors are multiplied with samples of maps, or used alone when maps are absent, except `ao`.
+#[derive(Debug, Clone)]
+pub struct MaterialPbr {
+    /// Base color in linear space, `w` is alpha.
+    pub albedo: na::Vector4<f32>,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Strength of ambient occlusion map like `strength` of glTF occlusion texture, it's not a multiplier: occlusion is
+    /// `1.0 + ao * (sample - 1.0)`, i.e. `mix(1.0, sample, ao)`, so 0 disables it and there's no occlusion without map.
+    pub ao: f32,
+    pub albedo_map: Option<Texture>,
+    pub metallic_map: Option<Texture>,
+    pub roughness_map: Option<Texture>,
+    pub ao_map: Option<Texture>,
+}
+
+impl Default for MaterialPbr {
+    fn default() -> Self {
+        Self {
+            albedo: na::Vector4::new(1.0, 1.0, 1.0, 1.0),
+            metallic: 1.0,
+            roughness: 1.0,
+            ao: 1.0,
+            albedo_map: None,
+            metallic_map: None,
+            roughness_map: None,
+            ao_map: None,
+        }
+    }
+}
@@ -1,14 +1,17 @@
 use std::ffi::CString;
 
 use anyhow::bail;
-use gl::types::GLsizei;
+use nalgebra as na;
 
+use crate::material_mtl::DEFAULT_SHININESS;
 use crate::{
-    Buffer, BufferType, BufferUsage, ShaderProgram, Texture, TextureType, TextureUnit, Vertex,
-    VertexArray, VertexDescription,
+    Buffer, BufferType, BufferUsage, IndexType, InstanceBuffer, MaterialMtl, MaterialPbr,
+    PrimitiveMode, ShaderProgram, Texture, TextureType, TextureUnit, Vertex, VertexArray,
+    VertexLayout, MESH_INSTANCE_LOCATION,
 };
 
-const DEFAULT_SHININESS: f32 = 128.0;
+/// Maps of material are bound from this unit on, lower units are left for other textures, e.g. shadow maps.
+const MATERIAL_FIRST_TEXTURE_UNIT: TextureUnit = TextureUnit::TEXTURE10;
 
 #[allow(dead_code)]
 pub struct Mesh {
@@ -22,6 +25,11 @@ pub struct Mesh {
     pub specular_texture: Option<Texture>,
     pub normal_texture: Option<Texture>,
     pub shininess: Option<f32>,
+    /// PBR material used instead of Blinn-Phong textures by PBR shaders, see `with_pbr_material()`.
+    pub pbr_material: Option<MaterialPbr>,
+    /// Full Blinn-Phong material of MTL file, see `with_mtl_material()`.
+    pub mtl_material: Option<MaterialMtl>,
+    center: na::Point3<f32>,
 }
 
 impl Mesh {
@@ -41,10 +49,8 @@ impl Mesh {
         vbo.set_vertices(&vertices, BufferUsage::StaticDraw);
 
         /* Vertex Attribute description */
-        let mut vertex_desc = VertexDescription::new();
-        vertex_desc.add_attribute(gl::FLOAT, 3); // set NDC coord attribute
-        vertex_desc.add_attribute(gl::FLOAT, 3); // set normal attribute
-        vertex_desc.add_attribute(gl::FLOAT, 2); // set Texture coord attribute
+        // Generated from fields of `Vertex`: position, normal, texture coord, tangent, bitangent
+        let mut vertex_desc = Vertex::vertex_description();
         vertex_desc.bind_to(&vbo, Some(&vao));
 
         /* Index Buffer Object */
@@ -64,6 +70,8 @@ impl Mesh {
             Self::check_texture_type(normal_texture, TextureType::Normal)?;
         }
 
+        let center = Self::bounding_box_center(&vertices);
+
         Ok(Self {
             vao,
             vbo,
@@ -74,12 +82,123 @@ impl Mesh {
             specular_texture,
             normal_texture,
             shininess,
+            pbr_material: None,
+            mtl_material: None,
+            center,
         })
     }
 
+    /// Set Blinn-Phong material of MTL file, whose diffuse/specular/normal maps & shininess replace the ones of mesh.
+    pub fn with_mtl_material(mut self, material: MaterialMtl) -> anyhow::Result<Self> {
+        let maps = [
+            (&material.ambient_map, TextureType::BlinnAmbient),
+            (&material.diffuse_map, TextureType::BlinnDiffuse),
+            (&material.specular_map, TextureType::BlinnSpecular),
+            (&material.normal_map, TextureType::Normal),
+            (&material.shininess_map, TextureType::BlinnShininess),
+            (&material.alpha_map, TextureType::Alpha),
+        ];
+        for (map, texture_type) in maps {
+            if let Some(map) = map {
+                Self::check_texture_type(map, texture_type)?;
+            }
+        }
+
+        self.diffuse_texture = material.diffuse_map.clone();
+        self.specular_texture = material.specular_map.clone();
+        self.normal_texture = material.normal_map.clone();
+        self.shininess = Some(material.shininess);
+        self.mtl_material = Some(material);
+        Ok(self)
+    }
+
+    /// Whether mesh should be blended after opaque ones: its MTL material is transparent, or alpha of its PBR
+    /// material is less than 1.
+    pub fn is_transparent(&self) -> bool {
+        self.mtl_material
+            .as_ref()
+            .is_some_and(|material| material.is_transparent())
+            || self
+                .pbr_material
+                .as_ref()
+                .is_some_and(|material| material.albedo.w < 1.0)
+    }
+
+    /// Center of bounding box of vertices, e.g. for sorting transparent meshes by distance.
+    pub fn center(&self) -> na::Point3<f32> {
+        self.center
+    }
+
+    /// Set PBR material, whose maps must be of `TextureType::PbrAlbedo/PbrMetallic/PbrRoughness/PbrAO`.
+    pub fn with_pbr_material(mut self, material: MaterialPbr) -> anyhow::Result<Self> {
+        if let Some(albedo_map) = material.albedo_map.as_ref() {
+            Self::check_texture_type(albedo_map, TextureType::PbrAlbedo)?;
+        }
+        if let Some(metallic_map) = material.metallic_map.as_ref() {
+            Self::check_texture_type(metallic_map, TextureType::PbrMetallic)?;
+        }
+        if let Some(roughness_map) = material.roughness_map.as_ref() {
+            Self::check_texture_type(roughness_map, TextureType::PbrRoughness)?;
+        }
+        if let Some(ao_map) = material.ao_map.as_ref() {
+            Self::check_texture_type(ao_map, TextureType::PbrAO)?;
+        }
+
+        self.pbr_material = Some(material);
+        Ok(self)
+    }
+
     pub fn draw(&self, shader: &ShaderProgram, material_uniform_name: &str) -> anyhow::Result<()> {
-        /* Bind uniforms */
+        self.bind_material(shader, material_uniform_name)?;
+
+        /* Draw mesh */
+
+        self.vao.draw_elements(
+            PrimitiveMode::Triangles,
+            self.indices.len(),
+            IndexType::UnsignedInt,
+            0,
+        );
+        // always good practice to set everything back to defaults once configured.
+        self.vao.unbind();
+        Texture::active(TextureUnit::TEXTURE0);
+
+        Ok(())
+    }
+
+    /// Attach per-instance attributes at locations from `MESH_INSTANCE_LOCATION`, for `draw_instanced()`.
+    pub fn set_instance_buffer<T: VertexLayout>(&self, instances: &InstanceBuffer<T>) {
+        instances.bind_to(&self.vao, MESH_INSTANCE_LOCATION);
+    }
+
+    /// Draw `count` instances of mesh in one draw call, per-instance attributes are set by `set_instance_buffer()`.
+    pub fn draw_instanced(
+        &self,
+        shader: &ShaderProgram,
+        material_uniform_name: &str,
+        count: usize,
+    ) -> anyhow::Result<()> {
+        self.bind_material(shader, material_uniform_name)?;
+
+        self.vao.draw_elements_instanced(
+            PrimitiveMode::Triangles,
+            self.indices.len(),
+            IndexType::UnsignedInt,
+            0,
+            count,
+        );
+        self.vao.unbind();
+        Texture::active(TextureUnit::TEXTURE0);
+
+        Ok(())
+    }
 
+    /// Set uniforms of shininess & textures of material.
+    fn bind_material(
+        &self,
+        shader: &ShaderProgram,
+        material_uniform_name: &str,
+    ) -> anyhow::Result<()> {
         // Set uniform: shininess
         if let Some(shininess) = self.shininess {
             assert!(shininess >= 0.0);
@@ -95,50 +214,144 @@ impl Mesh {
         }
 
         // Set uniform: diffuse map & specular map & normal map
-        let mut texture_unit = TextureUnit::TEXTURE10;
-        if let Some(diffuse_texture) = &self.diffuse_texture {
-            shader.set_texture_unit(
-                &CString::new(format!("{material_uniform_name}.diffuse_map"))?,
-                diffuse_texture,
-                texture_unit,
-            );
-            texture_unit = texture_unit.increase();
+        let mut texture_unit = Some(MATERIAL_FIRST_TEXTURE_UNIT);
+        let maps = [
+            ("diffuse_map", self.diffuse_texture.as_ref()),
+            ("specular_map", self.specular_texture.as_ref()),
+            ("normal_map", self.normal_texture.as_ref()),
+        ];
+        for (map_name, map) in maps {
+            Self::bind_map(
+                shader,
+                material_uniform_name,
+                map_name,
+                map,
+                &mut texture_unit,
+            )?;
         }
-        if let Some(specular_texture) = &self.specular_texture {
-            shader.set_texture_unit(
-                &CString::new(format!("{material_uniform_name}.specular_map"))?,
-                specular_texture,
-                texture_unit,
+
+        // Set uniform: MTL colors & maps, colors are used by shader when `has_*_map` tells the map isn't bound
+        if let Some(material) = &self.mtl_material {
+            let colors = [
+                ("ambient", &material.ambient),
+                ("diffuse", &material.diffuse),
+                ("specular", &material.specular),
+                ("emissive", &material.emissive),
+            ];
+            for (color_name, color) in colors {
+                shader.set_uniform_3f(
+                    &CString::new(format!("{material_uniform_name}.{color_name}"))?,
+                    color.x,
+                    color.y,
+                    color.z,
+                );
+            }
+            shader.set_uniform_1f(
+                &CString::new(format!("{material_uniform_name}.dissolve"))?,
+                material.dissolve,
             );
-            texture_unit = texture_unit.increase();
+
+            // Diffuse map & specular map & normal map are bound above
+            let maps = [
+                ("ambient_map", material.ambient_map.as_ref()),
+                ("shininess_map", material.shininess_map.as_ref()),
+                ("alpha_map", material.alpha_map.as_ref()),
+            ];
+            for (map_name, map) in maps {
+                Self::bind_map(
+                    shader,
+                    material_uniform_name,
+                    map_name,
+                    map,
+                    &mut texture_unit,
+                )?;
+            }
         }
-        if let Some(normal_texture) = &self.normal_texture {
-            shader.set_texture_unit(
-                &CString::new(format!("{material_uniform_name}.normal_map"))?,
-                normal_texture,
-                texture_unit,
+
+        // Set uniform: PBR factors & maps, `has_*_map` tells shader whether the map is bound
+        if let Some(material) = &self.pbr_material {
+            shader.set_uniform_4f(
+                &CString::new(format!("{material_uniform_name}.albedo"))?,
+                material.albedo.x,
+                material.albedo.y,
+                material.albedo.z,
+                material.albedo.w,
+            );
+            shader.set_uniform_1f(
+                &CString::new(format!("{material_uniform_name}.metallic"))?,
+                material.metallic,
             );
-            // texture_unit = texture_unit.increase();
+            shader.set_uniform_1f(
+                &CString::new(format!("{material_uniform_name}.roughness"))?,
+                material.roughness,
+            );
+            shader.set_uniform_1f(
+                &CString::new(format!("{material_uniform_name}.ao"))?,
+                material.ao,
+            );
+
+            let maps = [
+                ("albedo_map", material.albedo_map.as_ref()),
+                ("metallic_map", material.metallic_map.as_ref()),
+                ("roughness_map", material.roughness_map.as_ref()),
+                ("ao_map", material.ao_map.as_ref()),
+            ];
+            for (map_name, map) in maps {
+                Self::bind_map(
+                    shader,
+                    material_uniform_name,
+                    map_name,
+                    map,
+                    &mut texture_unit,
+                )?;
+            }
         }
 
-        /* Draw mesh */
+        Ok(())
+    }
 
-        self.vao.bind();
-        unsafe {
-            gl::DrawElements(
-                gl::TRIANGLES,
-                self.indices.len() as GLsizei,
-                gl::UNSIGNED_INT,
-                std::ptr::null(),
-            );
-        }
-        // always good practice to set everything back to defaults once configured.
-        self.vao.unbind();
-        Texture::active(TextureUnit::TEXTURE0);
+    /// Bind `map` to sampler `<material>.<map_name>` at `texture_unit` and advance it, and set
+    /// `<material>.has_<map_name>`.
+    ///
+    /// Maps which shader doesn't sample are skipped, so they don't take texture units. `texture_unit` is `None` when all
+    /// units are taken, then binding another map is an error.
+    fn bind_map(
+        shader: &ShaderProgram,
+        material_uniform_name: &str,
+        map_name: &str,
+        map: Option<&Texture>,
+        texture_unit: &mut Option<TextureUnit>,
+    ) -> anyhow::Result<()> {
+        let sampler_name = format!("{material_uniform_name}.{map_name}");
+        let map = map.filter(|_| shader.get_uniform_info(&sampler_name).is_some());
+        shader.set_uniform_1i(
+            &CString::new(format!("{material_uniform_name}.has_{map_name}"))?,
+            map.is_some() as i32,
+        );
 
+        if let Some(map) = map {
+            let Some(unit) = *texture_unit else {
+                bail!("No texture unit left for {sampler_name}, material has too many maps");
+            };
+            shader.set_texture_unit(&CString::new(sampler_name)?, map, unit);
+            *texture_unit = unit.increase();
+        }
         Ok(())
     }
 
+    fn bounding_box_center(vertices: &[Vertex]) -> na::Point3<f32> {
+        let Some(first) = vertices.first() else {
+            return na::Point3::origin();
+        };
+        let (min, max) = vertices
+            .iter()
+            .fold((first.position, first.position), |(min, max), vertex| {
+                (min.inf(&vertex.position), max.sup(&vertex.position))
+            });
+
+        na::Point3::from((min + max) / 2.0)
+    }
+
     fn check_texture_type(texture: &Texture, texture_type: TextureType) -> anyhow::Result<()> {
         if texture.tex_type != texture_type {
             bail!(
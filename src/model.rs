@@ -1,26 +1,77 @@
 use std::path::PathBuf;
 
-use anyhow::{bail, Ok};
+use std::collections::HashMap;
+
+use anyhow::{Context, Ok};
 use nalgebra as na;
-use tracing::{debug, trace, warn};
 
-use crate::{Mesh, ShaderProgram, Texture, TextureType, Vertex};
+use crate::{InstanceBuffer, Mesh, ShaderProgram, Texture, TextureType, Vertex, VertexLayout};
+
+mod gltf_loader;
+mod obj_loader;
 
 pub struct Model {
     pub meshes: Vec<Mesh>,
+    /// Root nodes of the hierarchy of meshes.
+    pub nodes: Vec<ModelNode>,
     textures_loaded: Vec<Texture>,
     model_path: PathBuf,
+    generated_normals: GeneratedNormals,
+}
+
+/// How normals are generated for OBJ meshes without them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GeneratedNormals {
+    /// Average normals of faces around each vertex position.
+    #[default]
+    Smooth,
+    /// Normals of faces, vertices are split so each face has its own.
+    Flat,
+}
+
+/// Node of model hierarchy, e.g. a glTF node or an OBJ object.
+///
+/// Node transforms are already applied to vertices of meshes, they're kept for looking up parts of model.
+#[derive(Debug, Clone)]
+pub struct ModelNode {
+    pub name: Option<String>,
+    /// Transform relative to parent node.
+    pub transform: na::Matrix4<f32>,
+    /// Indices of meshes in `Model::meshes`.
+    pub meshes: Vec<usize>,
+    pub children: Vec<ModelNode>,
 }
 
 impl Model {
+    /// Load model from Wavefront OBJ (`.obj`) or glTF 2.0 (`.gltf`, `.glb`) file.
+    ///
+    /// Missing normals of OBJ meshes are generated smooth, see `new_with_normals()`.
     pub fn new(model_path: PathBuf) -> anyhow::Result<Model> {
+        Self::new_with_normals(model_path, GeneratedNormals::default())
+    }
+
+    /// Same as `new()`, and missing normals of OBJ meshes are generated as `generated_normals`. glTF meshes without
+    /// normals are always flat shaded, as required by glTF.
+    pub fn new_with_normals(
+        model_path: PathBuf,
+        generated_normals: GeneratedNormals,
+    ) -> anyhow::Result<Model> {
         let mut model = Self {
             meshes: Vec::new(),
+            nodes: Vec::new(),
             textures_loaded: Vec::new(),
             model_path,
+            generated_normals,
         };
 
-        model.load_model()?;
+        let extension = model
+            .model_path
+            .extension()
+            .map(|extension| extension.to_ascii_lowercase());
+        match extension.as_ref().and_then(|extension| extension.to_str()) {
+            Some("gltf") | Some("glb") => model.load_gltf()?,
+            _ => model.load_obj()?,
+        }
 
         Ok(model)
     }
@@ -33,117 +84,68 @@ impl Model {
         Ok(())
     }
 
-    fn load_model(&mut self) -> anyhow::Result<()> {
-        debug!("Loading model from {:?}", &self.model_path);
-
-        // Load .obj file
-        let (models, materials) = tobj::load_obj(
-            &self.model_path,
-            &tobj::LoadOptions {
-                single_index: true,
-                triangulate: true,
-                ignore_points: true,
-                ignore_lines: true,
-            },
-        )?;
-        let materials = materials?;
-
-        trace!("Number of models          = {}", models.len());
-        trace!("Number of materials       = {}", materials.len());
-
-        // Load Meshes
-        for (i, model) in models.iter().enumerate() {
-            trace!("model[{}].name             = \'{}\'", i, model.name);
-            trace!(
-                "model[{}].mesh.material_id = {:?}",
-                i,
-                model.mesh.material_id
-            );
-            trace!(
-                "model[{}].face_count       = {}",
-                i,
-                model.mesh.face_arities.len()
-            );
-
-            let mesh = self.load_mesh(&model.mesh, &materials, &model.name)?;
-            self.meshes.push(mesh);
+    /// Draw opaque meshes first, then transparent meshes (see `Mesh::is_transparent()`) from far to near
+    /// `camera_position`, with alpha blending enabled and depth writing disabled. Model is placed by `model_matrix`.
+    ///
+    /// Blending & depth writing are restored to defaults (disabled & enabled) after drawing.
+    pub fn draw_sorted(
+        &self,
+        shader: &ShaderProgram,
+        material_uniform_name: &str,
+        model_matrix: &na::Matrix4<f32>,
+        camera_position: &na::Point3<f32>,
+    ) -> anyhow::Result<()> {
+        let (transparent_meshes, opaque_meshes): (Vec<_>, Vec<_>) =
+            self.meshes.iter().partition(|mesh| mesh.is_transparent());
+        for mesh in opaque_meshes {
+            mesh.draw(shader, material_uniform_name)?;
         }
 
-        Ok(())
+        // Sort transparent meshes by distance
+        let mut transparent_meshes: Vec<_> = transparent_meshes
+            .into_iter()
+            .map(|mesh| {
+                let center = model_matrix.transform_point(&mesh.center());
+                (na::distance_squared(&center, camera_position), mesh)
+            })
+            .collect();
+        transparent_meshes.sort_by(|(distance1, _), (distance2, _)| distance2.total_cmp(distance1));
+
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::DepthMask(gl::FALSE);
+        }
+        let result = transparent_meshes
+            .into_iter()
+            .try_for_each(|(_, mesh)| mesh.draw(shader, material_uniform_name));
+        unsafe {
+            gl::DepthMask(gl::TRUE);
+            gl::Disable(gl::BLEND);
+        }
+
+        result
     }
 
-    fn load_mesh(
-        &mut self,
-        mesh: &tobj::Mesh,
-        materials: &[tobj::Material],
-        model_name: &str,
-    ) -> anyhow::Result<Mesh> {
-        // Handle indices of mesh
-        let indices: Vec<u32> = mesh.indices.clone();
-
-        // Handle vertices of mesh
-        assert!(mesh.positions.len() % 3 == 0);
-        let vertices_num = mesh.positions.len() / 3;
-        let mut vertices: Vec<Vertex> = Vec::with_capacity(vertices_num);
-        for i in 0..vertices_num {
-            vertices.push(Vertex {
-                position: na::Vector3::new(
-                    mesh.positions[i * 3],
-                    mesh.positions[i * 3 + 1],
-                    mesh.positions[i * 3 + 2],
-                ),
-                normal: na::Vector3::new(
-                    mesh.normals[i * 3],
-                    mesh.normals[i * 3 + 1],
-                    mesh.normals[i * 3 + 2],
-                ),
-                texture_coords: na::Vector2::new(mesh.texcoords[i * 2], mesh.texcoords[i * 2 + 1]),
-            })
+    /// Attach per-instance attributes to all meshes, see `Mesh::set_instance_buffer()`.
+    pub fn set_instance_buffer<T: VertexLayout>(&self, instances: &InstanceBuffer<T>) {
+        for mesh in self.meshes.iter() {
+            mesh.set_instance_buffer(instances);
         }
+    }
 
-        // Handle material of mesh
-        if let Some(material_id) = mesh.material_id {
-            let material = &materials[material_id];
-
-            let shininess = material.shininess;
-            let mut diffuse_texture = None;
-            let mut specular_texture = None;
-            let mut normal_texture = None;
-
-            // load diffuse map
-            if let Some(ref diffuse_texture_filename) = material.diffuse_texture {
-                diffuse_texture =
-                    Some(self.load_texture(diffuse_texture_filename, TextureType::BlinnDiffuse)?);
-            } else {
-                warn!("No diffuse texture for mesh in model({})!", model_name)
-            }
-            // load specular map
-            if let Some(ref specular_texture_filename) = material.specular_texture {
-                specular_texture =
-                    Some(self.load_texture(specular_texture_filename, TextureType::BlinnSpecular)?);
-            } else {
-                warn!("No specular texture for mesh in model({})!", model_name)
-            }
-            // load normal map
-            if let Some(ref normal_texture_filename) = material.normal_texture {
-                normal_texture =
-                    Some(self.load_texture(normal_texture_filename, TextureType::Normal)?);
-            } else {
-                warn!("No normal texture for mesh in model({})!", model_name)
-            }
-            // TODO: load ambient & shiness map
-
-            Ok(Mesh::new(
-                vertices,
-                indices,
-                diffuse_texture,
-                specular_texture,
-                normal_texture,
-                shininess,
-            )?)
-        } else {
-            bail!("No material id for mesh")
+    /// Draw `count` instances of model, one draw call per mesh, see `Mesh::draw_instanced()`.
+    pub fn draw_instanced(
+        &self,
+        shader: &ShaderProgram,
+        material_uniform_name: &str,
+        count: usize,
+    ) -> anyhow::Result<()> {
+        for mesh in self.meshes.iter() {
+            mesh.draw_instanced(shader, material_uniform_name, count)?;
         }
+
+        Ok(())
     }
 
     fn load_texture(
@@ -162,9 +164,56 @@ impl Model {
             return Ok(texture_loaded.clone());
         }
 
-        let texture = Texture::create(texture_path, Some(texture_type))?;
+        let texture = Texture::create(texture_path.clone(), Some(texture_type))
+            .with_context(|| format!("Failed to load texture: {texture_path:?}"))?;
         self.textures_loaded.push(texture.clone());
 
         Ok(texture)
     }
 }
+
+/// Split vertices so each triangle has its own, and set normals to face normals.
+fn flat_shaded(vertices: &[Vertex], indices: &[u32]) -> (Vec<Vertex>, Vec<u32>) {
+    let mut flat_vertices = Vec::with_capacity(indices.len());
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|k| vertices[triangle[k] as usize]);
+        let normal = (b.position - a.position)
+            .cross(&(c.position - a.position))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(na::Vector3::zeros);
+        for mut vertex in [a, b, c] {
+            vertex.normal = normal;
+            flat_vertices.push(vertex);
+        }
+    }
+
+    let flat_indices = (0..flat_vertices.len() as u32).collect();
+    (flat_vertices, flat_indices)
+}
+
+/// Set normals to the average of normals of faces sharing the vertex position, weighted by face areas.
+///
+/// Vertices are matched by position rather than index, so normals stay smooth across seams of texture coords.
+fn smooth_normals(vertices: &mut [Vertex], indices: &[u32]) {
+    // `+ 0.0` turns -0.0 into 0.0, so both are the same position
+    let position_key = |vertex: &Vertex| vertex.position.map(|x| (x + 0.0).to_bits());
+
+    let mut normals: HashMap<_, na::Vector3<f32>> = HashMap::new();
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|k| vertices[triangle[k] as usize]);
+        // Length of cross product is twice the area
+        let normal = (b.position - a.position).cross(&(c.position - a.position));
+        for vertex in [a, b, c] {
+            *normals
+                .entry(position_key(&vertex))
+                .or_insert_with(na::Vector3::zeros) += normal;
+        }
+    }
+
+    for vertex in vertices.iter_mut() {
+        vertex.normal = normals
+            .get(&position_key(vertex))
+            .and_then(|normal| normal.try_normalize(f32::EPSILON))
+            .unwrap_or_else(na::Vector3::zeros);
+    }
+}
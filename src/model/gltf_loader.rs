@@ -0,0 +1,480 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
+use gltf::mesh::Mode;
+use image::{DynamicImage, ImageBuffer};
+use nalgebra as na;
+use tracing::{trace, warn};
+
+use super::{flat_shaded, Model, ModelNode};
+use crate::{
+    generate_tangents, FilterMode, MaterialPbr, Mesh, MipmapFilter, Texture, TextureOptions,
+    TextureType, Vertex, WrapMode,
+};
+
+impl Model {
+    /// Load glTF 2.0 file: `.gltf` with external or embedded (data URI) buffers & images, or binary `.glb`.
+    ///
+    /// Nodes of the default scene are loaded with their hierarchy, and each primitive of meshes becomes a `Mesh`
+    /// whose vertices are transformed by its node. Metallic-roughness materials become `MaterialPbr`.
+    pub(super) fn load_gltf(&mut self) -> anyhow::Result<()> {
+        let (document, buffers, images) = gltf::import(&self.model_path)
+            .with_context(|| format!("Failed to import glTF file: {:?}", self.model_path))?;
+
+        let Some(scene) = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+        else {
+            bail!("No scene in glTF file: {:?}", self.model_path);
+        };
+
+        trace!("Number of nodes           = {}", document.nodes().len());
+        trace!("Number of meshes          = {}", document.meshes().len());
+        trace!("Number of materials       = {}", document.materials().len());
+
+        for node in scene.nodes() {
+            let node = self.load_gltf_node(&node, &na::Matrix4::identity(), &buffers, &images)?;
+            self.nodes.push(node);
+        }
+
+        Ok(())
+    }
+
+    fn load_gltf_node(
+        &mut self,
+        node: &gltf::Node,
+        parent_transform: &na::Matrix4<f32>,
+        buffers: &[gltf::buffer::Data],
+        images: &[gltf::image::Data],
+    ) -> anyhow::Result<ModelNode> {
+        // Matrix of glTF is column-major, so is nalgebra
+        let transform = na::Matrix4::from(node.transform().matrix());
+        let world_transform = parent_transform * transform;
+
+        let mut meshes = Vec::new();
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                let loaded = self
+                    .load_gltf_primitive(&primitive, &world_transform, buffers, images)
+                    .with_context(|| {
+                        format!(
+                            "Failed to load primitive {} of glTF mesh({})",
+                            primitive.index(),
+                            mesh.name().unwrap_or_default()
+                        )
+                    })?;
+                if let Some(loaded) = loaded {
+                    meshes.push(self.meshes.len());
+                    self.meshes.push(loaded);
+                }
+            }
+        }
+
+        let children = node
+            .children()
+            .map(|child| self.load_gltf_node(&child, &world_transform, buffers, images))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(ModelNode {
+            name: node.name().map(str::to_owned),
+            transform,
+            meshes,
+            children,
+        })
+    }
+
+    /// Load a primitive into mesh transformed by `transform`, or `None` if it isn't made of triangles.
+    fn load_gltf_primitive(
+        &mut self,
+        primitive: &gltf::Primitive,
+        transform: &na::Matrix4<f32>,
+        buffers: &[gltf::buffer::Data],
+        images: &[gltf::image::Data],
+    ) -> anyhow::Result<Option<Mesh>> {
+        let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data[..]));
+
+        // Handle indices of mesh
+        let Some(positions) = reader.read_positions() else {
+            bail!("No positions in glTF primitive");
+        };
+        let positions: Vec<na::Vector3<f32>> = positions.map(na::Vector3::from).collect();
+        let indices: Vec<u32> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+        let mut indices = match primitive.mode() {
+            Mode::Triangles => indices,
+            Mode::TriangleStrip => strip_to_triangles(&indices),
+            Mode::TriangleFan => fan_to_triangles(&indices),
+            mode => {
+                warn!("Skip glTF primitive of mode {mode:?}, only triangles are supported");
+                return Ok(None);
+            }
+        };
+        if let Some(&index) = indices
+            .iter()
+            .find(|&&index| index as usize >= positions.len())
+        {
+            bail!("Index {index} of glTF primitive is out of range");
+        }
+
+        // Handle vertices of mesh
+        let normals: Option<Vec<na::Vector3<f32>>> = reader
+            .read_normals()
+            .map(|normals| normals.map(na::Vector3::from).collect());
+        // Texture coords of glTF start from top-left corner, while textures are flipped to start from bottom-left
+        let texture_coords: Option<Vec<na::Vector2<f32>>> = reader.read_tex_coords(0).map(|uvs| {
+            uvs.into_f32()
+                .map(|[u, v]| na::Vector2::new(u, 1.0 - v))
+                .collect()
+        });
+        // `w` is sign of bitangent
+        let tangents: Option<Vec<[f32; 4]>> =
+            reader.read_tangents().map(|tangents| tangents.collect());
+
+        let linear = transform.fixed_view::<3, 3>(0, 0).into_owned();
+        let normal_matrix = linear
+            .try_inverse()
+            .unwrap_or_else(na::Matrix3::identity)
+            .transpose();
+        let mut vertices: Vec<Vertex> = positions
+            .iter()
+            .enumerate()
+            .map(|(i, position)| {
+                let mut vertex = Vertex::new(
+                    transform
+                        .transform_point(&na::Point3::from(*position))
+                        .coords,
+                    normals.as_ref().map_or(na::Vector3::zeros(), |normals| {
+                        (normal_matrix * normals[i])
+                            .try_normalize(f32::EPSILON)
+                            .unwrap_or_else(na::Vector3::zeros)
+                    }),
+                    texture_coords
+                        .as_ref()
+                        .map_or(na::Vector2::zeros(), |uvs| uvs[i]),
+                );
+                if let (Some(normals), Some(tangents)) = (&normals, &tangents) {
+                    let [x, y, z, w] = tangents[i];
+                    let tangent = na::Vector3::new(x, y, z);
+                    let bitangent = normals[i].cross(&tangent) * w;
+                    vertex.tangent = (linear * tangent)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(na::Vector3::zeros);
+                    vertex.bitangent = (linear * bitangent)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(na::Vector3::zeros);
+                }
+                vertex
+            })
+            .collect();
+
+        // Mirroring transform flips triangles inside out
+        if linear.determinant() < 0.0 {
+            for triangle in indices.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+
+        // Flat normals are required by glTF when normals are absent
+        if normals.is_none() {
+            (vertices, indices) = flat_shaded(&vertices, &indices);
+        }
+        if (normals.is_none() || tangents.is_none())
+            && texture_coords.is_some()
+            && !generate_tangents(&mut vertices, &mut indices)
+        {
+            warn!("Failed to generate tangents for glTF primitive");
+        }
+
+        // Handle material of mesh
+        let material = primitive.material();
+        let pbr = material.pbr_metallic_roughness();
+        let mut pbr_material = MaterialPbr {
+            albedo: na::Vector4::from(pbr.base_color_factor()),
+            metallic: pbr.metallic_factor(),
+            roughness: pbr.roughness_factor(),
+            ..Default::default()
+        };
+        // load albedo map
+        if let Some(info) = pbr.base_color_texture() {
+            check_tex_coord(info.tex_coord());
+            pbr_material.albedo_map =
+                Some(self.load_gltf_texture(&info.texture(), images, TextureType::PbrAlbedo)?);
+        }
+        // load metallic & roughness map, which are B & G channels of the same image
+        if let Some(info) = pbr.metallic_roughness_texture() {
+            check_tex_coord(info.tex_coord());
+            pbr_material.metallic_map =
+                Some(self.load_gltf_texture(&info.texture(), images, TextureType::PbrMetallic)?);
+            pbr_material.roughness_map =
+                Some(self.load_gltf_texture(&info.texture(), images, TextureType::PbrRoughness)?);
+        }
+        // load ambient occlusion map, which is R channel. `strength` isn't a multiplier: occlusion is
+        // `1 + strength * (sample - 1)`, which is how `MaterialPbr::ao` is defined as well.
+        if let Some(occlusion) = material.occlusion_texture() {
+            check_tex_coord(occlusion.tex_coord());
+            pbr_material.ao = occlusion.strength();
+            pbr_material.ao_map =
+                Some(self.load_gltf_texture(&occlusion.texture(), images, TextureType::PbrAO)?);
+        }
+        // load normal map
+        let normal_texture = match material.normal_texture() {
+            Some(normal) => {
+                check_tex_coord(normal.tex_coord());
+                Some(self.load_gltf_texture(&normal.texture(), images, TextureType::Normal)?)
+            }
+            None => None,
+        };
+
+        let mesh = Mesh::new(vertices, indices, None, None, normal_texture, None)?
+            .with_pbr_material(pbr_material)?;
+
+        Ok(Some(mesh))
+    }
+
+    /// Load image of glTF texture, PBR metallic/roughness/AO maps are extracted from B/G/R channel.
+    fn load_gltf_texture(
+        &mut self,
+        texture: &gltf::Texture,
+        images: &[gltf::image::Data],
+        texture_type: TextureType,
+    ) -> anyhow::Result<Texture> {
+        // Images may be embedded, so they're identified by index, sampler & usage in model file
+        let image_index = texture.source().index();
+        let sampler = texture.sampler();
+        let sampler_name = sampler
+            .index()
+            .map_or_else(|| "default".to_owned(), |index| index.to_string());
+        let texture_path = PathBuf::from(format!(
+            "{}#image{image_index}/sampler{sampler_name}/{texture_type:?}",
+            self.model_path.display()
+        ));
+
+        // Check if texture was loaded before and if so, return it
+        if let Some(texture_loaded) = self.textures_loaded.iter().find(|t| t.path == texture_path) {
+            return Ok(texture_loaded.clone());
+        }
+
+        let Some(data) = images.get(image_index) else {
+            bail!("Image {image_index} of glTF texture is missing");
+        };
+        let mut img = gltf_image(data)?;
+        let channel = match texture_type {
+            TextureType::PbrMetallic => Some(2),
+            TextureType::PbrRoughness => Some(1),
+            TextureType::PbrAO => Some(0),
+            _ => None,
+        };
+        if let Some(channel) = channel {
+            let rgb = img.to_rgb8();
+            img = DynamicImage::ImageLuma8(ImageBuffer::from_fn(
+                img.width(),
+                img.height(),
+                |x, y| image::Luma([rgb.get_pixel(x, y)[channel]]),
+            ));
+        }
+
+        let options = gltf_texture_options(&sampler);
+        let texture = Texture::create_from_image(&img, texture_path, Some(texture_type), &options)?;
+        self.textures_loaded.push(texture.clone());
+
+        Ok(texture)
+    }
+}
+
+/// Map glTF sampler to `TextureOptions`, undefined filters are trilinear.
+fn gltf_texture_options(sampler: &gltf::texture::Sampler) -> TextureOptions {
+    use gltf::texture::{MagFilter, MinFilter, WrappingMode};
+
+    let wrap_mode = |mode| match mode {
+        WrappingMode::ClampToEdge => WrapMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => WrapMode::MirroredRepeat,
+        WrappingMode::Repeat => WrapMode::Repeat,
+    };
+    let mut options = TextureOptions::default()
+        .with_wrap_s(wrap_mode(sampler.wrap_s()))
+        .with_wrap_t(wrap_mode(sampler.wrap_t()));
+
+    if let Some(mag_filter) = sampler.mag_filter() {
+        options.mag_filter = match mag_filter {
+            MagFilter::Nearest => FilterMode::Nearest,
+            MagFilter::Linear => FilterMode::Linear,
+        };
+    }
+    if let Some(min_filter) = sampler.min_filter() {
+        (options.min_filter, options.mipmap_filter) = match min_filter {
+            MinFilter::Nearest => (FilterMode::Nearest, MipmapFilter::None),
+            MinFilter::Linear => (FilterMode::Linear, MipmapFilter::None),
+            MinFilter::NearestMipmapNearest => (FilterMode::Nearest, MipmapFilter::Nearest),
+            MinFilter::LinearMipmapNearest => (FilterMode::Linear, MipmapFilter::Nearest),
+            MinFilter::NearestMipmapLinear => (FilterMode::Nearest, MipmapFilter::Linear),
+            MinFilter::LinearMipmapLinear => (FilterMode::Linear, MipmapFilter::Linear),
+        };
+    }
+
+    options
+}
+
+/// Convert decoded glTF image to `DynamicImage`, images of 16 bits or float channels are converted to 8 bits.
+fn gltf_image(data: &gltf::image::Data) -> anyhow::Result<DynamicImage> {
+    use gltf::image::Format;
+
+    let (width, height) = (data.width, data.height);
+    let pixels = data.pixels.clone();
+    let u16_pixels = || -> Vec<u16> {
+        data.pixels
+            .chunks_exact(2)
+            .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]))
+            .collect()
+    };
+    let f32_pixels = || -> Vec<f32> {
+        data.pixels
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect()
+    };
+
+    let img = match data.format {
+        Format::R8 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8),
+        Format::R8G8 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLumaA8),
+        Format::R8G8B8 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8),
+        Format::R8G8B8A8 => {
+            ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8)
+        }
+        Format::R16 => ImageBuffer::from_raw(width, height, u16_pixels())
+            .map(|img| DynamicImage::ImageLuma8(DynamicImage::ImageLuma16(img).to_luma8())),
+        Format::R16G16 => ImageBuffer::from_raw(width, height, u16_pixels())
+            .map(|img| DynamicImage::ImageLumaA8(DynamicImage::ImageLumaA16(img).to_luma_alpha8())),
+        Format::R16G16B16 => ImageBuffer::from_raw(width, height, u16_pixels())
+            .map(|img| DynamicImage::ImageRgb8(DynamicImage::ImageRgb16(img).to_rgb8())),
+        Format::R16G16B16A16 => ImageBuffer::from_raw(width, height, u16_pixels())
+            .map(|img| DynamicImage::ImageRgba8(DynamicImage::ImageRgba16(img).to_rgba8())),
+        Format::R32G32B32FLOAT => ImageBuffer::from_raw(width, height, f32_pixels())
+            .map(|img| DynamicImage::ImageRgb8(DynamicImage::ImageRgb32F(img).to_rgb8())),
+        Format::R32G32B32A32FLOAT => ImageBuffer::from_raw(width, height, f32_pixels())
+            .map(|img| DynamicImage::ImageRgba8(DynamicImage::ImageRgba32F(img).to_rgba8())),
+    };
+
+    img.with_context(|| {
+        format!(
+            "Size of glTF image data doesn't match {width}x{height} {:?}",
+            data.format
+        )
+    })
+}
+
+/// Only the first set of texture coords is loaded.
+fn check_tex_coord(tex_coord: u32) {
+    if tex_coord != 0 {
+        warn!("glTF texture uses texture coords {tex_coord}, but only 0 is supported");
+    }
+}
+
+/// Convert indices of triangle strip into triangle list, keeping winding of every triangle.
+fn strip_to_triangles(indices: &[u32]) -> Vec<u32> {
+    indices
+        .windows(3)
+        .enumerate()
+        .flat_map(|(i, w)| {
+            if i % 2 == 0 {
+                [w[0], w[1], w[2]]
+            } else {
+                [w[1], w[0], w[2]]
+            }
+        })
+        .collect()
+}
+
+/// Convert indices of triangle fan into triangle list.
+fn fan_to_triangles(indices: &[u32]) -> Vec<u32> {
+    match indices.split_first() {
+        Some((&center, rest)) => rest.windows(2).flat_map(|w| [center, w[0], w[1]]).collect(),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signed area of triangles of 2D `positions`, positive if counter-clockwise.
+    fn signed_areas(positions: &[[f32; 2]], triangles: &[u32]) -> Vec<f32> {
+        triangles
+            .chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|k| positions[triangle[k] as usize]);
+                (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn strip_keeps_winding() {
+        // Zigzag between top & bottom rows, whose first triangle is counter-clockwise
+        let positions = [
+            [0.0, 1.0],
+            [0.0, 0.0],
+            [1.0, 1.0],
+            [1.0, 0.0],
+            [2.0, 1.0],
+            [2.0, 0.0],
+        ];
+        let triangles = strip_to_triangles(&[0, 1, 2, 3, 4, 5]);
+        assert_eq!(triangles, [0, 1, 2, 2, 1, 3, 2, 3, 4, 4, 3, 5]);
+        assert!(signed_areas(&positions, &triangles)
+            .iter()
+            .all(|&area| area > 0.0));
+    }
+
+    #[test]
+    fn fan_keeps_winding() {
+        let positions = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [-1.0, 1.0]];
+        let triangles = fan_to_triangles(&[0, 1, 2, 3, 4]);
+        assert_eq!(triangles, [0, 1, 2, 0, 2, 3, 0, 3, 4]);
+        assert!(signed_areas(&positions, &triangles)
+            .iter()
+            .all(|&area| area > 0.0));
+    }
+
+    #[test]
+    fn degenerate_strip_and_fan() {
+        for indices in [&[][..], &[0], &[0, 1]] {
+            assert!(strip_to_triangles(indices).is_empty());
+            assert!(fan_to_triangles(indices).is_empty());
+        }
+        assert_eq!(strip_to_triangles(&[0, 1, 2]), [0, 1, 2]);
+        assert_eq!(fan_to_triangles(&[0, 1, 2]), [0, 1, 2]);
+    }
+
+    #[test]
+    fn texture_options_of_samplers() {
+        // NEAREST mag filter, LINEAR_MIPMAP_NEAREST min filter, CLAMP_TO_EDGE & MIRRORED_REPEAT wrapping, and a
+        // sampler of defaults
+        let gltf = gltf::Gltf::from_slice(
+            br#"{
+                "asset": { "version": "2.0" },
+                "samplers": [
+                    { "magFilter": 9728, "minFilter": 9985, "wrapS": 33071, "wrapT": 33648 },
+                    { "minFilter": 9728 },
+                    {}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let options: Vec<_> = gltf.samplers().map(|s| gltf_texture_options(&s)).collect();
+
+        assert_eq!(options[0].wrap_s, WrapMode::ClampToEdge);
+        assert_eq!(options[0].wrap_t, WrapMode::MirroredRepeat);
+        assert_eq!(options[0].mag_filter, FilterMode::Nearest);
+        assert_eq!(options[0].min_filter, FilterMode::Linear);
+        assert_eq!(options[0].mipmap_filter, MipmapFilter::Nearest);
+
+        // Filter without mipmap disables mipmap filtering
+        assert_eq!(options[1].min_filter, FilterMode::Nearest);
+        assert_eq!(options[1].mipmap_filter, MipmapFilter::None);
+        assert_eq!(options[1].mag_filter, TextureOptions::default().mag_filter);
+
+        assert_eq!(options[2], TextureOptions::default());
+    }
+}
@@ -0,0 +1,413 @@
+use anyhow::Context;
+use nalgebra as na;
+use tracing::{debug, trace, warn};
+
+use super::{flat_shaded, smooth_normals, GeneratedNormals, Model, ModelNode};
+use crate::{generate_tangents, MaterialMtl, Mesh, TextureType, Vertex};
+
+const OBJ_LOAD_OPTIONS: tobj::LoadOptions = tobj::LoadOptions {
+    single_index: true,
+    triangulate: true,
+    ignore_points: true,
+    ignore_lines: true,
+};
+
+impl Model {
+    /// Load Wavefront OBJ file with materials of its MTL files, each object becomes a `Mesh`.
+    pub(super) fn load_obj(&mut self) -> anyhow::Result<()> {
+        debug!("Loading model from {:?}", &self.model_path);
+
+        // Load .obj file
+        let (models, materials) = tobj::load_obj(&self.model_path, &OBJ_LOAD_OPTIONS)
+            .with_context(|| format!("Failed to load OBJ file: {:?}", self.model_path))?;
+        let materials = materials.with_context(|| {
+            format!(
+                "Failed to load MTL materials of OBJ file: {:?}",
+                self.model_path
+            )
+        })?;
+
+        trace!("Number of models          = {}", models.len());
+        trace!("Number of materials       = {}", materials.len());
+
+        // Load Meshes
+        for (i, model) in models.iter().enumerate() {
+            trace!("model[{}].name             = \'{}\'", i, model.name);
+            trace!(
+                "model[{}].mesh.material_id = {:?}",
+                i,
+                model.mesh.material_id
+            );
+            trace!(
+                "model[{}].face_count       = {}",
+                i,
+                model.mesh.face_arities.len()
+            );
+
+            let mesh = self
+                .load_mesh(&model.mesh, &materials, &model.name)
+                .with_context(|| {
+                    format!(
+                        "Failed to load mesh of model({}) in OBJ file: {:?}",
+                        model.name, self.model_path
+                    )
+                })?;
+            self.nodes.push(ModelNode {
+                name: Some(model.name.clone()),
+                transform: na::Matrix4::identity(),
+                meshes: vec![self.meshes.len()],
+                children: Vec::new(),
+            });
+            self.meshes.push(mesh);
+        }
+
+        Ok(())
+    }
+
+    fn load_mesh(
+        &mut self,
+        mesh: &tobj::Mesh,
+        materials: &[tobj::Material],
+        model_name: &str,
+    ) -> anyhow::Result<Mesh> {
+        // Handle vertices & indices of mesh
+        let (vertices, indices) = obj_vertices(mesh, self.generated_normals, model_name);
+
+        // Handle material of mesh
+        let material = match obj_material(mesh, materials, model_name) {
+            Some(material) => self.load_mtl_material(material)?,
+            None => MaterialMtl::default(),
+        };
+
+        Mesh::new(vertices, indices, None, None, None, None)?.with_mtl_material(material)
+    }
+
+    /// Load colors & maps of MTL material, missing properties are defaults of `MaterialMtl`.
+    fn load_mtl_material(&mut self, material: &tobj::Material) -> anyhow::Result<MaterialMtl> {
+        let default = MaterialMtl::default();
+        let color = |color: Option<[f32; 3]>, default| color.map_or(default, na::Vector3::from);
+        // `Tr` is transparency, which is inverse of `d`
+        let dissolve = match (material.dissolve, material.unknown_param.get("Tr")) {
+            (Some(dissolve), _) => dissolve,
+            (None, Some(transparency)) => {
+                let transparency: f32 = transparency.trim().parse().with_context(|| {
+                    format!("Invalid Tr({transparency}) of material({})", material.name)
+                })?;
+                1.0 - transparency
+            }
+            (None, None) => default.dissolve,
+        };
+
+        let mut load_map = |filename: &Option<String>, texture_type| {
+            filename
+                .as_ref()
+                .map(|filename| self.load_texture(filename, texture_type))
+                .transpose()
+        };
+
+        Ok(MaterialMtl {
+            name: material.name.clone(),
+            ambient: color(material.ambient, default.ambient),
+            diffuse: color(material.diffuse, default.diffuse),
+            specular: color(material.specular, default.specular),
+            emissive: color(material.emissive, default.emissive),
+            shininess: material.shininess.unwrap_or(default.shininess),
+            dissolve,
+            optical_density: material.optical_density.unwrap_or(default.optical_density),
+            illumination_model: material.illumination_model,
+            ambient_map: load_map(&material.ambient_texture, TextureType::BlinnAmbient)?,
+            diffuse_map: load_map(&material.diffuse_texture, TextureType::BlinnDiffuse)?,
+            specular_map: load_map(&material.specular_texture, TextureType::BlinnSpecular)?,
+            normal_map: load_map(&material.normal_texture, TextureType::Normal)?,
+            shininess_map: load_map(&material.shininess_texture, TextureType::BlinnShininess)?,
+            alpha_map: load_map(&material.dissolve_texture, TextureType::Alpha)?,
+        })
+    }
+}
+
+/// Build vertices & indices of OBJ mesh.
+///
+/// Normals are generated as `generated_normals` if absent, and texture coords default to (0, 0) if absent.
+fn obj_vertices(
+    mesh: &tobj::Mesh,
+    generated_normals: GeneratedNormals,
+    model_name: &str,
+) -> (Vec<Vertex>, Vec<u32>) {
+    let vertices_num = mesh.positions.len() / 3;
+    // Some faces may have normals or texture coords while the others don't, then they're ignored
+    let has_normals = mesh.normals.len() == vertices_num * 3;
+    let has_texture_coords = mesh.texcoords.len() == vertices_num * 2;
+    if !has_normals && !mesh.normals.is_empty() {
+        warn!(
+            "Normals of some vertices are missing in model({}), all normals are generated!",
+            model_name
+        );
+    }
+    if !has_texture_coords && !mesh.texcoords.is_empty() {
+        warn!(
+            "Texture coords of some vertices are missing in model({}), all are ignored!",
+            model_name
+        );
+    }
+
+    let mut vertices: Vec<Vertex> = (0..vertices_num)
+        .map(|i| {
+            Vertex::new(
+                na::Vector3::from_column_slice(&mesh.positions[i * 3..i * 3 + 3]),
+                if has_normals {
+                    na::Vector3::from_column_slice(&mesh.normals[i * 3..i * 3 + 3])
+                } else {
+                    na::Vector3::zeros()
+                },
+                if has_texture_coords {
+                    na::Vector2::new(mesh.texcoords[i * 2], mesh.texcoords[i * 2 + 1])
+                } else {
+                    na::Vector2::zeros()
+                },
+            )
+        })
+        .collect();
+    let mut indices: Vec<u32> = mesh.indices.clone();
+
+    if !has_normals {
+        match generated_normals {
+            GeneratedNormals::Smooth => smooth_normals(&mut vertices, &indices),
+            GeneratedNormals::Flat => (vertices, indices) = flat_shaded(&vertices, &indices),
+        }
+    }
+
+    // Tangents are only meaningful when the surface is parameterized by texture coords
+    if has_texture_coords && !generate_tangents(&mut vertices, &mut indices) {
+        warn!(
+            "Failed to generate tangents for mesh in model({})!",
+            model_name
+        );
+    }
+
+    (vertices, indices)
+}
+
+/// Material of OBJ mesh, or `None` for the default material if mesh has no `usemtl` or its material is not found.
+fn obj_material<'a>(
+    mesh: &tobj::Mesh,
+    materials: &'a [tobj::Material],
+    model_name: &str,
+) -> Option<&'a tobj::Material> {
+    let Some(material_id) = mesh.material_id else {
+        warn!(
+            "No material for mesh in model({}), default material is used!",
+            model_name
+        );
+        return None;
+    };
+
+    let material = materials.get(material_id);
+    if material.is_none() {
+        warn!(
+            "Material({}) of mesh in model({}) is not found, default material is used!",
+            material_id, model_name
+        );
+    }
+    material
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    fn fixture(filename: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/obj")
+            .join(filename)
+    }
+
+    fn load_fixture(filename: &str) -> (Vec<tobj::Model>, Vec<tobj::Material>) {
+        let (models, materials) = tobj::load_obj(fixture(filename), &OBJ_LOAD_OPTIONS).unwrap();
+        (models, materials.unwrap())
+    }
+
+    fn assert_near(actual: na::Vector3<f32>, expected: na::Vector3<f32>) {
+        assert!(
+            (actual - expected).norm() < 1e-5,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    /// Normal of triangles sharing edge on x axis in `tent.obj` & `tent_uv_seam.obj`.
+    fn tent_edge_normal() -> na::Vector3<f32> {
+        na::Vector3::new(0.0, -1.0, 1.0).normalize()
+    }
+
+    fn is_on_tent_edge(vertex: &Vertex) -> bool {
+        vertex.position.y == 0.0 && vertex.position.z == 0.0
+    }
+
+    #[test]
+    fn smooth_normals_without_vn() {
+        let (models, _) = load_fixture("tent.obj");
+        let (vertices, indices) = obj_vertices(&models[0].mesh, GeneratedNormals::Smooth, "tent");
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices.len(), 6);
+        for vertex in &vertices {
+            let expected = if is_on_tent_edge(vertex) {
+                tent_edge_normal()
+            } else if vertex.position.y > 0.0 {
+                na::Vector3::z()
+            } else {
+                -na::Vector3::y()
+            };
+            assert_near(vertex.normal, expected);
+        }
+    }
+
+    #[test]
+    fn smooth_normals_across_uv_seam() {
+        let (models, _) = load_fixture("tent_uv_seam.obj");
+        let (vertices, _) = obj_vertices(&models[0].mesh, GeneratedNormals::Smooth, "tent");
+
+        // Vertices on the edge are split by different texture coords
+        assert_eq!(vertices.len(), 6);
+        for vertex in vertices.iter().filter(|vertex| is_on_tent_edge(vertex)) {
+            assert_near(vertex.normal, tent_edge_normal());
+        }
+    }
+
+    #[test]
+    fn flat_normals_without_vn() {
+        let (models, _) = load_fixture("tent.obj");
+        let (vertices, indices) = obj_vertices(&models[0].mesh, GeneratedNormals::Flat, "tent");
+
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(indices, (0..6).collect::<Vec<u32>>());
+        for vertex in &vertices[..3] {
+            assert_near(vertex.normal, na::Vector3::z());
+        }
+        for vertex in &vertices[3..] {
+            assert_near(vertex.normal, -na::Vector3::y());
+        }
+    }
+
+    #[test]
+    fn default_texture_coords_without_vt() {
+        let (models, _) = load_fixture("tent.obj");
+        let (vertices, _) = obj_vertices(&models[0].mesh, GeneratedNormals::Smooth, "tent");
+
+        for vertex in &vertices {
+            assert_eq!(vertex.texture_coords, na::Vector2::zeros());
+            assert_eq!(vertex.tangent, na::Vector3::zeros());
+        }
+    }
+
+    #[test]
+    fn normals_and_texture_coords_from_file() {
+        let (models, _) = load_fixture("quad.obj");
+        let (vertices, indices) = obj_vertices(&models[0].mesh, GeneratedNormals::Flat, "quad");
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices.len(), 6);
+        for vertex in &vertices {
+            assert_near(vertex.normal, na::Vector3::z());
+            assert_eq!(
+                vertex.texture_coords,
+                vertex.position.xy(),
+                "texture coords match positions of unit quad"
+            );
+            assert_near(vertex.tangent, na::Vector3::x());
+            assert_near(vertex.bitangent, na::Vector3::y());
+        }
+    }
+
+    #[test]
+    fn material_of_usemtl() {
+        let (models, materials) = load_fixture("quad.obj");
+        let material = obj_material(&models[0].mesh, &materials, "quad").unwrap();
+
+        assert_eq!(material.name, "red");
+        assert_eq!(material.diffuse, Some([1.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn default_material_without_usemtl() {
+        let (models, materials) = load_fixture("tent.obj");
+
+        assert!(obj_material(&models[0].mesh, &materials, "tent").is_none());
+    }
+
+    #[test]
+    fn error_of_missing_mtl_names_obj_file() {
+        let error = Model::new(fixture("missing_mtl.obj")).err().unwrap();
+
+        assert!(
+            format!("{error:#}").contains("missing_mtl.obj"),
+            "{error:#}"
+        );
+    }
+
+    #[test]
+    fn error_of_missing_obj_names_obj_file() {
+        let error = Model::new(fixture("not_found.obj")).err().unwrap();
+
+        assert!(format!("{error:#}").contains("not_found.obj"), "{error:#}");
+    }
+
+    /// Load materials of `materials.obj` in order of its objects: glass, tinted, plain.
+    fn load_materials_fixture() -> Vec<MaterialMtl> {
+        let (models, materials) = load_fixture("materials.obj");
+        let mut model = Model {
+            meshes: Vec::new(),
+            nodes: Vec::new(),
+            textures_loaded: Vec::new(),
+            model_path: fixture("materials.obj"),
+            generated_normals: GeneratedNormals::default(),
+        };
+
+        models
+            .iter()
+            .map(|m| {
+                let material = obj_material(&m.mesh, &materials, &m.name).unwrap();
+                model.load_mtl_material(material).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mtl_colors_and_factors() {
+        let glass = &load_materials_fixture()[0];
+
+        assert_eq!(glass.name, "glass");
+        assert_eq!(glass.ambient, na::Vector3::new(0.1, 0.2, 0.3));
+        assert_eq!(glass.diffuse, na::Vector3::new(0.4, 0.5, 0.6));
+        assert_eq!(glass.specular, na::Vector3::new(0.7, 0.8, 0.9));
+        assert_eq!(glass.emissive, na::Vector3::new(0.0, 0.5, 1.0));
+        assert_eq!(glass.shininess, 32.0);
+        assert_eq!(glass.optical_density, 1.5);
+        assert_eq!(glass.dissolve, 0.5);
+        assert_eq!(glass.illumination_model, Some(4));
+        assert!(glass.is_transparent());
+    }
+
+    #[test]
+    fn mtl_dissolve_from_tr() {
+        let tinted = &load_materials_fixture()[1];
+
+        assert_eq!(tinted.dissolve, 0.75);
+        assert!(tinted.is_transparent());
+    }
+
+    #[test]
+    fn mtl_defaults_of_missing_properties() {
+        let plain = &load_materials_fixture()[2];
+        let default = MaterialMtl::default();
+
+        assert_eq!(plain.ambient, default.ambient);
+        assert_eq!(plain.diffuse, default.diffuse);
+        assert_eq!(plain.specular, default.specular);
+        assert_eq!(plain.shininess, default.shininess);
+        assert_eq!(plain.dissolve, 1.0);
+        assert!(plain.diffuse_map.is_none());
+        assert!(!plain.is_transparent());
+    }
+}
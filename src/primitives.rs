@@ -0,0 +1,464 @@
+//! Procedural primitive meshes with normals, texture coords and tangents.
+//!
+//! All meshes are centered at origin, Y is up, and triangles are counter-clockwise seen from outside. Texture coords
+//! start from bottom-left as OpenGL does, and tangents point to increasing `u`.
+
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+use anyhow::bail;
+use nalgebra as na;
+
+use crate::{Mesh, Vertex};
+
+/// Cube of edge length `size`, each face is a grid of `subdivisions` x `subdivisions` quads with its own texture
+/// coords from 0 to 1.
+pub fn cube(size: f32, subdivisions: u32) -> anyhow::Result<Mesh> {
+    check_segments("subdivisions", subdivisions, 1)?;
+
+    // (normal, tangent), whose cross product is bitangent, i.e. direction of increasing `v`
+    let faces = [
+        (na::Vector3::x(), -na::Vector3::z()),
+        (-na::Vector3::x(), na::Vector3::z()),
+        (na::Vector3::y(), na::Vector3::x()),
+        (-na::Vector3::y(), na::Vector3::x()),
+        (na::Vector3::z(), na::Vector3::x()),
+        (-na::Vector3::z(), -na::Vector3::x()),
+    ];
+
+    let mut geometry = Geometry::default();
+    for (normal, tangent) in faces {
+        let bitangent = normal.cross(&tangent);
+        geometry.push_grid(subdivisions, subdivisions, |u, v| {
+            let position = (normal * 0.5 + tangent * (u - 0.5) + bitangent * (v - 0.5)) * size;
+            vertex(position, normal, na::Vector2::new(u, v), tangent)
+        });
+    }
+
+    geometry.into_mesh()
+}
+
+/// Plane in XZ plane facing +Y, of `width` along X and `depth` along Z, which is a grid of
+/// `subdivisions_x` x `subdivisions_z` quads.
+pub fn plane(
+    width: f32,
+    depth: f32,
+    subdivisions_x: u32,
+    subdivisions_z: u32,
+) -> anyhow::Result<Mesh> {
+    check_segments("subdivisions_x", subdivisions_x, 1)?;
+    check_segments("subdivisions_z", subdivisions_z, 1)?;
+
+    let mut geometry = Geometry::default();
+    geometry.push_grid(subdivisions_x, subdivisions_z, |u, v| {
+        let position = na::Vector3::new((u - 0.5) * width, 0.0, (0.5 - v) * depth);
+        vertex(
+            position,
+            na::Vector3::y(),
+            na::Vector2::new(u, v),
+            na::Vector3::x(),
+        )
+    });
+
+    geometry.into_mesh()
+}
+
+/// Sphere of `sectors` segments around Y axis and `stacks` segments from south pole to north pole.
+///
+/// `u` starts from +Z and goes counter-clockwise seen from +Y, `v` goes from south pole to north pole.
+pub fn uv_sphere(radius: f32, sectors: u32, stacks: u32) -> anyhow::Result<Mesh> {
+    check_segments("sectors", sectors, 3)?;
+    check_segments("stacks", stacks, 2)?;
+
+    let mut geometry = Geometry::default();
+    geometry.push_grid(sectors, stacks, |u, v| {
+        let normal = sphere_normal(u * TAU, (1.0 - v) * PI);
+        vertex(
+            normal * radius,
+            normal,
+            na::Vector2::new(u, v),
+            around_y_tangent(u * TAU),
+        )
+    });
+
+    geometry.into_mesh()
+}
+
+/// Sphere subdivided from icosahedron `subdivisions` times, whose triangles are almost uniform.
+///
+/// Texture coords are mapped the same as `uv_sphere()`, and vertices on the seam are duplicated.
+pub fn icosphere(radius: f32, subdivisions: u32) -> anyhow::Result<Mesh> {
+    // Icosahedron made of 3 orthogonal golden rectangles
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let mut positions: Vec<na::Vector3<f32>> = [
+        (-1.0, t, 0.0),
+        (1.0, t, 0.0),
+        (-1.0, -t, 0.0),
+        (1.0, -t, 0.0),
+        (0.0, -1.0, t),
+        (0.0, 1.0, t),
+        (0.0, -1.0, -t),
+        (0.0, 1.0, -t),
+        (t, 0.0, -1.0),
+        (t, 0.0, 1.0),
+        (-t, 0.0, -1.0),
+        (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| na::Vector3::new(x, y, z).normalize())
+    .collect();
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    // Split each triangle into 4, vertices on the middle of edges are shared by adjacent triangles
+    for _ in 0..subdivisions {
+        let mut middles: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut middle = |a: u32, b: u32| {
+            *middles.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let position = (positions[a as usize] + positions[b as usize]).normalize();
+                positions.push(position);
+                positions.len() as u32 - 1
+            })
+        };
+        faces = faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = middle(a, b);
+                let bc = middle(b, c);
+                let ca = middle(c, a);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut geometry = Geometry::default();
+    // Vertex index of (position index, whether `u` is wrapped to be greater than 1)
+    let mut emitted: HashMap<(u32, bool), u32> = HashMap::new();
+    for face in faces {
+        let normals = face.map(|i| positions[i as usize]);
+        let poles = normals.map(|normal| normal.x.abs() < 1e-6 && normal.z.abs() < 1e-6);
+        let mut us = normals.map(|normal| normal.x.atan2(normal.z).rem_euclid(TAU) / TAU);
+
+        // Triangle crossing the seam at u = 0, make its `u` continuous
+        let (mut min_u, mut max_u) = (1.0_f32, 0.0_f32);
+        for (&u, _) in us.iter().zip(poles).filter(|(_, pole)| !pole) {
+            min_u = min_u.min(u);
+            max_u = max_u.max(u);
+        }
+        let mut wrapped = [false; 3];
+        for (u, wrapped) in us.iter_mut().zip(&mut wrapped) {
+            if max_u - min_u > 0.5 && *u < 0.5 {
+                *u += 1.0;
+                *wrapped = true;
+            }
+        }
+
+        let mut indices = [0; 3];
+        for k in 0..3 {
+            let normal = normals[k];
+            let v = 1.0 - normal.y.clamp(-1.0, 1.0).acos() / PI;
+
+            if poles[k] {
+                // `u` of pole is undefined, use the middle of other 2 vertices, so pole is split per triangle
+                let u = (us[(k + 1) % 3] + us[(k + 2) % 3]) / 2.0;
+                indices[k] = geometry.push_vertex(vertex(
+                    normal * radius,
+                    normal,
+                    na::Vector2::new(u, v),
+                    around_y_tangent(u * TAU),
+                ));
+                continue;
+            }
+
+            let u = us[k];
+            indices[k] = *emitted.entry((face[k], wrapped[k])).or_insert_with(|| {
+                geometry.vertices.push(vertex(
+                    normal * radius,
+                    normal,
+                    na::Vector2::new(u, v),
+                    around_y_tangent(u * TAU),
+                ));
+                geometry.vertices.len() as u32 - 1
+            });
+        }
+        geometry.indices.extend(indices);
+    }
+
+    geometry.into_mesh()
+}
+
+/// Cylinder along Y axis with caps, whose side is `sectors` segments around Y axis and `stacks` segments along it.
+pub fn cylinder(radius: f32, height: f32, sectors: u32, stacks: u32) -> anyhow::Result<Mesh> {
+    check_segments("sectors", sectors, 3)?;
+    check_segments("stacks", stacks, 1)?;
+
+    let mut geometry = Geometry::default();
+    geometry.push_grid(sectors, stacks, |u, v| {
+        let normal = sphere_normal(u * TAU, PI / 2.0);
+        let position = normal * radius + na::Vector3::y() * (v - 0.5) * height;
+        vertex(
+            position,
+            normal,
+            na::Vector2::new(u, v),
+            around_y_tangent(u * TAU),
+        )
+    });
+    geometry.push_disk(radius, height / 2.0, true, sectors);
+    geometry.push_disk(radius, -height / 2.0, false, sectors);
+
+    geometry.into_mesh()
+}
+
+/// Cone along Y axis with bottom cap, whose apex is at `height / 2`. The side is `sectors` segments around Y axis
+/// and `stacks` segments along it.
+pub fn cone(radius: f32, height: f32, sectors: u32, stacks: u32) -> anyhow::Result<Mesh> {
+    check_segments("sectors", sectors, 3)?;
+    check_segments("stacks", stacks, 1)?;
+
+    // Normal of side tilts up by the slope
+    let slope = (radius / height).atan();
+    let mut geometry = Geometry::default();
+    geometry.push_grid(sectors, stacks, |u, v| {
+        let outward = sphere_normal(u * TAU, PI / 2.0);
+        let position = outward * radius * (1.0 - v) + na::Vector3::y() * (v - 0.5) * height;
+        let normal = outward * slope.cos() + na::Vector3::y() * slope.sin();
+        vertex(
+            position,
+            normal,
+            na::Vector2::new(u, v),
+            around_y_tangent(u * TAU),
+        )
+    });
+    geometry.push_disk(radius, -height / 2.0, false, sectors);
+
+    geometry.into_mesh()
+}
+
+/// Torus around Y axis, `major_radius` is from center to the middle of tube, `minor_radius` is radius of tube.
+///
+/// `u` goes around Y axis in `major_segments`, and `v` goes around the tube in `minor_segments` from its outer side.
+pub fn torus(
+    major_radius: f32,
+    minor_radius: f32,
+    major_segments: u32,
+    minor_segments: u32,
+) -> anyhow::Result<Mesh> {
+    check_segments("major_segments", major_segments, 3)?;
+    check_segments("minor_segments", minor_segments, 3)?;
+
+    let mut geometry = Geometry::default();
+    geometry.push_grid(major_segments, minor_segments, |u, v| {
+        let outward = sphere_normal(u * TAU, PI / 2.0);
+        let (sin, cos) = (v * TAU).sin_cos();
+        let normal = outward * cos + na::Vector3::y() * sin;
+        let position = outward * major_radius + normal * minor_radius;
+        vertex(
+            position,
+            normal,
+            na::Vector2::new(u, v),
+            around_y_tangent(u * TAU),
+        )
+    });
+
+    geometry.into_mesh()
+}
+
+/// Capsule along Y axis, i.e. cylinder of `height` capped by hemispheres, so total height is `height + 2 * radius`.
+///
+/// Side is `sectors` segments around Y axis, each hemisphere is `hemisphere_stacks` segments, and `v` is
+/// proportional to length along the surface.
+pub fn capsule(
+    radius: f32,
+    height: f32,
+    sectors: u32,
+    hemisphere_stacks: u32,
+) -> anyhow::Result<Mesh> {
+    check_segments("sectors", sectors, 3)?;
+    check_segments("hemisphere_stacks", hemisphere_stacks, 1)?;
+
+    // Rows of vertices from bottom to top: (angle from +Y, offset along Y, `v`)
+    let arc = PI / 2.0 * radius;
+    let total = 2.0 * arc + height;
+    let mut rows = Vec::with_capacity(2 * hemisphere_stacks as usize + 2);
+    for i in 0..=hemisphere_stacks {
+        let t = i as f32 / hemisphere_stacks as f32;
+        rows.push((PI - t * PI / 2.0, -height / 2.0, t * arc / total));
+    }
+    for i in 0..=hemisphere_stacks {
+        let t = i as f32 / hemisphere_stacks as f32;
+        rows.push((
+            PI / 2.0 - t * PI / 2.0,
+            height / 2.0,
+            (arc + height + t * arc) / total,
+        ));
+    }
+
+    let mut geometry = Geometry::default();
+    geometry.push_grid(sectors, rows.len() as u32 - 1, |u, v| {
+        let row = (v * (rows.len() - 1) as f32).round() as usize;
+        let (polar, offset, v) = rows[row];
+        let normal = sphere_normal(u * TAU, polar);
+        vertex(
+            normal * radius + na::Vector3::y() * offset,
+            normal,
+            na::Vector2::new(u, v),
+            around_y_tangent(u * TAU),
+        )
+    });
+
+    geometry.into_mesh()
+}
+
+/// A triangle covering the whole viewport in NDC, for post-processing passes without a vertex transform.
+///
+/// Texture coords are 0 to 1 inside viewport, it's cheaper than a quad since no pixel is shaded twice on the
+/// diagonal.
+pub fn fullscreen_triangle() -> anyhow::Result<Mesh> {
+    let mut geometry = Geometry::default();
+    for (x, y) in [(-1.0, -1.0), (3.0, -1.0), (-1.0, 3.0)] {
+        geometry.push_vertex(vertex(
+            na::Vector3::new(x, y, 0.0),
+            na::Vector3::z(),
+            na::Vector2::new((x + 1.0) / 2.0, (y + 1.0) / 2.0),
+            na::Vector3::x(),
+        ));
+    }
+    geometry.indices.extend([0, 1, 2]);
+
+    geometry.into_mesh()
+}
+
+fn check_segments(name: &str, segments: u32, min: u32) -> anyhow::Result<()> {
+    if segments < min {
+        bail!("Primitive needs at least {min} {name}, got {segments}");
+    }
+
+    Ok(())
+}
+
+fn vertex(
+    position: na::Vector3<f32>,
+    normal: na::Vector3<f32>,
+    texture_coords: na::Vector2<f32>,
+    tangent: na::Vector3<f32>,
+) -> Vertex {
+    Vertex {
+        position,
+        normal,
+        texture_coords,
+        tangent,
+        bitangent: normal.cross(&tangent),
+    }
+}
+
+/// Unit vector at `azimuth` around Y axis from +Z, and `polar` angle from +Y.
+fn sphere_normal(azimuth: f32, polar: f32) -> na::Vector3<f32> {
+    na::Vector3::new(
+        polar.sin() * azimuth.sin(),
+        polar.cos(),
+        polar.sin() * azimuth.cos(),
+    )
+}
+
+/// Tangent of surfaces of revolution around Y axis, at `azimuth` from +Z.
+fn around_y_tangent(azimuth: f32) -> na::Vector3<f32> {
+    na::Vector3::new(azimuth.cos(), 0.0, -azimuth.sin())
+}
+
+#[derive(Default)]
+struct Geometry {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+impl Geometry {
+    fn push_vertex(&mut self, vertex: Vertex) -> u32 {
+        self.vertices.push(vertex);
+        self.vertices.len() as u32 - 1
+    }
+
+    /// Push a grid of `columns` x `rows` quads, `vertex` is called with `u` & `v` from 0 to 1. Quads are
+    /// counter-clockwise if tangent x bitangent (direction of increasing `v`) points outside.
+    fn push_grid(&mut self, columns: u32, rows: u32, vertex: impl Fn(f32, f32) -> Vertex) {
+        let first = self.vertices.len() as u32;
+        for j in 0..=rows {
+            for i in 0..=columns {
+                self.vertices
+                    .push(vertex(i as f32 / columns as f32, j as f32 / rows as f32));
+            }
+        }
+
+        let index = |i: u32, j: u32| first + j * (columns + 1) + i;
+        for j in 0..rows {
+            for i in 0..columns {
+                self.indices.extend([
+                    index(i, j),
+                    index(i + 1, j),
+                    index(i + 1, j + 1),
+                    index(i, j),
+                    index(i + 1, j + 1),
+                    index(i, j + 1),
+                ]);
+            }
+        }
+    }
+
+    /// Push a disk of `radius` at height `y` facing +Y or -Y, as a fan of `sectors` triangles. Texture coords are
+    /// its projection seen from outside.
+    fn push_disk(&mut self, radius: f32, y: f32, up: bool, sectors: u32) {
+        let normal = if up {
+            na::Vector3::y()
+        } else {
+            -na::Vector3::y()
+        };
+        // Seen from outside, `v` goes to -Z on top and +Z on bottom
+        let v_sign = if up { -1.0 } else { 1.0 };
+        let disk_vertex = |x: f32, z: f32| {
+            vertex(
+                na::Vector3::new(x * radius, y, z * radius),
+                normal,
+                na::Vector2::new(0.5 + x / 2.0, 0.5 + v_sign * z / 2.0),
+                na::Vector3::x(),
+            )
+        };
+
+        let center = self.push_vertex(disk_vertex(0.0, 0.0));
+        for i in 0..=sectors {
+            let (x, z) = (i as f32 / sectors as f32 * TAU).sin_cos();
+            self.vertices.push(disk_vertex(x, z));
+        }
+        for i in 0..sectors {
+            let (a, b) = (center + 1 + i, center + 2 + i);
+            // Ring goes counter-clockwise seen from +Y
+            if up {
+                self.indices.extend([center, a, b]);
+            } else {
+                self.indices.extend([center, b, a]);
+            }
+        }
+    }
+
+    fn into_mesh(self) -> anyhow::Result<Mesh> {
+        Mesh::new(self.vertices, self.indices, None, None, None, None)
+    }
+}
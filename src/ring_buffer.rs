@@ -0,0 +1,140 @@
+use std::marker::PhantomData;
+use std::mem;
+
+use anyhow::bail;
+use gl::types::*;
+use tracing::warn;
+
+use crate::{Buffer, BufferType, BufferUsage, MapAccessBit};
+
+/// Buffer Object split into `sections` of `capacity` elements each, for streaming data which is rewritten every frame,
+/// e.g. particle positions or debug lines.
+///
+/// Each `write()` fills the next section, so GPU can still read sections written in previous frames. A fence is
+/// inserted after a section is used, and writing into it again waits for the fence, so data in flight is never
+/// overwritten. 3 sections (triple buffering) are usually enough to never wait.
+///
+/// Requires OpenGL 3.2 for sync objects.
+pub struct RingBuffer<T: Copy> {
+    buffer: Buffer,
+    capacity: usize,
+    /// Fence of each section, which is signaled once commands reading the section are finished.
+    fences: Vec<GLsync>,
+    /// Section written by last `write()`, whose fence isn't inserted yet.
+    current: Option<usize>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy> RingBuffer<T> {
+    /// Create ring buffer of `sections * capacity` elements.
+    pub fn new(buffer_type: BufferType, capacity: usize, sections: usize) -> anyhow::Result<Self> {
+        if capacity == 0 || sections == 0 {
+            bail!("Ring buffer must have at least 1 section of 1 element");
+        }
+
+        let buffer = Buffer::new(buffer_type)?;
+        buffer.allocate(
+            sections * capacity * mem::size_of::<T>(),
+            BufferUsage::StreamDraw,
+        );
+
+        Ok(Self {
+            buffer,
+            capacity,
+            fences: vec![std::ptr::null(); sections],
+            current: None,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Write `data` into the next section, and return index of its first element in whole buffer, e.g. `first` of
+    /// `glDrawArrays` when vertex attributes start from the beginning of buffer.
+    ///
+    /// Call it after all commands reading the previously written section have been issued, since that section is
+    /// fenced here.
+    ///
+    /// wrap `glFenceSync`, `glClientWaitSync`, `glMapBufferRange`
+    pub fn write(&mut self, data: &[T]) -> anyhow::Result<usize> {
+        if data.len() > self.capacity {
+            bail!(
+                "{} elements exceed capacity of ring buffer section: {}",
+                data.len(),
+                self.capacity
+            );
+        }
+
+        let section = match self.current {
+            Some(current) => {
+                self.fences[current] = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+                (current + 1) % self.fences.len()
+            }
+            None => 0,
+        };
+        self.wait_section(section);
+        self.current = Some(section);
+
+        let first = section * self.capacity;
+        if !data.is_empty() {
+            // Fence has been waited, so no need to synchronize again by driver.
+            let mut mapped = self.buffer.map_range::<T>(
+                first * mem::size_of::<T>(),
+                data.len(),
+                MapAccessBit::Write as GLbitfield
+                    | MapAccessBit::InvalidateRange as GLbitfield
+                    | MapAccessBit::Unsynchronized as GLbitfield,
+            )?;
+            mapped.copy_from_slice(data);
+        }
+
+        Ok(first)
+    }
+
+    /// Block until commands reading `section` are finished, and delete its fence.
+    fn wait_section(&mut self, section: usize) {
+        let fence = std::mem::replace(&mut self.fences[section], std::ptr::null());
+        if fence.is_null() {
+            return;
+        }
+
+        unsafe {
+            // Flush for the first wait, otherwise the fence may never be signaled.
+            let mut flags = gl::SYNC_FLUSH_COMMANDS_BIT;
+            loop {
+                match gl::ClientWaitSync(fence, flags, 1_000_000_000) {
+                    gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => break,
+                    gl::TIMEOUT_EXPIRED => flags = 0,
+                    _ => {
+                        warn!("Failed to wait for fence of ring buffer section {section}");
+                        break;
+                    }
+                }
+            }
+            gl::DeleteSync(fence);
+        }
+    }
+
+    pub fn get_buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Max number of elements of each section.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of sections.
+    pub fn sections(&self) -> usize {
+        self.fences.len()
+    }
+}
+
+impl<T: Copy> Drop for RingBuffer<T> {
+    /// wrap `glDeleteSync`
+    fn drop(&mut self) {
+        for fence in &self.fences {
+            if !fence.is_null() {
+                unsafe { gl::DeleteSync(*fence) };
+            }
+        }
+    }
+}
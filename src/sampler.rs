@@ -0,0 +1,331 @@
+use std::sync::OnceLock;
+
+use gl::types::*;
+
+use crate::{get_gl_error, get_gl_version, has_gl_extension, ColorSpace, TextureUnit};
+
+/// `GL_TEXTURE_MAX_ANISOTROPY`, core since OpenGL 4.6 and same value as `GL_TEXTURE_MAX_ANISOTROPY_EXT`.
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+/// `GL_MAX_TEXTURE_MAX_ANISOTROPY`, core since OpenGL 4.6 and same value as `GL_MAX_TEXTURE_MAX_ANISOTROPY_EXT`.
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+/// Enum of texture wrapping modes, `GL_TEXTURE_WRAP_*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    /// Coords outside of texture sample `TextureOptions::border_color`.
+    ClampToBorder,
+    /// Requires OpenGL 4.4.
+    MirrorClampToEdge,
+}
+
+impl From<WrapMode> for GLenum {
+    fn from(val: WrapMode) -> Self {
+        match val {
+            WrapMode::Repeat => gl::REPEAT,
+            WrapMode::MirroredRepeat => gl::MIRRORED_REPEAT,
+            WrapMode::ClampToEdge => gl::CLAMP_TO_EDGE,
+            WrapMode::ClampToBorder => gl::CLAMP_TO_BORDER,
+            WrapMode::MirrorClampToEdge => gl::MIRROR_CLAMP_TO_EDGE,
+        }
+    }
+}
+
+/// Enum of filters between texels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    Nearest,
+    Linear,
+}
+
+/// Enum of filters between mipmap levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MipmapFilter {
+    /// Only level 0 is sampled.
+    None,
+    Nearest,
+    Linear,
+}
+
+/// Enum of depth comparison functions, `GL_TEXTURE_COMPARE_FUNC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareFunc {
+    Never,
+    Less,
+    LessEqual,
+    Equal,
+    NotEqual,
+    GreaterEqual,
+    Greater,
+    Always,
+}
+
+impl From<CompareFunc> for GLenum {
+    fn from(val: CompareFunc) -> Self {
+        match val {
+            CompareFunc::Never => gl::NEVER,
+            CompareFunc::Less => gl::LESS,
+            CompareFunc::LessEqual => gl::LEQUAL,
+            CompareFunc::Equal => gl::EQUAL,
+            CompareFunc::NotEqual => gl::NOTEQUAL,
+            CompareFunc::GreaterEqual => gl::GEQUAL,
+            CompareFunc::Greater => gl::GREATER,
+            CompareFunc::Always => gl::ALWAYS,
+        }
+    }
+}
+
+/// Sampling parameters of texture, applied to a texture by `Texture::set_options()` or to a `Sampler`.
+///
+/// Defaults are `REPEAT` wrapping and trilinear filtering without anisotropy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureOptions {
+    pub wrap_s: WrapMode,
+    pub wrap_t: WrapMode,
+    /// Only used by cubemaps & 3D textures.
+    pub wrap_r: WrapMode,
+    /// RGBA color of `WrapMode::ClampToBorder`.
+    pub border_color: [f32; 4],
+    pub min_filter: FilterMode,
+    pub mag_filter: FilterMode,
+    /// Mipmap levels must exist unless it's `MipmapFilter::None`.
+    pub mipmap_filter: MipmapFilter,
+    /// Max anisotropy of filtering, 1 disables it. It's clamped to the limit of implementation, and ignored without
+    /// `GL_EXT_texture_filter_anisotropic` (or OpenGL 4.6).
+    pub max_anisotropy: f32,
+    /// Bias added to mipmap level.
+    pub lod_bias: f32,
+    /// Compare depth textures with the reference value of coords by the function, e.g. for `sampler2DShadow`.
+    pub compare_func: Option<CompareFunc>,
+    /// Color space of 8 bits image data, which overrides `TextureType::color_space()` when textures are created from
+    /// images. It isn't a sampling parameter, so it's ignored by `Texture::set_options()` and `Sampler`.
+    pub color_space: Option<ColorSpace>,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            wrap_s: WrapMode::Repeat,
+            wrap_t: WrapMode::Repeat,
+            wrap_r: WrapMode::Repeat,
+            border_color: [0.0, 0.0, 0.0, 0.0],
+            min_filter: FilterMode::Linear,
+            mag_filter: FilterMode::Linear,
+            mipmap_filter: MipmapFilter::Linear,
+            max_anisotropy: 1.0,
+            lod_bias: 0.0,
+            compare_func: None,
+            color_space: None,
+        }
+    }
+}
+
+impl TextureOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set wrapping of all axes.
+    pub fn with_wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap_s = wrap;
+        self.wrap_t = wrap;
+        self.wrap_r = wrap;
+        self
+    }
+
+    pub fn with_wrap_s(mut self, wrap: WrapMode) -> Self {
+        self.wrap_s = wrap;
+        self
+    }
+
+    pub fn with_wrap_t(mut self, wrap: WrapMode) -> Self {
+        self.wrap_t = wrap;
+        self
+    }
+
+    pub fn with_wrap_r(mut self, wrap: WrapMode) -> Self {
+        self.wrap_r = wrap;
+        self
+    }
+
+    pub fn with_border_color(mut self, border_color: [f32; 4]) -> Self {
+        self.border_color = border_color;
+        self
+    }
+
+    /// Set both min & mag filters.
+    pub fn with_filter(mut self, filter: FilterMode) -> Self {
+        self.min_filter = filter;
+        self.mag_filter = filter;
+        self
+    }
+
+    pub fn with_min_filter(mut self, filter: FilterMode) -> Self {
+        self.min_filter = filter;
+        self
+    }
+
+    pub fn with_mag_filter(mut self, filter: FilterMode) -> Self {
+        self.mag_filter = filter;
+        self
+    }
+
+    pub fn with_mipmap_filter(mut self, filter: MipmapFilter) -> Self {
+        self.mipmap_filter = filter;
+        self
+    }
+
+    pub fn with_anisotropy(mut self, max_anisotropy: f32) -> Self {
+        self.max_anisotropy = max_anisotropy;
+        self
+    }
+
+    pub fn with_lod_bias(mut self, lod_bias: f32) -> Self {
+        self.lod_bias = lod_bias;
+        self
+    }
+
+    pub fn with_compare_func(mut self, compare_func: Option<CompareFunc>) -> Self {
+        self.compare_func = compare_func;
+        self
+    }
+
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = Some(color_space);
+        self
+    }
+
+    /// Value of `GL_TEXTURE_MIN_FILTER`, combined from min & mipmap filters.
+    pub fn gl_min_filter(&self) -> GLenum {
+        match (self.min_filter, self.mipmap_filter) {
+            (FilterMode::Nearest, MipmapFilter::None) => gl::NEAREST,
+            (FilterMode::Linear, MipmapFilter::None) => gl::LINEAR,
+            (FilterMode::Nearest, MipmapFilter::Nearest) => gl::NEAREST_MIPMAP_NEAREST,
+            (FilterMode::Linear, MipmapFilter::Nearest) => gl::LINEAR_MIPMAP_NEAREST,
+            (FilterMode::Nearest, MipmapFilter::Linear) => gl::NEAREST_MIPMAP_LINEAR,
+            (FilterMode::Linear, MipmapFilter::Linear) => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    /// Value of `GL_TEXTURE_MAG_FILTER`.
+    pub fn gl_mag_filter(&self) -> GLenum {
+        match self.mag_filter {
+            FilterMode::Nearest => gl::NEAREST,
+            FilterMode::Linear => gl::LINEAR,
+        }
+    }
+
+    /// Set all parameters by setters of integer, float & float vector parameters, so the same options are applied to
+    /// textures (`glTexParameter*`) and samplers (`glSamplerParameter*`).
+    pub(crate) fn apply(
+        &self,
+        set_i: impl Fn(GLenum, GLint),
+        set_f: impl Fn(GLenum, GLfloat),
+        set_fv: impl Fn(GLenum, *const GLfloat),
+    ) {
+        set_i(gl::TEXTURE_WRAP_S, GLenum::from(self.wrap_s) as GLint);
+        set_i(gl::TEXTURE_WRAP_T, GLenum::from(self.wrap_t) as GLint);
+        set_i(gl::TEXTURE_WRAP_R, GLenum::from(self.wrap_r) as GLint);
+        set_fv(gl::TEXTURE_BORDER_COLOR, self.border_color.as_ptr());
+        set_i(gl::TEXTURE_MIN_FILTER, self.gl_min_filter() as GLint);
+        set_i(gl::TEXTURE_MAG_FILTER, self.gl_mag_filter() as GLint);
+        set_f(gl::TEXTURE_LOD_BIAS, self.lod_bias);
+
+        match self.compare_func {
+            Some(compare_func) => {
+                set_i(
+                    gl::TEXTURE_COMPARE_MODE,
+                    gl::COMPARE_REF_TO_TEXTURE as GLint,
+                );
+                set_i(
+                    gl::TEXTURE_COMPARE_FUNC,
+                    GLenum::from(compare_func) as GLint,
+                );
+            }
+            None => set_i(gl::TEXTURE_COMPARE_MODE, gl::NONE as GLint),
+        }
+
+        if let Some(max_anisotropy) = max_texture_anisotropy() {
+            set_f(
+                TEXTURE_MAX_ANISOTROPY,
+                self.max_anisotropy.clamp(1.0, max_anisotropy),
+            );
+        } else if self.max_anisotropy > 1.0 {
+            tracing::debug!("Anisotropic filtering is unsupported, max anisotropy is ignored");
+        }
+    }
+}
+
+/// Limit of max anisotropy, or `None` if anisotropic filtering is unsupported.
+///
+/// It's queried once by the first call, since extensions are enumerated, and all contexts of the process are assumed to
+/// be of the same driver.
+///
+/// wrap `glGetFloatv` with `GL_MAX_TEXTURE_MAX_ANISOTROPY`
+fn max_texture_anisotropy() -> Option<f32> {
+    static MAX_ANISOTROPY: OnceLock<Option<f32>> = OnceLock::new();
+    *MAX_ANISOTROPY.get_or_init(|| {
+        let supported = get_gl_version() >= (4, 6)
+            || has_gl_extension("GL_EXT_texture_filter_anisotropic")
+            || has_gl_extension("GL_ARB_texture_filter_anisotropic");
+        if !supported {
+            return None;
+        }
+
+        let mut max_anisotropy = 1.0;
+        unsafe { gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy) };
+        Some(max_anisotropy)
+    })
+}
+
+/// Wrapper of [Sampler Object](https://www.khronos.org/opengl/wiki/Sampler_Object), requires OpenGL 3.3.
+///
+/// Sampler bound to a texture unit overrides sampling parameters of the texture bound to the unit, so a texture can be
+/// sampled in different ways without changing its parameters.
+#[derive(Debug)]
+pub struct Sampler {
+    pub id: GLuint,
+}
+
+impl Sampler {
+    /// wrap `glGenSamplers`
+    pub fn new(options: &TextureOptions) -> anyhow::Result<Self> {
+        let mut id = 0;
+        unsafe { gl::GenSamplers(1, &mut id) };
+        if id == 0 {
+            return Err(get_gl_error().unwrap().into());
+        }
+
+        let sampler = Self { id };
+        sampler.set_options(options);
+
+        Ok(sampler)
+    }
+
+    /// wrap `glSamplerParameter*`
+    pub fn set_options(&self, options: &TextureOptions) {
+        options.apply(
+            |name, value| unsafe { gl::SamplerParameteri(self.id, name, value) },
+            |name, value| unsafe { gl::SamplerParameterf(self.id, name, value) },
+            |name, value| unsafe { gl::SamplerParameterfv(self.id, name, value) },
+        );
+    }
+
+    /// wrap `glBindSampler`
+    pub fn bind(&self, unit: TextureUnit) {
+        unsafe { gl::BindSampler(GLint::from(unit) as GLuint, self.id) }
+    }
+
+    /// Unbind any sampler from `unit`, so parameters of texture are used again.
+    pub fn unbind(unit: TextureUnit) {
+        unsafe { gl::BindSampler(GLint::from(unit) as GLuint, 0) }
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteSamplers(1, &self.id) }
+    }
+}
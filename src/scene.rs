@@ -0,0 +1,459 @@
+use std::cell::Cell;
+use std::ffi::CString;
+use std::rc::Rc;
+
+use anyhow::bail;
+use nalgebra as na;
+
+use crate::{DirectionalLight, Mesh, Model, PointLight, ShaderProgram};
+
+/// Handle of node in `Scene`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// Object attached to scene node, placed by the world matrix of node.
+pub enum SceneObject {
+    Mesh(Rc<Mesh>),
+    Model(Rc<Model>),
+    /// Position of light is relative to node.
+    PointLight(PointLight),
+    /// Direction of light is relative to node.
+    DirectionalLight(DirectionalLight),
+    /// Camera at origin of node looking at its -z, with its projection.
+    Camera(na::Perspective3<f32>),
+}
+
+/// Node of `Scene` with local transform relative to its parent, which is applied in order of scale, rotation and
+/// translation.
+pub struct SceneNode {
+    pub name: Option<String>,
+    pub objects: Vec<SceneObject>,
+    translation: na::Vector3<f32>,
+    rotation: na::UnitQuaternion<f32>,
+    scale: na::Vector3<f32>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    // World matrix is cached until transforms of node or its ancestors change
+    world_matrix: Cell<na::Matrix4<f32>>,
+    dirty: Cell<bool>,
+}
+
+impl SceneNode {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            objects: Vec::new(),
+            translation: na::Vector3::zeros(),
+            rotation: na::UnitQuaternion::identity(),
+            scale: na::Vector3::new(1.0, 1.0, 1.0),
+            parent: None,
+            children: Vec::new(),
+            world_matrix: Cell::new(na::Matrix4::identity()),
+            dirty: Cell::new(true),
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_owned());
+        self
+    }
+
+    pub fn with_translation(mut self, translation: na::Vector3<f32>) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: na::UnitQuaternion<f32>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: na::Vector3<f32>) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_object(mut self, object: SceneObject) -> Self {
+        self.objects.push(object);
+        self
+    }
+
+    pub fn translation(&self) -> na::Vector3<f32> {
+        self.translation
+    }
+
+    pub fn rotation(&self) -> na::UnitQuaternion<f32> {
+        self.rotation
+    }
+
+    pub fn scale(&self) -> na::Vector3<f32> {
+        self.scale
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    /// Transform relative to parent: translation * rotation * scale.
+    pub fn local_matrix(&self) -> na::Matrix4<f32> {
+        na::Matrix4::new_translation(&self.translation)
+            * self.rotation.to_homogeneous()
+            * na::Matrix4::new_nonuniform_scaling(&self.scale)
+    }
+}
+
+impl Default for SceneNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Scene graph: hierarchy of nodes with attached meshes, models, lights and cameras.
+///
+/// World matrices of nodes are propagated from parents to children lazily: changing transform of a node marks it and
+/// its descendants dirty, and their world matrices are recomputed when queried or drawn.
+#[derive(Default)]
+pub struct Scene {
+    nodes: Vec<SceneNode>,
+    roots: Vec<NodeId>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `node` as a child of `parent`, or as a root node if `parent` is `None`.
+    pub fn add_node(&mut self, parent: Option<NodeId>, mut node: SceneNode) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        node.parent = parent;
+        node.children.clear();
+        node.dirty.set(true);
+        self.nodes.push(node);
+
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+
+        id
+    }
+
+    /// Move node under `parent`, or to root nodes if `parent` is `None`. Node keeps its local transform.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> anyhow::Result<()> {
+        // Node can't be moved under itself or its descendants
+        let mut ancestor = parent;
+        while let Some(ancestor_id) = ancestor {
+            if ancestor_id == id {
+                bail!("Scene node({:?}) can't be a child of its descendant", id);
+            }
+            ancestor = self.nodes[ancestor_id.0].parent;
+        }
+
+        match self.nodes[id.0].parent {
+            Some(old_parent) => self.nodes[old_parent.0].children.retain(|&c| c != id),
+            None => self.roots.retain(|&r| r != id),
+        }
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+        self.nodes[id.0].parent = parent;
+        self.mark_dirty(id);
+
+        Ok(())
+    }
+
+    pub fn node(&self, id: NodeId) -> &SceneNode {
+        &self.nodes[id.0]
+    }
+
+    /// Mutable node for its name & objects, transforms are set by `set_translation()` etc. of scene.
+    pub fn node_mut(&mut self, id: NodeId) -> &mut SceneNode {
+        &mut self.nodes[id.0]
+    }
+
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    /// Find the first node named `name`.
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .position(|node| node.name.as_deref() == Some(name))
+            .map(NodeId)
+    }
+
+    pub fn set_translation(&mut self, id: NodeId, translation: na::Vector3<f32>) {
+        self.nodes[id.0].translation = translation;
+        self.mark_dirty(id);
+    }
+
+    pub fn set_rotation(&mut self, id: NodeId, rotation: na::UnitQuaternion<f32>) {
+        self.nodes[id.0].rotation = rotation;
+        self.mark_dirty(id);
+    }
+
+    pub fn set_scale(&mut self, id: NodeId, scale: na::Vector3<f32>) {
+        self.nodes[id.0].scale = scale;
+        self.mark_dirty(id);
+    }
+
+    /// Transform from node to world: world matrix of parent * local matrix.
+    pub fn world_matrix(&self, id: NodeId) -> na::Matrix4<f32> {
+        let node = &self.nodes[id.0];
+        if node.dirty.get() {
+            let local_matrix = node.local_matrix();
+            let world_matrix = match node.parent {
+                Some(parent) => self.world_matrix(parent) * local_matrix,
+                None => local_matrix,
+            };
+            node.world_matrix.set(world_matrix);
+            node.dirty.set(false);
+        }
+
+        node.world_matrix.get()
+    }
+
+    /// Visit nodes depth-first from root nodes, parents before their children, with their world matrices.
+    pub fn traverse<F>(&self, mut visit: F) -> anyhow::Result<()>
+    where
+        F: FnMut(NodeId, &SceneNode, &na::Matrix4<f32>) -> anyhow::Result<()>,
+    {
+        let mut stack: Vec<NodeId> = self.roots.iter().rev().copied().collect();
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id.0];
+            visit(id, node, &self.world_matrix(id))?;
+            stack.extend(node.children.iter().rev());
+        }
+
+        Ok(())
+    }
+
+    /// Draw meshes & models of all nodes with `shader`, uniforms `model` (mat4) and `normal_matrix` (mat3) are set
+    /// to world matrix of each node.
+    pub fn draw(&self, shader: &ShaderProgram, material_uniform_name: &str) -> anyhow::Result<()> {
+        let model_name = CString::new("model")?;
+        let normal_matrix_name = CString::new("normal_matrix")?;
+
+        shader.bind();
+        self.traverse(|_, node, world_matrix| {
+            if node
+                .objects
+                .iter()
+                .all(|object| !matches!(object, SceneObject::Mesh(_) | SceneObject::Model(_)))
+            {
+                return Ok(());
+            }
+
+            let normal_matrix = world_matrix
+                .fixed_view::<3, 3>(0, 0)
+                .try_inverse()
+                .unwrap_or_else(na::Matrix3::identity)
+                .transpose();
+            shader.set_uniform_mat4fv(&model_name, world_matrix);
+            shader.set_uniform_mat3fv(&normal_matrix_name, &normal_matrix);
+
+            for object in node.objects.iter() {
+                match object {
+                    SceneObject::Mesh(mesh) => mesh.draw(shader, material_uniform_name)?,
+                    SceneObject::Model(model) => model.draw(shader, material_uniform_name)?,
+                    _ => {}
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Point lights of all nodes, with positions in world space.
+    pub fn point_lights(&self) -> Vec<PointLight> {
+        let mut lights = Vec::new();
+        self.for_each_object(|object, world_matrix| {
+            if let SceneObject::PointLight(light) = object {
+                lights.push(PointLight::new(
+                    world_matrix
+                        .transform_point(&na::Point3::from(light.position))
+                        .coords,
+                    light.color,
+                    light.attenuation_linear,
+                    light.attenuation_quadratic,
+                ));
+            }
+        });
+
+        lights
+    }
+
+    /// Directional lights of all nodes, with directions in world space.
+    pub fn directional_lights(&self) -> Vec<DirectionalLight> {
+        let mut lights = Vec::new();
+        self.for_each_object(|object, world_matrix| {
+            if let SceneObject::DirectionalLight(light) = object {
+                lights.push(DirectionalLight::new(
+                    world_matrix.transform_vector(&light.direction).normalize(),
+                    light.color,
+                ));
+            }
+        });
+
+        lights
+    }
+
+    /// View & projection matrices of the first camera attached to node, or `None` if it has no camera.
+    pub fn camera_matrices(&self, id: NodeId) -> Option<(na::Matrix4<f32>, na::Matrix4<f32>)> {
+        let projection = self.nodes[id.0]
+            .objects
+            .iter()
+            .find_map(|object| match object {
+                SceneObject::Camera(projection) => Some(projection),
+                _ => None,
+            })?;
+        let view = self.world_matrix(id).try_inverse()?;
+
+        Some((view, projection.to_homogeneous()))
+    }
+
+    fn for_each_object(&self, mut visit: impl FnMut(&SceneObject, &na::Matrix4<f32>)) {
+        for (i, node) in self.nodes.iter().enumerate() {
+            let world_matrix = self.world_matrix(NodeId(i));
+            for object in node.objects.iter() {
+                visit(object, &world_matrix);
+            }
+        }
+    }
+
+    /// Mark node and its descendants dirty. Descendants of a dirty node are always dirty, so they're skipped: a node
+    /// is only cleaned by `world_matrix()` after its ancestors, and is marked dirty together with its descendants.
+    fn mark_dirty(&self, id: NodeId) {
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id.0];
+            if node.dirty.get() {
+                continue;
+            }
+            node.dirty.set(true);
+            stack.extend(node.children.iter());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Position of origin of node in world.
+    fn world_origin(scene: &Scene, id: NodeId) -> na::Point3<f32> {
+        scene
+            .world_matrix(id)
+            .transform_point(&na::Point3::origin())
+    }
+
+    #[test]
+    fn world_matrix_composes_parents() {
+        let mut scene = Scene::new();
+        let parent = scene.add_node(
+            None,
+            SceneNode::new()
+                .with_translation(na::Vector3::new(1.0, 0.0, 0.0))
+                .with_scale(na::Vector3::new(2.0, 2.0, 2.0)),
+        );
+        let child = scene.add_node(
+            Some(parent),
+            SceneNode::new().with_translation(na::Vector3::new(0.0, 1.0, 0.0)),
+        );
+
+        assert_eq!(
+            scene.world_matrix(child),
+            scene.node(parent).local_matrix() * scene.node(child).local_matrix()
+        );
+        assert_eq!(world_origin(&scene, child), na::Point3::new(1.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn cached_descendants_follow_ancestor() {
+        let mut scene = Scene::new();
+        let root = scene.add_node(None, SceneNode::new());
+        let child = scene.add_node(
+            Some(root),
+            SceneNode::new().with_translation(na::Vector3::new(0.0, 1.0, 0.0)),
+        );
+        let grandchild = scene.add_node(
+            Some(child),
+            SceneNode::new().with_translation(na::Vector3::new(0.0, 0.0, 1.0)),
+        );
+
+        // Cache world matrices of all nodes
+        assert_eq!(
+            world_origin(&scene, grandchild),
+            na::Point3::new(0.0, 1.0, 1.0)
+        );
+
+        scene.set_translation(root, na::Vector3::new(5.0, 0.0, 0.0));
+        assert_eq!(world_origin(&scene, child), na::Point3::new(5.0, 1.0, 0.0));
+        assert_eq!(
+            world_origin(&scene, grandchild),
+            na::Point3::new(5.0, 1.0, 1.0)
+        );
+
+        // Changing an already dirty ancestor again still reaches descendants
+        scene.set_translation(root, na::Vector3::new(6.0, 0.0, 0.0));
+        scene.set_translation(child, na::Vector3::new(0.0, 2.0, 0.0));
+        assert_eq!(
+            world_origin(&scene, grandchild),
+            na::Point3::new(6.0, 2.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn set_parent_moves_node() {
+        let mut scene = Scene::new();
+        let a = scene.add_node(
+            None,
+            SceneNode::new().with_translation(na::Vector3::new(1.0, 0.0, 0.0)),
+        );
+        let b = scene.add_node(
+            None,
+            SceneNode::new().with_translation(na::Vector3::new(0.0, 10.0, 0.0)),
+        );
+        let node = scene.add_node(
+            Some(a),
+            SceneNode::new().with_translation(na::Vector3::new(0.0, 0.0, 1.0)),
+        );
+        let child = scene.add_node(Some(node), SceneNode::new());
+        assert_eq!(world_origin(&scene, child), na::Point3::new(1.0, 0.0, 1.0));
+
+        scene.set_parent(node, Some(b)).unwrap();
+        assert_eq!(scene.node(node).parent(), Some(b));
+        assert!(scene.node(a).children().is_empty());
+        assert_eq!(scene.node(b).children(), [node]);
+        assert_eq!(world_origin(&scene, child), na::Point3::new(0.0, 10.0, 1.0));
+
+        scene.set_parent(node, None).unwrap();
+        assert_eq!(scene.node(node).parent(), None);
+        assert!(scene.node(b).children().is_empty());
+        assert_eq!(scene.roots(), [a, b, node]);
+        assert_eq!(world_origin(&scene, child), na::Point3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn set_parent_rejects_descendants() {
+        let mut scene = Scene::new();
+        let root = scene.add_node(None, SceneNode::new());
+        let child = scene.add_node(Some(root), SceneNode::new());
+        let grandchild = scene.add_node(Some(child), SceneNode::new());
+
+        assert!(scene.set_parent(root, Some(grandchild)).is_err());
+        assert!(scene.set_parent(child, Some(child)).is_err());
+
+        // Hierarchy is unchanged
+        assert_eq!(scene.roots(), [root]);
+        assert_eq!(scene.node(root).parent(), None);
+        assert_eq!(scene.node(child).children(), [grandchild]);
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::bail;
+use gl::types::*;
+use image::{DynamicImage, Rgb32FImage, RgbaImage};
+use tracing::info;
+
+use crate::Framebuffer;
+
+/// Default directory of screenshots captured by hotkey.
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Read a region of the currently bound read framebuffer as 8-bit RGBA image.
+///
+/// OpenGL images start from bottom-left corner, so rows are flipped to make top-left corner as origin.
+///
+/// wrap `glReadPixels`
+pub fn read_pixels(x: i32, y: i32, width: u32, height: u32) -> DynamicImage {
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    let _pack_alignment = TightPackAlignment::new();
+    unsafe {
+        gl::ReadPixels(
+            x,
+            y,
+            width as GLsizei,
+            height as GLsizei,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr() as *mut _,
+        );
+    }
+
+    let mut image = RgbaImage::from_raw(width, height, pixels).unwrap();
+    image::imageops::flip_vertical_in_place(&mut image);
+
+    DynamicImage::ImageRgba8(image)
+}
+
+/// Read a region of the currently bound read framebuffer as 32-bit float RGB image, which keeps HDR values.
+///
+/// wrap `glReadPixels`
+pub fn read_pixels_hdr(x: i32, y: i32, width: u32, height: u32) -> Rgb32FImage {
+    let mut pixels = vec![0f32; (width * height * 3) as usize];
+    let _pack_alignment = TightPackAlignment::new();
+    unsafe {
+        gl::ReadPixels(
+            x,
+            y,
+            width as GLsizei,
+            height as GLsizei,
+            gl::RGB,
+            gl::FLOAT,
+            pixels.as_mut_ptr() as *mut _,
+        );
+    }
+
+    let mut image = Rgb32FImage::from_raw(width, height, pixels).unwrap();
+    image::imageops::flip_vertical_in_place(&mut image);
+
+    image
+}
+
+/// Pack pixel rows tightly by `GL_PACK_ALIGNMENT` of 1 while it's alive, so rows of read images have no padding.
+/// Previous alignment is restored when it's dropped.
+struct TightPackAlignment {
+    previous: GLint,
+}
+
+impl TightPackAlignment {
+    fn new() -> Self {
+        let mut previous = 4;
+        unsafe {
+            gl::GetIntegerv(gl::PACK_ALIGNMENT, &mut previous);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        }
+        Self { previous }
+    }
+}
+
+impl Drop for TightPackAlignment {
+    fn drop(&mut self) {
+        unsafe { gl::PixelStorei(gl::PACK_ALIGNMENT, self.previous) }
+    }
+}
+
+/// Read back buffer of default framebuffer.
+///
+/// Note: content of back buffer is undefined after swapping, so call it before `swap_buffers()`.
+pub fn read_default_framebuffer(width: u32, height: u32) -> DynamicImage {
+    let default_id = Framebuffer::default_id();
+    unsafe {
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, default_id);
+        gl::ReadBuffer(if default_id == 0 {
+            gl::BACK
+        } else {
+            gl::COLOR_ATTACHMENT0
+        });
+    }
+
+    read_pixels(0, 0, width, height)
+}
+
+/// Save HDR image to Radiance (`.hdr`) or OpenEXR (`.exr`) file, according to extension of `path`.
+pub fn save_hdr_image(image: &Rgb32FImage, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("hdr") => {
+            let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+            let pixels: Vec<image::Rgb<f32>> = image.pixels().copied().collect();
+            image::codecs::hdr::HdrEncoder::new(file).encode(
+                &pixels,
+                image.width() as usize,
+                image.height() as usize,
+            )?;
+        }
+        Some(ext) if ext.eq_ignore_ascii_case("exr") => {
+            DynamicImage::ImageRgb32F(image.clone()).save(path)?;
+        }
+        _ => bail!("Unsupported HDR image format: {}", path.display()),
+    }
+
+    Ok(())
+}
+
+/// Capture back buffer of default framebuffer and save it as PNG into `SCREENSHOT_DIR`, named by current timestamp.
+pub fn capture_screenshot(width: u32, height: u32) -> anyhow::Result<PathBuf> {
+    let image = read_default_framebuffer(width, height);
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis();
+    std::fs::create_dir_all(SCREENSHOT_DIR)?;
+    let path = PathBuf::from(SCREENSHOT_DIR).join(format!("screenshot-{timestamp}.png"));
+
+    image.save(&path)?;
+    info!("Save screenshot to {}", path.display());
+
+    Ok(path)
+}
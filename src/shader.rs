@@ -1,20 +1,52 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::ffi::{CStr, CString};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
 
 use anyhow::bail;
 use gl::types::*;
 use nalgebra as na;
 use nalgebra_glm as glm;
+use tracing::warn;
 
 use crate::{
-    get_gl_error, Camera, DirectionalLight, FlashLight, MaterialPhong, PointLight, Texture,
-    TextureUnit,
+    get_gl_error, get_gl_version, query_active_uniforms, query_uniform_blocks, Camera,
+    DirectionalLight, FlashLight, MaterialPhong, PointLight, Std140, Texture, TextureUnit,
+    UniformBlockInfo, UniformBuffer, UniformError, UniformInfo, UniformValue,
+    FRAME_UNIFORMS_BINDING, FRAME_UNIFORMS_BLOCK,
 };
 
 /// enum of Shader types
-#[derive(Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ShaderType {
     Vertex = gl::VERTEX_SHADER as isize,
     Fragment = gl::FRAGMENT_SHADER as isize,
+    /// Requires OpenGL 3.2
+    Geometry = gl::GEOMETRY_SHADER as isize,
+    /// Requires OpenGL 4.0
+    TessControl = gl::TESS_CONTROL_SHADER as isize,
+    /// Requires OpenGL 4.0
+    TessEvaluation = gl::TESS_EVALUATION_SHADER as isize,
+    /// Requires OpenGL 4.3
+    Compute = gl::COMPUTE_SHADER as isize,
+}
+
+impl ShaderType {
+    /// Minimum OpenGL version `(major, minor)` which supports this shader stage in core profile.
+    pub fn min_gl_version(&self) -> (i32, i32) {
+        match self {
+            ShaderType::Vertex | ShaderType::Fragment => (2, 0),
+            ShaderType::Geometry => (3, 2),
+            ShaderType::TessControl | ShaderType::TessEvaluation => (4, 0),
+            ShaderType::Compute => (4, 3),
+        }
+    }
+
+    /// Whether this shader stage is supported by current OpenGL context.
+    pub fn is_supported(&self) -> bool {
+        get_gl_version() >= self.min_gl_version()
+    }
 }
 
 /// Wrapper of [Shader Object](https://www.khronos.org/opengl/wiki/GLSL_Object#Shader_objects)
@@ -28,6 +60,15 @@ impl Shader {
     ///
     /// wrap `glCreateShader`.
     fn new(shader_type: ShaderType) -> anyhow::Result<Self> {
+        if !shader_type.is_supported() {
+            let (major, minor) = shader_type.min_gl_version();
+            let (current_major, current_minor) = get_gl_version();
+            bail!(
+                "{shader_type:?} shader requires OpenGL {major}.{minor}, but current version is \
+                 {current_major}.{current_minor}"
+            );
+        }
+
         let shader = unsafe { gl::CreateShader(shader_type as GLenum) };
         if shader != 0 {
             Ok(Self { id: shader })
@@ -100,14 +141,23 @@ impl Shader {
         Ok(shader)
     }
 
-    /// Create/Attach/Link shader program from shader file
+    /// Create/Attach/Link shader program from shader file, whose `#include`s are expanded by default
+    /// `ShaderPreprocessor`.
     pub fn from_file(shader_type: ShaderType, path: &str) -> anyhow::Result<Self> {
-        let source = match std::fs::read_to_string(path) {
-            Ok(source) => source,
-            Err(e) => bail!("Failed to read shader file: {e}"),
-        };
+        let source = ShaderPreprocessor::new().process_file(path)?;
 
-        Self::from_source(shader_type, &source)
+        Self::from_preprocessed(shader_type, &source)
+    }
+
+    /// Create shader from preprocessed source. Locations in compiling errors are mapped back to original files.
+    pub fn from_preprocessed(
+        shader_type: ShaderType,
+        source: &ShaderSource,
+    ) -> anyhow::Result<Self> {
+        match Self::from_source(shader_type, &source.source) {
+            Ok(shader) => Ok(shader),
+            Err(e) => bail!(source.map_error_log(&e.to_string())),
+        }
     }
 }
 
@@ -120,6 +170,12 @@ impl Drop for Shader {
 /// Wrapper of [Program Object](https://www.khronos.org/opengl/wiki/GLSL_Object#Program_objects)
 pub struct ShaderProgram {
     pub id: GLuint,
+    /// Active uniforms queried after linking, see `get_uniforms()`.
+    uniforms: HashMap<String, UniformInfo>,
+    /// Active uniform blocks queried after linking, see `get_uniform_blocks()`.
+    uniform_blocks: HashMap<String, UniformBlockInfo>,
+    /// Uniforms which `set_uniform()` has warned about, so that the log isn't flooded every frame.
+    warned_uniforms: RefCell<HashSet<String>>,
 }
 
 impl ShaderProgram {
@@ -129,20 +185,37 @@ impl ShaderProgram {
     fn new() -> anyhow::Result<Self> {
         let program = unsafe { gl::CreateProgram() };
         if program != 0 {
-            Ok(Self { id: program })
+            Ok(Self {
+                id: program,
+                uniforms: HashMap::new(),
+                uniform_blocks: HashMap::new(),
+                warned_uniforms: RefCell::new(HashSet::new()),
+            })
         } else {
             Err(get_gl_error().unwrap().into())
         }
     }
 
+    /// Create a builder, which accepts any combination of shader stages.
+    pub fn builder() -> ShaderProgramBuilder {
+        ShaderProgramBuilder::new()
+    }
+
     /// Create Shader Program from vertex & fragment Shader Objects.
     /// This calling will consume Shader Objects.
     pub fn create(vert_shader: Shader, frag_shader: Shader) -> anyhow::Result<Self> {
-        let program = Self::new()?;
+        Self::create_from_shaders(vec![vert_shader, frag_shader])
+    }
 
-        // Attach vertex & fragment shader to program
-        program.attach_shader(&vert_shader);
-        program.attach_shader(&frag_shader);
+    /// Create Shader Program from Shader Objects of any stages.
+    /// This calling will consume Shader Objects.
+    pub fn create_from_shaders(shaders: Vec<Shader>) -> anyhow::Result<Self> {
+        let mut program = Self::new()?;
+
+        // Attach all shaders to program
+        for shader in &shaders {
+            program.attach_shader(shader);
+        }
 
         // Link all attached shader stages into program
         let link_rst = program.link_program();
@@ -150,12 +223,24 @@ impl ShaderProgram {
         // Delete shaders after link completed
         // tip: Of course, this operation does not need to be written out because it will be done on destructing. But
         //      for learning, I think it's necessary to write it out.
-        vert_shader.delete();
-        frag_shader.delete();
+        for shader in shaders {
+            shader.delete();
+        }
 
         match link_rst {
-            Ok(_) => Ok(program),
-            Err(msg) => Err(msg),
+            Ok(_) => {
+                program.uniforms = query_active_uniforms(program.id);
+                program.uniform_blocks = query_uniform_blocks(program.id);
+                // Shared per-frame block is always at its reserved binding point.
+                if program.uniform_blocks.contains_key(FRAME_UNIFORMS_BLOCK) {
+                    program.bind_uniform_block(FRAME_UNIFORMS_BLOCK, FRAME_UNIFORMS_BINDING)?;
+                }
+                Ok(program)
+            }
+            Err(msg) => {
+                program.close();
+                Err(msg)
+            }
         }
     }
 
@@ -178,12 +263,27 @@ impl ShaderProgram {
 
     /// Create Program Object from vertex & fragment shader file
     pub fn create_from_file(vert_path: &str, frag_path: &str) -> anyhow::Result<Self> {
+        Self::create_from_file_with(vert_path, frag_path, &ShaderPreprocessor::new())
+    }
+
+    /// Create Program Object from vertex & fragment shader file, which are preprocessed by `preprocessor`.
+    pub fn create_from_file_with(
+        vert_path: &str,
+        frag_path: &str,
+        preprocessor: &ShaderPreprocessor,
+    ) -> anyhow::Result<Self> {
         // Create vertex & fragment shader
-        let vert_shader = match Shader::from_file(ShaderType::Vertex, vert_path) {
+        let vert_shader = match preprocessor
+            .process_file(vert_path)
+            .and_then(|source| Shader::from_preprocessed(ShaderType::Vertex, &source))
+        {
             Ok(vert) => vert,
             Err(e) => bail!("Vertex Shader creation Error: {e}"),
         };
-        let frag_shader = match Shader::from_file(ShaderType::Fragment, frag_path) {
+        let frag_shader = match preprocessor
+            .process_file(frag_path)
+            .and_then(|source| Shader::from_preprocessed(ShaderType::Fragment, &source))
+        {
             Ok(frag) => frag,
             Err(e) => bail!("Fragment Shader creation Error: {e}"),
         };
@@ -253,6 +353,150 @@ impl ShaderProgram {
         unsafe { gl::DeleteProgram(self.id) };
     }
 
+    /// Launch compute work groups of this compute program, `x * y * z` groups in total.
+    ///
+    /// Note: results written by compute shader are visible to following commands only after a proper
+    /// `memory_barrier()`.
+    ///
+    /// wrap `glDispatchCompute`
+    pub fn dispatch(&self, x: u32, y: u32, z: u32) {
+        self.bind();
+        unsafe { gl::DispatchCompute(x, y, z) };
+    }
+
+    /// Get local work group size declared by `layout(local_size_x = ..., ...) in;` of compute shader.
+    ///
+    /// wrap `glGetProgramiv` with `GL_COMPUTE_WORK_GROUP_SIZE`
+    pub fn get_work_group_size(&self) -> [i32; 3] {
+        let mut size = [0; 3];
+        unsafe { gl::GetProgramiv(self.id, gl::COMPUTE_WORK_GROUP_SIZE, size.as_mut_ptr()) };
+        size
+    }
+
+    /// Get all active uniforms by name.
+    ///
+    /// Array uniform `foo[N]` is listed as both `foo` (whole array) and `foo[i]` (from element `i` to the end), and
+    /// members of struct are listed by full name, e.g. `lights[0].position`. Members of uniform blocks are not listed.
+    pub fn get_uniforms(&self) -> &HashMap<String, UniformInfo> {
+        &self.uniforms
+    }
+
+    /// Get info of an active uniform by name, see `get_uniforms()`.
+    pub fn get_uniform_info(&self, name: &str) -> Option<&UniformInfo> {
+        self.uniforms.get(name)
+    }
+
+    /// Get all active uniform blocks by block name.
+    pub fn get_uniform_blocks(&self) -> &HashMap<String, UniformBlockInfo> {
+        &self.uniform_blocks
+    }
+
+    /// Get info of an active uniform block by block name, see `get_uniform_blocks()`.
+    pub fn get_uniform_block_info(&self, name: &str) -> Option<&UniformBlockInfo> {
+        self.uniform_blocks.get(name)
+    }
+
+    /// Connect uniform block `name` to uniform buffer binding point `binding`.
+    ///
+    /// Tip: `FrameUniforms` block is connected to `FRAME_UNIFORMS_BINDING` automatically after linking.
+    ///
+    /// wrap `glUniformBlockBinding`
+    pub fn bind_uniform_block(&self, name: &str, binding: GLuint) -> Result<(), UniformError> {
+        let info = match self.uniform_blocks.get(name) {
+            Some(info) => info,
+            None => return Err(UniformError::BlockNotFound(name.to_string())),
+        };
+        unsafe { gl::UniformBlockBinding(self.id, info.index, binding) };
+
+        Ok(())
+    }
+
+    /// Get uniform buffer binding point which uniform block `name` is connected to.
+    ///
+    /// wrap `glGetActiveUniformBlockiv` with `GL_UNIFORM_BLOCK_BINDING`
+    pub fn get_uniform_block_binding(&self, name: &str) -> Option<GLuint> {
+        let info = self.uniform_blocks.get(name)?;
+        let mut binding = 0;
+        unsafe {
+            gl::GetActiveUniformBlockiv(
+                self.id,
+                info.index,
+                gl::UNIFORM_BLOCK_BINDING,
+                &mut binding,
+            )
+        };
+        Some(binding as GLuint)
+    }
+
+    /// Connect uniform block `name` to binding point of `buffer`, after checking that `buffer` is large enough for
+    /// the block.
+    pub fn bind_uniform_buffer<T: Std140>(
+        &self,
+        name: &str,
+        buffer: &UniformBuffer<T>,
+    ) -> Result<(), UniformError> {
+        let info = match self.uniform_blocks.get(name) {
+            Some(info) => info,
+            None => return Err(UniformError::BlockNotFound(name.to_string())),
+        };
+        if buffer.get_size() < info.size {
+            return Err(UniformError::BlockSizeMismatch {
+                name: name.to_string(),
+                size: info.size,
+                buffer_size: buffer.get_size(),
+            });
+        }
+
+        self.bind_uniform_block(name, buffer.get_binding())
+    }
+
+    /// Send uniform data of any `UniformValue` type, e.g. `f32`, `glm::Vec3`, `glm::Mat4`, or `[glm::Vec3; N]` for
+    /// array uniforms.
+    ///
+    /// Location is looked up from the cached uniforms, and type & array size are checked against the declaration.
+    ///
+    /// wrap `glUniform*`
+    ///
+    /// Tips: it'll call `bind()` automatically.
+    pub fn try_set_uniform<T: UniformValue + ?Sized>(
+        &self,
+        name: &str,
+        value: &T,
+    ) -> Result<(), UniformError> {
+        let info = match self.uniforms.get(name) {
+            Some(info) => info,
+            None => return Err(UniformError::NotFound(name.to_string())),
+        };
+        if !T::accepts(info.ty) {
+            return Err(UniformError::TypeMismatch {
+                name: name.to_string(),
+                expected: info.type_name(),
+                actual: T::TYPE_NAME,
+            });
+        }
+        if value.count() > info.size {
+            return Err(UniformError::TooManyElements {
+                name: name.to_string(),
+                size: info.size,
+                count: value.count(),
+            });
+        }
+
+        self.bind();
+        value.upload(info.location);
+
+        Ok(())
+    }
+
+    /// Same as `try_set_uniform()`, but logs a warning instead of returning error. Each uniform is warned only once.
+    pub fn set_uniform<T: UniformValue + ?Sized>(&self, name: &str, value: &T) {
+        if let Err(e) = self.try_set_uniform(name, value) {
+            if self.warned_uniforms.borrow_mut().insert(name.to_string()) {
+                warn!("Failed to set uniform of shader program {}: {e}", self.id);
+            }
+        }
+    }
+
     /// wrap `glGetUniformLocation`
     pub fn get_uniform_location(&self, uniform_name: &CStr) -> i32 {
         unsafe { gl::GetUniformLocation(self.id, uniform_name.as_ptr().cast()) }
@@ -508,3 +752,548 @@ impl ShaderProgram {
         Ok(())
     }
 }
+
+/// Source of a shader stage which will be compiled by [`ShaderProgramBuilder`].
+enum ShaderStageSource {
+    Source(String),
+    File(PathBuf),
+}
+
+/// Builder of [`ShaderProgram`] with any combination of shader stages.
+///
+/// ```ignore
+/// let program = ShaderProgram::builder()
+///     .with_file(ShaderType::Vertex, "assets/shaders/foo.vert")
+///     .with_file(ShaderType::Geometry, "assets/shaders/foo.geom")
+///     .with_file(ShaderType::Fragment, "assets/shaders/foo.frag")
+///     .build()?;
+/// ```
+#[derive(Default)]
+pub struct ShaderProgramBuilder {
+    stages: Vec<(ShaderType, ShaderStageSource)>,
+    preprocessor: ShaderPreprocessor,
+}
+
+impl ShaderProgramBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a shader stage from source.
+    ///
+    /// Tip: you can use `include_str!` to embed small shader file content.
+    pub fn with_source(mut self, shader_type: ShaderType, src: &str) -> Self {
+        self.stages
+            .push((shader_type, ShaderStageSource::Source(src.to_string())));
+        self
+    }
+
+    /// Add a shader stage from file, which is preprocessed on building.
+    pub fn with_file(mut self, shader_type: ShaderType, path: impl AsRef<Path>) -> Self {
+        self.stages.push((
+            shader_type,
+            ShaderStageSource::File(path.as_ref().to_path_buf()),
+        ));
+        self
+    }
+
+    /// Use `preprocessor` for stages from file, instead of default one.
+    pub fn with_preprocessor(mut self, preprocessor: ShaderPreprocessor) -> Self {
+        self.preprocessor = preprocessor;
+        self
+    }
+
+    /// Check combination of stages:
+    ///
+    /// * Each stage appears at most once.
+    /// * Compute shader can't be linked with other stages.
+    /// * Otherwise, vertex shader is required, and tessellation control shader requires evaluation shader.
+    fn validate(&self) -> anyhow::Result<()> {
+        let has = |shader_type| self.stages.iter().any(|(ty, _)| *ty == shader_type);
+
+        for (i, (shader_type, _)) in self.stages.iter().enumerate() {
+            if self.stages[..i].iter().any(|(ty, _)| ty == shader_type) {
+                bail!("Duplicate {shader_type:?} shader stage");
+            }
+        }
+        if self.stages.is_empty() {
+            bail!("No shader stage");
+        }
+        if has(ShaderType::Compute) {
+            if self.stages.len() > 1 {
+                bail!("Compute shader can't be linked with other shader stages");
+            }
+            return Ok(());
+        }
+        if !has(ShaderType::Vertex) {
+            bail!("Vertex shader is required");
+        }
+        if has(ShaderType::TessControl) && !has(ShaderType::TessEvaluation) {
+            bail!("Tessellation control shader requires tessellation evaluation shader");
+        }
+
+        Ok(())
+    }
+
+    /// Compile all stages and link them into a program.
+    pub fn build(self) -> anyhow::Result<ShaderProgram> {
+        self.validate()?;
+
+        let mut shaders = Vec::with_capacity(self.stages.len());
+        for (shader_type, source) in &self.stages {
+            let shader = match source {
+                ShaderStageSource::Source(src) => Shader::from_source(*shader_type, src),
+                ShaderStageSource::File(path) => self
+                    .preprocessor
+                    .process_file(path)
+                    .and_then(|source| Shader::from_preprocessed(*shader_type, &source)),
+            };
+            match shader {
+                Ok(shader) => shaders.push(shader),
+                Err(e) => bail!("{shader_type:?} Shader creation Error: {e}"),
+            }
+        }
+
+        ShaderProgram::create_from_shaders(shaders)
+    }
+}
+
+/// Enum of barrier bits for `memory_barrier()`, each one makes writes of shaders visible to a kind of following
+/// access.
+#[derive(Debug, Clone, Copy)]
+pub enum MemoryBarrierBit {
+    /// Vertex attributes sourced from buffer objects.
+    VertexAttribArray = gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT as isize,
+    /// Vertex array indices sourced from buffer objects.
+    ElementArray = gl::ELEMENT_ARRAY_BARRIER_BIT as isize,
+    /// Uniforms sourced from buffer objects.
+    Uniform = gl::UNIFORM_BARRIER_BIT as isize,
+    /// Texture fetches (sampling) in shaders.
+    TextureFetch = gl::TEXTURE_FETCH_BARRIER_BIT as isize,
+    /// Image load/store/atomic in shaders.
+    ShaderImageAccess = gl::SHADER_IMAGE_ACCESS_BARRIER_BIT as isize,
+    /// Indirect draw/dispatch commands sourced from buffer objects.
+    Command = gl::COMMAND_BARRIER_BIT as isize,
+    /// Reads/writes of buffer objects bound to `GL_PIXEL_PACK_BUFFER` or `GL_PIXEL_UNPACK_BUFFER`.
+    PixelBuffer = gl::PIXEL_BUFFER_BARRIER_BIT as isize,
+    /// Reads/writes of textures by `glTexImage*`, `glTexSubImage*`, `glGetTexImage` etc.
+    TextureUpdate = gl::TEXTURE_UPDATE_BARRIER_BIT as isize,
+    /// Reads/writes of buffer objects by `glBufferSubData`, `glMapBuffer*` etc.
+    BufferUpdate = gl::BUFFER_UPDATE_BARRIER_BIT as isize,
+    /// Reads/writes through framebuffer attachments.
+    Framebuffer = gl::FRAMEBUFFER_BARRIER_BIT as isize,
+    /// Writes of transform feedback to buffer objects.
+    TransformFeedback = gl::TRANSFORM_FEEDBACK_BARRIER_BIT as isize,
+    /// Atomic counter buffer accesses in shaders.
+    AtomicCounter = gl::ATOMIC_COUNTER_BARRIER_BIT as isize,
+    /// Shader storage buffer accesses in shaders.
+    ShaderStorage = gl::SHADER_STORAGE_BARRIER_BIT as isize,
+    /// Client reads of persistently mapped buffers written by shaders, requires OpenGL 4.4.
+    ClientMappedBuffer = gl::CLIENT_MAPPED_BUFFER_BARRIER_BIT as isize,
+    /// Query results written to buffer objects, requires OpenGL 4.4.
+    QueryBuffer = gl::QUERY_BUFFER_BARRIER_BIT as isize,
+}
+
+/// Make memory writes of shaders (e.g. compute shaders) visible to following accesses specified by `barriers`,
+/// which is a combination of `MemoryBarrierBit`, or `gl::ALL_BARRIER_BITS` for all kinds of accesses.
+///
+/// ```ignore
+/// compute_program.dispatch(64, 64, 1);
+/// memory_barrier(MemoryBarrierBit::ShaderImageAccess as GLbitfield | MemoryBarrierBit::TextureFetch as GLbitfield);
+/// ```
+///
+/// wrap `glMemoryBarrier`, requires OpenGL 4.2
+#[inline]
+pub fn memory_barrier(barriers: GLbitfield) {
+    unsafe { gl::MemoryBarrier(barriers) }
+}
+
+/// Set number of vertices of each patch, which is the input of tessellation shaders.
+///
+/// wrap `glPatchParameteri` with `GL_PATCH_VERTICES`, requires OpenGL 4.0
+#[inline]
+pub fn set_patch_vertices(count: i32) {
+    unsafe { gl::PatchParameteri(gl::PATCH_VERTICES, count) }
+}
+
+/// GLSL source whose `#include`s are expanded by `ShaderPreprocessor`.
+#[derive(Debug, Clone)]
+pub struct ShaderSource {
+    /// Expanded source, which can be compiled directly.
+    pub source: String,
+    /// All files which the source comes from. Index of file is used as source string number in `#line` directives.
+    pub files: Vec<PathBuf>,
+}
+
+impl ShaderSource {
+    /// Replace source string numbers in compiling info log by file paths, e.g. `0:12(5): error` of Mesa becomes
+    /// `shaders/foo.frag:12(5): error`.
+    ///
+    /// Formats of Mesa (`0:12(5):`), NVIDIA (`0(12) :`) and AMD/Intel/Apple (`ERROR: 0:12:`) are recognized, other
+    /// lines are kept as is.
+    pub fn map_error_log(&self, log: &str) -> String {
+        log.lines()
+            .map(|line| self.map_error_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn map_error_line(&self, line: &str) -> String {
+        let prefix_len = ["ERROR: ", "WARNING: "]
+            .iter()
+            .find(|prefix| line.starts_with(*prefix))
+            .map_or(0, |prefix| prefix.len());
+        let (prefix, rest) = line.split_at(prefix_len);
+
+        let digits_len = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let (file_index, location) = rest.split_at(digits_len);
+        if !(location.starts_with(':') || location.starts_with('(')) {
+            return line.to_string();
+        }
+
+        match file_index
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| self.files.get(i))
+        {
+            Some(file) => format!("{prefix}{}{location}", file.display()),
+            None => line.to_string(),
+        }
+    }
+}
+
+/// Preprocessor of GLSL shader files, which supports:
+///
+/// * `#include "path"`: paste content of file, path is relative to the including file.
+/// * `#pragma once`: include guard, file with it is included at most once. Classic `#ifndef` guards work as well,
+///   since they are handled by GLSL compiler.
+/// * Injecting `#define`s right after `#version` directive, see `with_define`.
+///
+/// `#line` directives are inserted around included content, so that compiling errors can be mapped back to original
+/// files and lines by `ShaderSource::map_error_log`.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderPreprocessor {
+    defines: Vec<(String, String)>,
+}
+
+/// State of expanding a shader file.
+#[derive(Default)]
+struct IncludeContext {
+    output: String,
+    files: Vec<PathBuf>,
+    canonical_files: Vec<PathBuf>,
+    /// Files with `#pragma once`.
+    once_files: HashSet<PathBuf>,
+    /// Files being expanded, to detect recursive include.
+    stack: Vec<PathBuf>,
+    version_found: bool,
+}
+
+impl ShaderPreprocessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inject `#define <name> <value>` into shader. Use empty value for flag-like macro.
+    pub fn with_define(mut self, name: &str, value: impl ToString) -> Self {
+        self.defines.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Read shader file, and expand its `#include`s recursively.
+    pub fn process_file(&self, path: impl AsRef<Path>) -> anyhow::Result<ShaderSource> {
+        let mut ctx = IncludeContext::default();
+        self.expand_file(path.as_ref(), &mut ctx)?;
+
+        // Inject defines at the very beginning if there is no `#version`
+        if !ctx.version_found && !self.defines.is_empty() {
+            let mut output = String::new();
+            self.write_defines(&mut output);
+            writeln!(output, "#line 1 0")?;
+            ctx.output.insert_str(0, &output);
+        }
+
+        Ok(ShaderSource {
+            source: ctx.output,
+            files: ctx.files,
+        })
+    }
+
+    fn expand_file(&self, path: &Path, ctx: &mut IncludeContext) -> anyhow::Result<()> {
+        let canonical_path = match std::fs::canonicalize(path) {
+            Ok(canonical_path) => canonical_path,
+            Err(e) => bail!("Failed to read shader file {}: {e}", path.display()),
+        };
+        if ctx.once_files.contains(&canonical_path) {
+            return Ok(());
+        }
+        if ctx.stack.contains(&canonical_path) {
+            bail!("Recursive include of shader file {}", path.display());
+        }
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => bail!("Failed to read shader file {}: {e}", path.display()),
+        };
+
+        let file_index = match ctx
+            .canonical_files
+            .iter()
+            .position(|f| f == &canonical_path)
+        {
+            Some(file_index) => file_index,
+            None => {
+                ctx.files.push(path.to_path_buf());
+                ctx.canonical_files.push(canonical_path.clone());
+                ctx.files.len() - 1
+            }
+        };
+        let is_root = ctx.stack.is_empty();
+        ctx.stack.push(canonical_path.clone());
+
+        // tip: since GLSL 3.30, line after `#line <line> <source>` is numbered as `<line>`.
+        if !is_root {
+            writeln!(ctx.output, "#line 1 {file_index}")?;
+        }
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let directive = line.trim_start();
+
+            if let Some(include) = directive.strip_prefix("#include") {
+                let include = include.trim();
+                let include_path = match include
+                    .strip_prefix('"')
+                    .and_then(|include| include.strip_suffix('"'))
+                {
+                    Some(include_path) => include_path,
+                    None => bail!(
+                        "{}:{line_number}: invalid include `{directive}`, expect `#include \"path\"`",
+                        path.display()
+                    ),
+                };
+                let include_path = path.parent().unwrap_or(Path::new("")).join(include_path);
+                if let Err(e) = self.expand_file(&include_path, ctx) {
+                    bail!("{}:{line_number}: {e}", path.display());
+                }
+                writeln!(ctx.output, "#line {} {file_index}", line_number + 1)?;
+            } else if directive.split_whitespace().eq(["#pragma", "once"]) {
+                ctx.once_files.insert(canonical_path.clone());
+                ctx.output.push('\n'); // keep line numbers
+            } else if is_root && !ctx.version_found && directive.starts_with("#version") {
+                ctx.version_found = true;
+                writeln!(ctx.output, "{line}")?;
+                if !self.defines.is_empty() {
+                    self.write_defines(&mut ctx.output);
+                    writeln!(ctx.output, "#line {} {file_index}", line_number + 1)?;
+                }
+            } else {
+                writeln!(ctx.output, "{line}")?;
+            }
+        }
+
+        ctx.stack.pop();
+        Ok(())
+    }
+
+    fn write_defines(&self, output: &mut String) {
+        for (name, value) in &self.defines {
+            output.push_str("#define ");
+            output.push_str(name);
+            output.push(' ');
+            output.push_str(value);
+            output.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    fn fixture(filename: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/shader")
+            .join(filename)
+    }
+
+    /// Map each line of expanded source to `(file index, line number)` by following `#line` directives as GLSL
+    /// compiler does, `None` for directives.
+    fn resolve_lines(source: &str) -> Vec<Option<(usize, usize)>> {
+        let (mut file_index, mut line_number) = (0, 1);
+        source
+            .lines()
+            .map(|line| {
+                if let Some(directive) = line.strip_prefix("#line ") {
+                    let mut args = directive.split_whitespace().map(|arg| arg.parse().unwrap());
+                    line_number = args.next().unwrap();
+                    file_index = args.next().unwrap();
+                    return None;
+                }
+                line_number += 1;
+                Some((file_index, line_number - 1))
+            })
+            .collect()
+    }
+
+    /// Lines of original file, `#include` and `#pragma once` lines are blank in expanded source.
+    fn original_line(file: &Path, line_number: usize) -> String {
+        let source = std::fs::read_to_string(file).unwrap();
+        let line = source.lines().nth(line_number - 1).unwrap();
+        if line.starts_with("#include") || line.starts_with("#pragma once") {
+            String::new()
+        } else {
+            line.to_string()
+        }
+    }
+
+    #[test]
+    fn nested_includes_keep_line_numbers() {
+        let source = ShaderPreprocessor::new()
+            .process_file(fixture("main.frag"))
+            .unwrap();
+
+        assert_eq!(
+            source.files,
+            [
+                fixture("main.frag"),
+                fixture("include/common.glsl"),
+                fixture("include/lighting.glsl"),
+            ]
+        );
+        let lines: Vec<&str> = source.source.lines().collect();
+        for (line, location) in lines.iter().zip(resolve_lines(&source.source)) {
+            if let Some((file_index, line_number)) = location {
+                if line.is_empty() {
+                    continue;
+                }
+                assert_eq!(
+                    *line,
+                    original_line(&source.files[file_index], line_number),
+                    "{}:{line_number}",
+                    source.files[file_index].display()
+                );
+            }
+        }
+        assert!(lines.contains(&"vec3 lighting(vec3 color) {"));
+        assert!(lines.contains(&"    frag_color = vec4(lighting(vec3(1.0)), 1.0);"));
+    }
+
+    #[test]
+    fn pragma_once_includes_file_once() {
+        let source = ShaderPreprocessor::new()
+            .process_file(fixture("main.frag"))
+            .unwrap();
+
+        assert_eq!(source.source.matches("const float PI").count(), 1);
+        assert!(!source.source.contains("#include"));
+        assert!(!source.source.contains("#pragma once"));
+    }
+
+    #[test]
+    fn include_cycle_is_error() {
+        let err = ShaderPreprocessor::new()
+            .process_file(fixture("cycle_a.glsl"))
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("Recursive include"), "{err}");
+        assert!(err.contains("cycle_a.glsl:2:"), "{err}");
+        assert!(err.contains("cycle_b.glsl:1:"), "{err}");
+    }
+
+    #[test]
+    fn missing_and_invalid_include_are_errors() {
+        let err = ShaderPreprocessor::new()
+            .process_file(fixture("missing_include.frag"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("missing_include.frag:2:"), "{err}");
+        assert!(err.contains("missing.glsl"), "{err}");
+
+        let err = ShaderPreprocessor::new()
+            .process_file(fixture("invalid_include.frag"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("invalid_include.frag:2: invalid include"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn defines_are_injected_after_version() {
+        let source = ShaderPreprocessor::new()
+            .with_define("LIGHTS_NUM", 4)
+            .with_define("USE_SHADOW", "")
+            .process_file(fixture("main.frag"))
+            .unwrap();
+
+        let lines: Vec<&str> = source.source.lines().collect();
+        assert_eq!(
+            lines[..4],
+            [
+                "#version 330 core",
+                "#define LIGHTS_NUM 4",
+                "#define USE_SHADOW ",
+                "#line 2 0"
+            ]
+        );
+        assert_eq!(resolve_lines(&source.source)[4], Some((0, 2)));
+    }
+
+    #[test]
+    fn defines_are_injected_without_version() {
+        let source = ShaderPreprocessor::new()
+            .with_define("LIGHTS_NUM", 4)
+            .process_file(fixture("no_version.frag"))
+            .unwrap();
+
+        let lines: Vec<&str> = source.source.lines().collect();
+        assert_eq!(
+            lines[..3],
+            ["#define LIGHTS_NUM 4", "#line 1 0", "out vec4 frag_color;"]
+        );
+        assert_eq!(resolve_lines(&source.source)[2], Some((0, 1)));
+    }
+
+    #[test]
+    fn error_log_is_mapped_to_files() {
+        let source = ShaderPreprocessor::new()
+            .process_file(fixture("main.frag"))
+            .unwrap();
+        let common = fixture("include/common.glsl");
+        let lighting = fixture("include/lighting.glsl");
+
+        // Line of `return color / PI;` in expanded source is reported as line 5 of lighting.glsl
+        let return_line = source
+            .source
+            .lines()
+            .position(|line| line.contains("return color / PI;"))
+            .unwrap();
+        assert_eq!(resolve_lines(&source.source)[return_line], Some((2, 5)));
+
+        let log = [
+            "2:5(12): error: `PI' undeclared",      // Mesa
+            "1(3) : error C0000: syntax error",     // NVIDIA
+            "ERROR: 2:5: 'PI' : undeclared",        // AMD/Intel/Apple
+            "WARNING: 0:8: unused variable",        // AMD/Intel/Apple
+            "9:1(1): error: unknown source string", // out of range
+            "error: linking failed",                // not located
+        ]
+        .join("\n");
+        let expected = [
+            format!("{}:5(12): error: `PI' undeclared", lighting.display()),
+            format!("{}(3) : error C0000: syntax error", common.display()),
+            format!("ERROR: {}:5: 'PI' : undeclared", lighting.display()),
+            format!(
+                "WARNING: {}:8: unused variable",
+                fixture("main.frag").display()
+            ),
+            "9:1(1): error: unknown source string".to_string(),
+            "error: linking failed".to_string(),
+        ]
+        .join("\n");
+        assert_eq!(source.map_error_log(&log), expected);
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+use gl::types::*;
+use notify::{RecursiveMode, Watcher};
+use tracing::{error, info, warn};
+
+use crate::{ShaderPreprocessor, ShaderProgram};
+
+/// Shader program which is recompiled from its vertex & fragment shader files whenever they or files included by
+/// them change.
+///
+/// Files are watched in background, but recompiling must happen on the thread owning the OpenGL context, so call
+/// `reload_if_changed()` once per frame. It derefs to the current `ShaderProgram`.
+///
+/// If recompiling fails, the error (info log of compiling/linking) is logged and the previous working program keeps
+/// being used. Uniform values of previous program are copied into the reloaded one by name, so uniforms which are set
+/// only once (e.g. texture units) survive reloading.
+pub struct ReloadableShaderProgram {
+    program: ShaderProgram,
+    vert_path: String,
+    frag_path: String,
+    preprocessor: ShaderPreprocessor,
+    /// Canonical paths of shader files and their includes.
+    watched_files: HashSet<PathBuf>,
+    watched_dirs: HashSet<PathBuf>,
+    // Note: events stop once watcher is dropped.
+    watcher: notify::RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+}
+
+impl ReloadableShaderProgram {
+    /// Create Program Object from vertex & fragment shader file, and start watching them.
+    pub fn create_from_file(vert_path: &str, frag_path: &str) -> anyhow::Result<Self> {
+        Self::create_from_file_with(vert_path, frag_path, ShaderPreprocessor::new())
+    }
+
+    /// Create Program Object from vertex & fragment shader file which are preprocessed by `preprocessor`, and start
+    /// watching them.
+    pub fn create_from_file_with(
+        vert_path: &str,
+        frag_path: &str,
+        preprocessor: ShaderPreprocessor,
+    ) -> anyhow::Result<Self> {
+        let program = ShaderProgram::create_from_file_with(vert_path, frag_path, &preprocessor)?;
+
+        let (tx, events) = channel();
+        let watcher = notify::recommended_watcher(tx)?;
+
+        let mut reloadable = Self {
+            program,
+            vert_path: vert_path.to_string(),
+            frag_path: frag_path.to_string(),
+            preprocessor,
+            watched_files: HashSet::new(),
+            watched_dirs: HashSet::new(),
+            watcher,
+            events,
+        };
+        reloadable.update_watches()?;
+
+        info!(
+            "Watch shader files: vert={}, frag={}",
+            reloadable.vert_path, reloadable.frag_path
+        );
+
+        Ok(reloadable)
+    }
+
+    /// Get current shader program.
+    pub fn get_program(&self) -> &ShaderProgram {
+        &self.program
+    }
+
+    /// Recompile program if any shader file has changed since last call, return whether program is replaced.
+    ///
+    /// Note: the reloaded program is _not_ bound, call `bind()` before drawing as usual.
+    pub fn reload_if_changed(&mut self) -> bool {
+        if !self.has_changed() {
+            return false;
+        }
+
+        self.reload()
+    }
+
+    /// Recompile program from shader files, return whether program is replaced.
+    pub fn reload(&mut self) -> bool {
+        let program = match ShaderProgram::create_from_file_with(
+            &self.vert_path,
+            &self.frag_path,
+            &self.preprocessor,
+        ) {
+            Ok(program) => program,
+            Err(e) => {
+                error!("Failed to reload shader program, keep the previous one: {e}");
+                return false;
+            }
+        };
+
+        copy_uniforms(&self.program, &program);
+
+        // Keep binding state if previous program is in use.
+        let mut current_program = 0;
+        unsafe { gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut current_program) };
+        if current_program as GLuint == self.program.id {
+            program.bind();
+        }
+
+        let old_program = std::mem::replace(&mut self.program, program);
+        old_program.close();
+
+        // Includes may have changed
+        if let Err(e) = self.update_watches() {
+            warn!("Failed to watch shader files: {e}");
+        }
+
+        info!(
+            "Reload shader program: vert={}, frag={}",
+            self.vert_path, self.frag_path
+        );
+
+        true
+    }
+
+    /// Collect shader files and their includes, and watch their directories.
+    ///
+    /// Parent directories are watched instead of files, since many editors save file by replacing it, which would
+    /// remove the watch of original file.
+    fn update_watches(&mut self) -> anyhow::Result<()> {
+        let mut watched_files = HashSet::new();
+        for path in [&self.vert_path, &self.frag_path] {
+            for file in self.preprocessor.process_file(path)?.files {
+                watched_files.insert(std::fs::canonicalize(file)?);
+            }
+        }
+
+        for file in &watched_files {
+            let dir = parent_dir(file);
+            if !self.watched_dirs.contains(dir) {
+                self.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+                self.watched_dirs.insert(dir.to_path_buf());
+            }
+        }
+        self.watched_files = watched_files;
+
+        Ok(())
+    }
+
+    /// Drain pending events of watcher, and check whether any of them touches shader files.
+    fn has_changed(&self) -> bool {
+        let mut changed = false;
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) => {
+                    if (event.kind.is_create() || event.kind.is_modify())
+                        && event
+                            .paths
+                            .iter()
+                            .any(|path| self.watched_files.contains(path))
+                    {
+                        changed = true;
+                    }
+                }
+                Err(e) => warn!("Shader file watcher error: {e}"),
+            }
+        }
+
+        changed
+    }
+
+    /// Marks the program for deletion, and stop watching.
+    ///
+    /// wrap `glDeleteProgram`.
+    pub fn close(self) {
+        self.program.close();
+    }
+}
+
+impl Deref for ReloadableShaderProgram {
+    type Target = ShaderProgram;
+
+    fn deref(&self) -> &Self::Target {
+        &self.program
+    }
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new("."))
+}
+
+/// Copy values of active uniforms of `dst` from uniforms of the same name and type in `src`, and binding points of
+/// uniform blocks of the same name.
+///
+/// Uniforms which are missing in `src`, or of types other than float/int/bool vectors, float matrices and samplers,
+/// are left as default values.
+///
+/// wrap `glGetUniformfv`, `glGetUniformiv`, `glUniform*`, `glUniformBlockBinding`
+fn copy_uniforms(src: &ShaderProgram, dst: &ShaderProgram) {
+    // `glProgramUniform*` is OpenGL 4.1, so bind `dst` temporarily instead.
+    let mut current_program = 0;
+    unsafe {
+        gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut current_program);
+        gl::UseProgram(dst.id);
+    }
+
+    for (name, dst_info) in dst.get_uniforms() {
+        if let Some(src_info) = src.get_uniform_info(name) {
+            if src_info.ty == dst_info.ty {
+                copy_uniform(src.id, src_info.location, dst_info.location, dst_info.ty);
+            }
+        }
+    }
+
+    unsafe { gl::UseProgram(current_program as GLuint) };
+
+    for name in dst.get_uniform_blocks().keys() {
+        if let Some(binding) = src.get_uniform_block_binding(name) {
+            // The block exists in `dst`, so it can't fail.
+            let _ = dst.bind_uniform_block(name, binding);
+        }
+    }
+}
+
+/// Copy value of a single uniform from `src_program` into currently bound program.
+fn copy_uniform(src_program: GLuint, src_location: GLint, dst_location: GLint, ty: GLenum) {
+    let mut floats = [0f32; 16];
+    let mut ints = [0i32; 4];
+    unsafe {
+        match ty {
+            gl::FLOAT
+            | gl::FLOAT_VEC2
+            | gl::FLOAT_VEC3
+            | gl::FLOAT_VEC4
+            | gl::FLOAT_MAT2
+            | gl::FLOAT_MAT3
+            | gl::FLOAT_MAT4 => gl::GetUniformfv(src_program, src_location, floats.as_mut_ptr()),
+            _ => gl::GetUniformiv(src_program, src_location, ints.as_mut_ptr()),
+        }
+
+        match ty {
+            gl::FLOAT => gl::Uniform1fv(dst_location, 1, floats.as_ptr()),
+            gl::FLOAT_VEC2 => gl::Uniform2fv(dst_location, 1, floats.as_ptr()),
+            gl::FLOAT_VEC3 => gl::Uniform3fv(dst_location, 1, floats.as_ptr()),
+            gl::FLOAT_VEC4 => gl::Uniform4fv(dst_location, 1, floats.as_ptr()),
+            gl::FLOAT_MAT2 => gl::UniformMatrix2fv(dst_location, 1, gl::FALSE, floats.as_ptr()),
+            gl::FLOAT_MAT3 => gl::UniformMatrix3fv(dst_location, 1, gl::FALSE, floats.as_ptr()),
+            gl::FLOAT_MAT4 => gl::UniformMatrix4fv(dst_location, 1, gl::FALSE, floats.as_ptr()),
+            gl::INT
+            | gl::BOOL
+            | gl::SAMPLER_2D
+            | gl::SAMPLER_3D
+            | gl::SAMPLER_CUBE
+            | gl::SAMPLER_2D_SHADOW
+            | gl::SAMPLER_2D_ARRAY => gl::Uniform1iv(dst_location, 1, ints.as_ptr()),
+            gl::INT_VEC2 | gl::BOOL_VEC2 => gl::Uniform2iv(dst_location, 1, ints.as_ptr()),
+            gl::INT_VEC3 | gl::BOOL_VEC3 => gl::Uniform3iv(dst_location, 1, ints.as_ptr()),
+            gl::INT_VEC4 | gl::BOOL_VEC4 => gl::Uniform4iv(dst_location, 1, ints.as_ptr()),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Encoding of Rust values in [std140 layout](https://www.khronos.org/opengl/wiki/Interface_Block_(GLSL)#Memory_layout)
+//! of uniform blocks.
+//!
+//! Rules in short:
+//!
+//! * Scalars are aligned to 4 bytes, `vec2` to 8 bytes, `vec3` & `vec4` to 16 bytes.
+//! * Elements of arrays are aligned to 16 bytes, so `float[N]` takes `16 * N` bytes.
+//! * Matrices are arrays of column vectors, so a `mat3` takes 3 * 16 bytes.
+//! * Structs are aligned to 16 bytes, and padded to a multiple of 16 bytes.
+
+use nalgebra as na;
+
+/// Value which can be encoded in std140 layout.
+///
+/// Implement it for structs by `impl_std140!`, whose fields must be in the same order as in GLSL block:
+///
+/// ```ignore
+/// struct Material {
+///     color: na::Vector3<f32>,
+///     shininess: f32,
+/// }
+/// impl_std140!(Material { color, shininess });
+/// ```
+pub trait Std140 {
+    /// Base alignment in bytes.
+    const ALIGN: usize;
+
+    /// Write value into `writer`, which has been aligned to `ALIGN`.
+    fn write_std140(&self, writer: &mut Std140Writer);
+
+    /// Encode value into bytes, which can be uploaded to uniform buffer directly.
+    fn to_std140_bytes(&self) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut writer = Std140Writer::new();
+        writer.write(self);
+        writer.align(16);
+        writer.into_bytes()
+    }
+}
+
+/// Byte buffer which values are written into with std140 alignment.
+#[derive(Debug, Default)]
+pub struct Std140Writer {
+    bytes: Vec<u8>,
+}
+
+impl Std140Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pad zeros until length is a multiple of `align`.
+    pub fn align(&mut self, align: usize) {
+        let len = self.bytes.len().next_multiple_of(align);
+        self.bytes.resize(len, 0);
+    }
+
+    /// Write value at its base alignment.
+    pub fn write<T: Std140 + ?Sized>(&mut self, value: &T) {
+        self.align(T::ALIGN);
+        value.write_std140(self);
+    }
+
+    /// Write raw bytes without alignment.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// Current length in bytes, which is the offset of next value before alignment.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Implement `Std140` for a struct by writing its fields in order.
+#[macro_export]
+macro_rules! impl_std140 {
+    ($ty:ty { $($field:ident),* $(,)? }) => {
+        impl $crate::Std140 for $ty {
+            // Base alignment of struct is rounded up to `vec4`.
+            const ALIGN: usize = 16;
+
+            fn write_std140(&self, writer: &mut $crate::Std140Writer) {
+                $(writer.write(&self.$field);)*
+                writer.align(16);
+            }
+        }
+    };
+}
+
+macro_rules! impl_std140_scalar {
+    ($($ty:ty),*) => {
+        $(
+            impl Std140 for $ty {
+                const ALIGN: usize = 4;
+
+                fn write_std140(&self, writer: &mut Std140Writer) {
+                    writer.write_bytes(&self.to_ne_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_std140_scalar!(f32, i32, u32);
+
+/// GLSL `bool` is 4 bytes.
+impl Std140 for bool {
+    const ALIGN: usize = 4;
+
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_bytes(&(*self as u32).to_ne_bytes());
+    }
+}
+
+macro_rules! impl_std140_vector {
+    ($($dim:literal => $align:literal),*) => {
+        $(
+            impl<T: Std140 + na::Scalar> Std140 for na::SVector<T, $dim> {
+                const ALIGN: usize = $align;
+
+                fn write_std140(&self, writer: &mut Std140Writer) {
+                    for component in self.iter() {
+                        component.write_std140(writer);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_std140_vector!(2 => 8, 3 => 16, 4 => 16);
+
+impl<T: Std140 + na::Scalar> Std140 for na::Point3<T> {
+    const ALIGN: usize = 16;
+
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        self.coords.write_std140(writer);
+    }
+}
+
+macro_rules! impl_std140_matrix {
+    ($($dim:literal),*) => {
+        $(
+            /// Column-major, each column is aligned like an array element.
+            impl Std140 for na::SMatrix<f32, $dim, $dim> {
+                const ALIGN: usize = 16;
+
+                fn write_std140(&self, writer: &mut Std140Writer) {
+                    for column in self.column_iter() {
+                        writer.align(16);
+                        for component in column.iter() {
+                            component.write_std140(writer);
+                        }
+                    }
+                    writer.align(16);
+                }
+            }
+        )*
+    };
+}
+
+impl_std140_matrix!(2, 3, 4);
+
+/// Elements are aligned to 16 bytes at least, and the stride is padded to a multiple of 16 bytes.
+impl<T: Std140, const N: usize> Std140 for [T; N] {
+    const ALIGN: usize = if T::ALIGN > 16 { T::ALIGN } else { 16 };
+
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        for element in self {
+            writer.align(Self::ALIGN);
+            element.write_std140(writer);
+        }
+        writer.align(Self::ALIGN);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FrameLight, FrameUniforms, MAX_FRAME_LIGHTS};
+
+    fn f32_at(bytes: &[u8], offset: usize) -> f32 {
+        f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn i32_at(bytes: &[u8], offset: usize) -> i32 {
+        i32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    struct Vec3AndFloat {
+        v: na::Vector3<f32>,
+        f: f32,
+    }
+    impl_std140!(Vec3AndFloat { v, f });
+
+    struct FloatAndVec3 {
+        f: f32,
+        v: na::Vector3<f32>,
+    }
+    impl_std140!(FloatAndVec3 { f, v });
+
+    #[test]
+    fn vec3_is_aligned_to_16() {
+        // float right after vec3 fills its 4th component
+        let bytes = Vec3AndFloat {
+            v: na::Vector3::new(1.0, 2.0, 3.0),
+            f: 4.0,
+        }
+        .to_std140_bytes();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(f32_at(&bytes, 12), 4.0);
+
+        // vec3 after float starts at next 16 bytes
+        let bytes = FloatAndVec3 {
+            f: 1.0,
+            v: na::Vector3::new(2.0, 3.0, 4.0),
+        }
+        .to_std140_bytes();
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(f32_at(&bytes, 16), 2.0);
+        assert_eq!(f32_at(&bytes, 24), 4.0);
+    }
+
+    #[test]
+    fn vec2_and_bool_alignment() {
+        let mut writer = Std140Writer::new();
+        writer.write(&true);
+        writer.write(&na::Vector2::new(1.0f32, 2.0));
+        let bytes = writer.into_bytes();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(i32_at(&bytes, 0), 1);
+        assert_eq!(f32_at(&bytes, 8), 1.0);
+    }
+
+    #[test]
+    fn mat3_columns_have_stride_16() {
+        let m = na::Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0);
+        let bytes = m.to_std140_bytes();
+        assert_eq!(bytes.len(), 48);
+        for (column, offset) in [0, 16, 32].into_iter().enumerate() {
+            for row in 0..3 {
+                assert_eq!(f32_at(&bytes, offset + row * 4), m[(row, column)]);
+            }
+            assert_eq!(f32_at(&bytes, offset + 12), 0.0);
+        }
+    }
+
+    #[test]
+    fn array_elements_have_stride_16() {
+        let mut writer = Std140Writer::new();
+        writer.write(&1.0f32);
+        writer.write(&[2.0f32, 3.0, 4.0]);
+        writer.write(&5.0f32);
+        let bytes = writer.into_bytes();
+        // float[3] starts at 16 and takes 3 * 16 bytes, next member follows it
+        assert_eq!(bytes.len(), 68);
+        assert_eq!(f32_at(&bytes, 16), 2.0);
+        assert_eq!(f32_at(&bytes, 32), 3.0);
+        assert_eq!(f32_at(&bytes, 48), 4.0);
+        assert_eq!(f32_at(&bytes, 64), 5.0);
+    }
+
+    fn test_light(i: usize) -> FrameLight {
+        let base = i as f32 * 10.0;
+        FrameLight {
+            position: na::Vector4::new(base + 1.0, base + 2.0, base + 3.0, 1.0),
+            color: na::Vector3::new(base + 4.0, base + 5.0, base + 6.0),
+            attenuation_linear: base + 7.0,
+            attenuation_quadratic: base + 8.0,
+        }
+    }
+
+    /// Offsets of `struct FrameLight` in `frame_uniforms.glsl`: `vec4 position` at 0, `vec3 color` at 16,
+    /// `float attenuation_linear` at 28, `float attenuation_quadratic` at 32, size padded to 48.
+    fn assert_frame_light(bytes: &[u8], offset: usize, light: &FrameLight) {
+        for i in 0..4 {
+            assert_eq!(f32_at(bytes, offset + i * 4), light.position[i]);
+        }
+        for i in 0..3 {
+            assert_eq!(f32_at(bytes, offset + 16 + i * 4), light.color[i]);
+        }
+        assert_eq!(f32_at(bytes, offset + 28), light.attenuation_linear);
+        assert_eq!(f32_at(bytes, offset + 32), light.attenuation_quadratic);
+    }
+
+    #[test]
+    fn frame_light_layout() {
+        let light = test_light(0);
+        let bytes = light.to_std140_bytes();
+        assert_eq!(bytes.len(), 48);
+        assert_frame_light(&bytes, 0, &light);
+    }
+
+    /// Offsets of `FrameUniforms` block in `frame_uniforms.glsl`: `mat4 view` at 0, `mat4 projection` at 64,
+    /// `vec3 camera_pos` at 128, `float time` at 140, `int light_count` at 144, `FrameLight lights[8]` at 160 with
+    /// stride 48.
+    #[test]
+    fn frame_uniforms_layout() {
+        let mut frame = FrameUniforms {
+            view: na::Matrix4::from_fn(|row, column| (row * 4 + column) as f32),
+            projection: na::Matrix4::from_fn(|row, column| (100 + row * 4 + column) as f32),
+            camera_pos: na::Vector3::new(1.0, 2.0, 3.0),
+            time: 4.5,
+            ..Default::default()
+        };
+        for i in 0..MAX_FRAME_LIGHTS {
+            assert!(frame.push_light(test_light(i)));
+        }
+        let bytes = frame.to_std140_bytes();
+
+        assert_eq!(bytes.len(), 160 + 48 * MAX_FRAME_LIGHTS);
+        for column in 0..4 {
+            for row in 0..4 {
+                let offset = column * 16 + row * 4;
+                assert_eq!(f32_at(&bytes, offset), frame.view[(row, column)]);
+                assert_eq!(f32_at(&bytes, 64 + offset), frame.projection[(row, column)]);
+            }
+        }
+        assert_eq!(f32_at(&bytes, 128), 1.0);
+        assert_eq!(f32_at(&bytes, 136), 3.0);
+        assert_eq!(f32_at(&bytes, 140), 4.5);
+        assert_eq!(i32_at(&bytes, 144), MAX_FRAME_LIGHTS as i32);
+        for (i, light) in frame.lights.iter().enumerate() {
+            assert_frame_light(&bytes, 160 + 48 * i, light);
+        }
+    }
+}
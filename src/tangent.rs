@@ -0,0 +1,184 @@
+use bevy_mikktspace::Geometry;
+use nalgebra as na;
+
+use crate::Vertex;
+
+/// Generate `tangent` & `bitangent` of triangle list `vertices` from their positions, normals and texture coords,
+/// by MikkTSpace algorithm, which is the standard of normal maps baked by most tools and required by glTF.
+///
+/// MikkTSpace generates a tangent per corner of triangles. Vertices shared by corners with different tangents, e.g. at
+/// seams of mirrored texture coords, are split: the copies are appended to `vertices` and `indices` are updated.
+///
+/// Return `false` if tangent space can't be generated, e.g. indices are not triangles or out of range.
+pub fn generate_tangents(vertices: &mut Vec<Vertex>, indices: &mut [u32]) -> bool {
+    if indices.is_empty() || !indices.len().is_multiple_of(3) {
+        return false;
+    }
+    if indices
+        .iter()
+        .any(|&index| index as usize >= vertices.len())
+    {
+        return false;
+    }
+
+    let mut mesh = MikkTSpaceMesh {
+        vertices,
+        indices,
+        tangents: vec![[0.0; 4]; indices.len()],
+    };
+    if !bevy_mikktspace::generate_tangents(&mut mesh) {
+        return false;
+    }
+    let tangents = mesh.tangents;
+
+    // Copies of each original vertex with distinct tangents, the first one is the vertex itself
+    let mut copies: Vec<Vec<u32>> = vec![Vec::new(); vertices.len()];
+    for (index, tangent) in indices.iter_mut().zip(tangents) {
+        let tangent_sign = tangent[3];
+        let tangent = na::Vector3::new(tangent[0], tangent[1], tangent[2]);
+        let bitangent = vertices[*index as usize].normal.cross(&tangent) * tangent_sign;
+
+        let vertex_copies = &mut copies[*index as usize];
+        let same_tangent = vertex_copies.iter().find(|&&copy| {
+            let vertex = &vertices[copy as usize];
+            (vertex.tangent - tangent).norm() < 1e-4 && (vertex.bitangent - bitangent).norm() < 1e-4
+        });
+        match same_tangent {
+            Some(&copy) => *index = copy,
+            None => {
+                if !vertex_copies.is_empty() {
+                    vertices.push(vertices[*index as usize]);
+                    *index = (vertices.len() - 1) as u32;
+                }
+                vertex_copies.push(*index);
+                let vertex = &mut vertices[*index as usize];
+                vertex.tangent = tangent;
+                vertex.bitangent = bitangent;
+            }
+        }
+    }
+    true
+}
+
+/// Triangle list viewed as MikkTSpace geometry.
+struct MikkTSpaceMesh<'a> {
+    vertices: &'a [Vertex],
+    indices: &'a [u32],
+    /// Tangent of each corner, i.e. each index.
+    tangents: Vec<[f32; 4]>,
+}
+
+impl MikkTSpaceMesh<'_> {
+    fn vertex(&self, face: usize, vert: usize) -> &Vertex {
+        &self.vertices[self.indices[face * 3 + vert] as usize]
+    }
+}
+
+impl Geometry for MikkTSpaceMesh<'_> {
+    fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.vertex(face, vert).position.into()
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.vertex(face, vert).normal.into()
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.vertex(face, vert).texture_coords.into()
+    }
+
+    /// `w` of `tangent` is sign of bitangent, which is -1 for mirrored texture coords.
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.tangents[face * 3 + vert] = tangent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two quads in xy plane sharing the edge at x = 1, where texture coords are mirrored.
+    fn mirrored_quads() -> (Vec<Vertex>, Vec<u32>) {
+        let vertex = |x: f32, y: f32, u: f32, v: f32| {
+            Vertex::new(
+                na::Vector3::new(x, y, 0.0),
+                na::Vector3::z(),
+                na::Vector2::new(u, v),
+            )
+        };
+        let vertices = vec![
+            vertex(0.0, 0.0, 0.0, 0.0),
+            vertex(1.0, 0.0, 1.0, 0.0),
+            vertex(1.0, 1.0, 1.0, 1.0),
+            vertex(0.0, 1.0, 0.0, 1.0),
+            vertex(2.0, 0.0, 0.0, 0.0),
+            vertex(2.0, 1.0, 0.0, 1.0),
+        ];
+        let indices = vec![0, 1, 2, 0, 2, 3, 1, 4, 5, 1, 5, 2];
+        (vertices, indices)
+    }
+
+    fn assert_near(actual: na::Vector3<f32>, expected: na::Vector3<f32>) {
+        assert!(
+            (actual - expected).norm() < 1e-5,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn vertices_are_split_at_mirrored_seam() {
+        let (mut vertices, mut indices) = mirrored_quads();
+        assert!(generate_tangents(&mut vertices, &mut indices));
+
+        // Vertices on the shared edge are split
+        assert_eq!(vertices.len(), 8);
+        assert_eq!(indices.len(), 12);
+        for (triangle_index, triangle) in indices.chunks_exact(3).enumerate() {
+            let expected_tangent = if triangle_index < 2 {
+                na::Vector3::x()
+            } else {
+                -na::Vector3::x()
+            };
+            for &index in triangle {
+                let vertex = &vertices[index as usize];
+                assert_near(vertex.tangent, expected_tangent);
+                assert_near(vertex.bitangent, na::Vector3::y());
+            }
+        }
+        // Copies keep position, normal & texture coords
+        for (index, original) in [(6, 1), (7, 2)] {
+            assert_eq!(vertices[index].position, vertices[original].position);
+            assert_eq!(
+                vertices[index].texture_coords,
+                vertices[original].texture_coords
+            );
+        }
+    }
+
+    #[test]
+    fn vertices_are_shared_without_seam() {
+        let (mut vertices, mut indices) = mirrored_quads();
+        vertices.truncate(4);
+        indices.truncate(6);
+        assert!(generate_tangents(&mut vertices, &mut indices));
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices, [0, 1, 2, 0, 2, 3]);
+    }
+
+    #[test]
+    fn invalid_indices() {
+        let (mut vertices, _) = mirrored_quads();
+        assert!(!generate_tangents(&mut vertices, &mut []));
+        assert!(!generate_tangents(&mut vertices, &mut [0, 1]));
+        assert!(!generate_tangents(&mut vertices, &mut [0, 1, 6]));
+    }
+}
@@ -1,9 +1,19 @@
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
 
+use anyhow::Context;
 use gl::types::*;
 use image::GenericImageView;
 
-use crate::get_gl_error;
+use crate::{get_gl_error, ColorFormat, TextureOptions};
+
+mod bcn;
+mod compressed;
+mod cubemap;
+
+pub use compressed::*;
+pub use cubemap::*;
 
 /// Wrapper of [Texture Object](https://www.khronos.org/opengl/wiki/Texture)
 #[derive(Debug, Clone)]
@@ -11,22 +21,49 @@ pub struct Texture {
     pub id: GLuint,
     pub tex_type: TextureType,
     pub path: PathBuf,
+    /// Target Texture is bound to, `GL_TEXTURE_CUBE_MAP` for `TextureType::Cubemap` and `GL_TEXTURE_2D` for others,
+    /// or array targets of compressed textures.
+    pub target: GLenum,
 }
 
 #[repr(u8)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TextureType {
     Normal,
-    BlinnDiffuse,  // Blinn-Phong
-    BlinnSpecular, // Blinn-Phong
-    PbrAlbedo,     // PBR
-    PbrMetallic,   // PBR
-    PbrRoughness,  // PBR
-    PbrAO,         // PBR
+    BlinnDiffuse,   // Blinn-Phong
+    BlinnSpecular,  // Blinn-Phong
+    BlinnAmbient,   // Blinn-Phong
+    BlinnShininess, // Blinn-Phong
+    Alpha,          // opacity
+    PbrAlbedo,      // PBR
+    PbrMetallic,    // PBR
+    PbrRoughness,   // PBR
+    PbrAO,          // PBR
     Cubemap,
     Unknown,
 }
 
+/// Enum of color spaces of texture image data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Data is used as is, e.g. normals, metallic & roughness.
+    Linear,
+    /// Data is gamma encoded colors, decoded to linear when sampled by `SRGB8`/`SRGB8_ALPHA8` internal formats.
+    Srgb,
+}
+
+impl TextureType {
+    /// Color space of images of this type: diffuse, ambient & albedo maps are sRGB, and others are linear.
+    pub fn color_space(&self) -> ColorSpace {
+        match self {
+            TextureType::BlinnDiffuse | TextureType::BlinnAmbient | TextureType::PbrAlbedo => {
+                ColorSpace::Srgb
+            }
+            _ => ColorSpace::Linear,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum TextureUnit {
@@ -49,9 +86,10 @@ pub enum TextureUnit {
 }
 
 impl TextureUnit {
-    pub fn increase(&self) -> TextureUnit {
+    /// Next texture unit, `None` after `TEXTURE15`.
+    pub fn increase(&self) -> Option<TextureUnit> {
         let val: GLint = (*self).into();
-        (val + 1).into()
+        (val < 15).then(|| (val + 1).into())
     }
 }
 
@@ -133,18 +171,48 @@ impl Texture {
         }
 
         if texture != 0 {
+            let target = if texture_type == TextureType::Cubemap {
+                gl::TEXTURE_CUBE_MAP
+            } else {
+                gl::TEXTURE_2D
+            };
             Ok(Self {
                 id: texture,
                 tex_type: texture_type,
                 path,
+                target,
             })
         } else {
             Err(get_gl_error().unwrap().into())
         }
     }
 
-    /// Create Texture
+    /// Create Texture from image file, sampled with default `TextureOptions`.
+    ///
+    /// Use `create_with_options()` for other sampling, e.g. `CLAMP_TO_EDGE` for sprites, whose transparent borders
+    /// would bleed from the opposite edge with `REPEAT`.
     pub fn create(path: PathBuf, texture_type: Option<TextureType>) -> anyhow::Result<Self> {
+        Self::create_with_options(path, texture_type, &TextureOptions::default())
+    }
+
+    /// Create Texture from image file, sampled with `options`.
+    pub fn create_with_options(
+        path: PathBuf,
+        texture_type: Option<TextureType>,
+        options: &TextureOptions,
+    ) -> anyhow::Result<Self> {
+        let img = image::open(&path)?;
+
+        Self::create_from_image(&img, path, texture_type, options)
+    }
+
+    /// Create Texture from a decoded image, e.g. image embedded in model file. `path` only identifies the texture.
+    pub fn create_from_image(
+        img: &image::DynamicImage,
+        path: PathBuf,
+        texture_type: Option<TextureType>,
+        options: &TextureOptions,
+    ) -> anyhow::Result<Self> {
         // Generate Texture
         let tex_type: TextureType = match texture_type {
             Some(t) => t,
@@ -157,36 +225,21 @@ impl Texture {
             gl::BindTexture(gl::TEXTURE_2D, texture.id); // Bind Texture
         }
 
-        // Load Texture image
-        let img = image::open(&texture.path).unwrap().flipv();
+        // OpenGL images start from bottom-left corner
+        let img = img.flipv();
         let (width, height) = img.dimensions();
-        let img_format: GLenum;
-        let img_type: GLenum;
-        match img.color() {
-            image::ColorType::Rgb8 => {
-                img_format = gl::RGB;
-                img_type = gl::UNSIGNED_BYTE;
-            }
-            image::ColorType::Rgba8 => {
-                img_format = gl::RGBA;
-                img_type = gl::UNSIGNED_BYTE;
-            }
-            _ => match img.color().channel_count() {
-                1 => {
-                    img_format = gl::RED;
-                    img_type = gl::UNSIGNED_BYTE;
-                }
-                3 => {
-                    img_format = gl::RGB;
-                    img_type = gl::UNSIGNED_BYTE;
-                }
-                4 => {
-                    img_format = gl::RGBA;
-                    img_type = gl::UNSIGNED_BYTE;
-                }
-                _ => anyhow::bail!("Unsupported image color type: {:?}", img.color()),
-            },
-        }
+        let srgb = options
+            .color_space
+            .unwrap_or(texture.tex_type.color_space())
+            == ColorSpace::Srgb;
+        let (internal_format, img_format) = match img.color().channel_count() {
+            1 => (gl::R8, gl::RED),
+            3 if srgb => (gl::SRGB8, gl::RGB),
+            3 => (gl::RGB8, gl::RGB),
+            4 if srgb => (gl::SRGB8_ALPHA8, gl::RGBA),
+            4 => (gl::RGBA8, gl::RGBA),
+            _ => anyhow::bail!("Unsupported image color type: {:?}", img.color()),
+        };
 
         tracing::debug!(
             "Texture image loaded. path: {:?}, color type: {:?}, size: {:?}",
@@ -195,34 +248,93 @@ impl Texture {
             img.dimensions()
         );
 
-        // Set Texture wrapping & filtering
+        // Send Texture image data, 16 bits images are converted to 8 bits
+        let pixels = match img_format {
+            gl::RED => img.into_luma8().into_raw(),
+            gl::RGB => img.into_rgb8().into_raw(),
+            _ => img.into_rgba8().into_raw(),
+        };
+        let _unpack_alignment = TightUnpackAlignment::new();
         unsafe {
-            if img_format == gl::RGBA {
-                gl::TexParameteri(
-                    gl::TEXTURE_2D,
-                    gl::TEXTURE_WRAP_S,
-                    gl::CLAMP_TO_EDGE as GLint,
-                );
-                gl::TexParameteri(
-                    gl::TEXTURE_2D,
-                    gl::TEXTURE_WRAP_T,
-                    gl::CLAMP_TO_EDGE as GLint,
-                );
-            } else {
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as GLint);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as GLint);
-            }
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal_format as GLint,
+                width.try_into()?,
+                height.try_into()?,
+                0,
+                img_format,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr().cast(),
+            );
         }
 
-        // Send Texture image data
-        let pixels = img.into_bytes();
+        // Generate mipmap
+        unsafe { gl::GenerateMipmap(gl::TEXTURE_2D) }
+
+        // Set Texture wrapping & filtering
+        texture.set_options(options);
+
+        Ok(texture)
+    }
+
+    /// Create float Texture from HDR image file, Radiance HDR (`.hdr`) or OpenEXR (`.exr`), with internal `format`,
+    /// e.g. `ColorFormat::RGB16F`. Image data is kept linear and unclamped.
+    pub fn create_hdr(
+        path: PathBuf,
+        texture_type: Option<TextureType>,
+        format: ColorFormat,
+        options: &TextureOptions,
+    ) -> anyhow::Result<Self> {
+        let img =
+            open_hdr_image(&path).with_context(|| format!("Failed to load HDR image: {path:?}"))?;
+
+        Self::create_from_float_image(&img, path, texture_type, format, options)
+    }
+
+    /// Create Texture from a decoded image with float internal `format`, e.g. `ColorFormat::RGBA32F`. Channels of image
+    /// are converted to 32 bits float, and dropped if `format` has fewer of them.
+    pub fn create_from_float_image(
+        img: &image::DynamicImage,
+        path: PathBuf,
+        texture_type: Option<TextureType>,
+        format: ColorFormat,
+        options: &TextureOptions,
+    ) -> anyhow::Result<Self> {
+        let (internal_format, img_format, img_type) = format.gl_formats();
+        if img_type != gl::FLOAT {
+            anyhow::bail!("Format of float texture must be float, but it's {format:?}");
+        }
+        let channel_count = match img_format {
+            gl::RED => 1,
+            gl::RG => 2,
+            gl::RGB => 3,
+            _ => 4,
+        };
+
+        let texture = Self::new(path, texture_type.unwrap_or(TextureType::Unknown))?;
+
+        // OpenGL images start from bottom-left corner
+        let img = img.flipv().into_rgba32f();
+        let (width, height) = img.dimensions();
+        let pixels: Vec<f32> = img
+            .pixels()
+            .flat_map(|pixel| pixel.0.into_iter().take(channel_count))
+            .collect();
+
+        tracing::debug!(
+            "Float texture image loaded. path: {:?}, format: {:?}, size: {:?}",
+            texture.path,
+            format,
+            (width, height)
+        );
+
         unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, texture.id);
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                img_format as GLint,
+                internal_format as GLint,
                 width.try_into()?,
                 height.try_into()?,
                 0,
@@ -230,10 +342,9 @@ impl Texture {
                 img_type,
                 pixels.as_ptr().cast(),
             );
+            gl::GenerateMipmap(gl::TEXTURE_2D);
         }
-
-        // Generate mipmap
-        unsafe { gl::GenerateMipmap(gl::TEXTURE_2D) }
+        texture.set_options(options);
 
         Ok(texture)
     }
@@ -244,11 +355,20 @@ impl Texture {
         Self::active(unit);
 
         // Bind Texture
-        if self.tex_type == TextureType::Cubemap {
-            unsafe { gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.id) }
-        } else {
-            unsafe { gl::BindTexture(gl::TEXTURE_2D, self.id) }
-        }
+        unsafe { gl::BindTexture(self.target, self.id) }
+    }
+
+    /// Bind this Texture and set its sampling parameters.
+    ///
+    /// wrap `glTexParameter*`
+    pub fn set_options(&self, options: &TextureOptions) {
+        let target = self.target;
+        unsafe { gl::BindTexture(target, self.id) }
+        options.apply(
+            |name, value| unsafe { gl::TexParameteri(target, name, value) },
+            |name, value| unsafe { gl::TexParameterf(target, name, value) },
+            |name, value| unsafe { gl::TexParameterfv(target, name, value) },
+        );
     }
 
     pub fn active(unit: TextureUnit) {
@@ -257,3 +377,50 @@ impl Texture {
         }
     }
 }
+
+/// Unpack pixel rows tightly by `GL_UNPACK_ALIGNMENT` of 1 while it's alive, e.g. rows of `RED`/`RGB` images whose
+/// width isn't a multiple of 4. Previous alignment is restored when it's dropped, even on early return.
+struct TightUnpackAlignment {
+    previous: GLint,
+}
+
+impl TightUnpackAlignment {
+    fn new() -> Self {
+        let mut previous = 4;
+        unsafe {
+            gl::GetIntegerv(gl::UNPACK_ALIGNMENT, &mut previous);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        }
+        Self { previous }
+    }
+}
+
+impl Drop for TightUnpackAlignment {
+    fn drop(&mut self) {
+        unsafe { gl::PixelStorei(gl::UNPACK_ALIGNMENT, self.previous) }
+    }
+}
+
+/// Open image file with float channels. `image::open()` tone maps Radiance HDR images to 8 bits, so they're decoded by
+/// `HdrDecoder` directly.
+fn open_hdr_image(path: &Path) -> anyhow::Result<image::DynamicImage> {
+    let is_radiance_hdr = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("hdr"));
+    if !is_radiance_hdr {
+        return Ok(image::open(path)?);
+    }
+
+    let decoder = image::codecs::hdr::HdrDecoder::new(BufReader::new(File::open(path)?))?;
+    let metadata = decoder.metadata();
+    let pixels: Vec<f32> = decoder
+        .read_image_hdr()?
+        .into_iter()
+        .flat_map(|pixel| pixel.0)
+        .collect();
+    let Some(img) = image::Rgb32FImage::from_raw(metadata.width, metadata.height, pixels) else {
+        anyhow::bail!("Size of HDR image doesn't match its pixels");
+    };
+
+    Ok(image::DynamicImage::ImageRgb32F(img))
+}
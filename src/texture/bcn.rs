@@ -0,0 +1,1134 @@
+//! CPU decoders of BC1-BC7 blocks, used when the OpenGL context doesn't support a compressed format.
+//!
+//! Each block holds 4x4 texels in rows, see
+//! [BC formats](https://learn.microsoft.com/en-us/windows/win32/direct3d11/texture-block-compression-in-direct3d-11).
+
+/// Texels of a BC1 (DXT1) block, `alpha` enables the transparent black of 3-color blocks.
+pub(super) fn decode_bc1(block: &[u8], alpha: bool) -> [[u8; 4]; 16] {
+    decode_color_block(block, true, alpha)
+}
+
+/// Texels of a BC2 (DXT3) block, 4 bits explicit alpha followed by a BC1 color block.
+pub(super) fn decode_bc2(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut texels = decode_color_block(&block[8..], false, false);
+    let alphas = u64::from_le_bytes(block[..8].try_into().unwrap());
+    for (i, texel) in texels.iter_mut().enumerate() {
+        texel[3] = ((alphas >> (4 * i)) & 0xF) as u8 * 17;
+    }
+    texels
+}
+
+/// Texels of a BC3 (DXT5) block, BC4 alpha followed by a BC1 color block.
+pub(super) fn decode_bc3(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut texels = decode_color_block(&block[8..], false, false);
+    for (texel, alpha) in texels.iter_mut().zip(decode_bc4(&block[..8], false)) {
+        texel[3] = alpha;
+    }
+    texels
+}
+
+/// Single channel texels of a BC4 (RGTC1) block, `signed` ones are `i8` as bytes.
+pub(super) fn decode_bc4(block: &[u8], signed: bool) -> [u8; 16] {
+    let (e0, e1) = if signed {
+        // -128 is the same as -127
+        (
+            (block[0] as i8).max(-127) as i32,
+            (block[1] as i8).max(-127) as i32,
+        )
+    } else {
+        (block[0] as i32, block[1] as i32)
+    };
+    let (min, max) = if signed { (-127, 127) } else { (0, 255) };
+
+    let mut palette = [e0, e1, 0, 0, 0, 0, min, max];
+    if e0 > e1 {
+        for i in 1..7 {
+            palette[i + 1] = ((7 - i as i32) * e0 + i as i32 * e1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = ((5 - i as i32) * e0 + i as i32 * e1) / 5;
+        }
+    }
+
+    let mut indices = [0; 8];
+    indices[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(indices);
+    std::array::from_fn(|i| palette[((indices >> (3 * i)) & 0x7) as usize] as u8)
+}
+
+/// Two channels texels of a BC5 (RGTC2) block, two BC4 blocks of red and green.
+pub(super) fn decode_bc5(block: &[u8], signed: bool) -> [[u8; 2]; 16] {
+    let red = decode_bc4(&block[..8], signed);
+    let green = decode_bc4(&block[8..], signed);
+    std::array::from_fn(|i| [red[i], green[i]])
+}
+
+/// Color block of BC1-BC3. BC1 blocks with `color0 <= color1` have 3 colors and black, which is transparent if
+/// `alpha`, and BC2/BC3 blocks always have 4 colors.
+fn decode_color_block(block: &[u8], bc1: bool, alpha: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (rgb0, rgb1) = (rgb565(c0), rgb565(c1));
+    let mix = |w0: u32, w1: u32| -> [u8; 4] {
+        let channel = |i: usize| ((w0 * rgb0[i] as u32 + w1 * rgb1[i] as u32) / (w0 + w1)) as u8;
+        [channel(0), channel(1), channel(2), 255]
+    };
+
+    let palette = if bc1 && c0 <= c1 {
+        let black = [0, 0, 0, if alpha { 0 } else { 255 }];
+        [mix(1, 0), mix(0, 1), mix(1, 1), black]
+    } else {
+        [mix(1, 0), mix(0, 1), mix(2, 1), mix(1, 2)]
+    };
+
+    let indices = u32::from_le_bytes(block[4..8].try_into().unwrap());
+    std::array::from_fn(|i| palette[((indices >> (2 * i)) & 0x3) as usize])
+}
+
+fn rgb565(color: u16) -> [u8; 3] {
+    let r = ((color >> 11) & 0x1F) as u8;
+    let g = ((color >> 5) & 0x3F) as u8;
+    let b = (color & 0x1F) as u8;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
+
+/// Little endian bit reader of a 128 bits block.
+struct BitReader {
+    bits: u128,
+}
+
+impl BitReader {
+    fn new(block: &[u8]) -> Self {
+        Self {
+            bits: u128::from_le_bytes(block[..16].try_into().unwrap()),
+        }
+    }
+
+    fn read(&mut self, count: u32) -> u32 {
+        let value = (self.bits & ((1 << count) - 1)) as u32;
+        self.bits >>= count;
+        value
+    }
+}
+
+const WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+const WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+fn weights(index_bits: u32) -> &'static [u32] {
+    match index_bits {
+        2 => &WEIGHTS_2,
+        3 => &WEIGHTS_3,
+        _ => &WEIGHTS_4,
+    }
+}
+
+/// Subsets of texels in BC6H & BC7 partitions of 2 subsets, a bit per texel.
+const PARTITIONS_2: [u16; 64] = [
+    0xCCCC, 0x8888, 0xEEEE, 0xECC8, 0xC880, 0xFEEC, 0xFEC8, 0xEC80, 0xC800, 0xFFEC, 0xFE80, 0xE800,
+    0xFFE8, 0xFF00, 0xFFF0, 0xF000, 0xF710, 0x008E, 0x7100, 0x08CE, 0x008C, 0x7310, 0x3100, 0x8CCE,
+    0x088C, 0x3110, 0x6666, 0x366C, 0x17E8, 0x0FF0, 0x718E, 0x399C, 0xAAAA, 0xF0F0, 0x5A5A, 0x33CC,
+    0x3C3C, 0x55AA, 0x9696, 0xA55A, 0x73CE, 0x13C8, 0x324C, 0x3BDC, 0x6996, 0xC33C, 0x9966, 0x0660,
+    0x0272, 0x04E4, 0x4E40, 0x2720, 0xC936, 0x936C, 0x39C6, 0x639C, 0x9336, 0x9CC6, 0x817E, 0xE718,
+    0xCCF0, 0x0FCC, 0x7744, 0xEE22,
+];
+
+/// Subsets of texels in BC7 partitions of 3 subsets.
+const PARTITIONS_3: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 1, 2, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 2, 0, 0, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2],
+    [0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0, 2, 2, 2, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2],
+    [0, 1, 1, 1, 0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0],
+    [0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2, 0, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 0, 1, 2, 2, 2, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 1, 0, 2, 2, 1, 0],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1, 0, 0, 0, 0],
+    [0, 0, 1, 2, 0, 0, 1, 2, 1, 1, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1, 0, 1, 1, 0],
+    [0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1],
+    [0, 0, 2, 2, 1, 1, 0, 2, 1, 1, 0, 2, 0, 0, 2, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 0, 0, 2, 2, 2, 2, 2],
+    [0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 0, 0, 2, 0, 0, 0, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 0, 2, 2, 2],
+    [0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0],
+    [0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0],
+    [0, 1, 2, 0, 2, 0, 1, 2, 1, 2, 0, 1, 0, 1, 2, 0],
+    [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, 1, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 0, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1],
+    [0, 2, 2, 0, 1, 2, 2, 1, 0, 2, 2, 0, 1, 2, 2, 1],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 0, 1, 0, 1],
+    [0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 2, 2, 2, 0, 1, 1, 1],
+    [0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 2, 1, 1, 1, 2],
+    [0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2],
+    [0, 0, 0, 2, 1, 1, 1, 2, 1, 1, 1, 2, 0, 0, 0, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2],
+    [0, 0, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2],
+    [0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1],
+    [0, 2, 2, 2, 1, 2, 2, 2, 0, 2, 2, 2, 1, 2, 2, 2],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 1, 2, 0, 1, 1, 2, 2, 0, 1, 2, 2, 2, 0],
+];
+
+/// Anchor texels of the second subset in partitions of 2 subsets, whose indices have one bit less.
+const ANCHORS_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, //
+    15, 2, 8, 2, 2, 8, 8, 15, 2, 8, 2, 2, 8, 8, 2, 2, //
+    15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, //
+    6, 2, 6, 8, 15, 15, 2, 2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+/// Anchor texels of the second subset in partitions of 3 subsets.
+const ANCHORS_3_SECOND: [u8; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3, //
+    3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5, 15, 15, //
+    8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15, //
+    3, 15, 5, 5, 5, 8, 5, 10, 5, 10, 8, 13, 15, 12, 3, 3,
+];
+
+/// Anchor texels of the third subset in partitions of 3 subsets.
+const ANCHORS_3_THIRD: [u8; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8, //
+    15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6, 10, 15, 15, 10, 8, //
+    15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8, //
+    15, 3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+];
+
+/// Subset of `texel` in `partition` of `subset_count` subsets, and whether it's the anchor of the subset.
+fn partition_subset(subset_count: u32, partition: usize, texel: usize) -> (usize, bool) {
+    match subset_count {
+        1 => (0, texel == 0),
+        2 => {
+            let subset = ((PARTITIONS_2[partition] >> texel) & 1) as usize;
+            let anchor = [0, ANCHORS_2[partition] as usize][subset];
+            (subset, texel == anchor)
+        }
+        _ => {
+            let subset = PARTITIONS_3[partition][texel] as usize;
+            let anchor = [
+                0,
+                ANCHORS_3_SECOND[partition] as usize,
+                ANCHORS_3_THIRD[partition] as usize,
+            ][subset];
+            (subset, texel == anchor)
+        }
+    }
+}
+
+/// Layout of a BC7 mode.
+struct Bc7Mode {
+    subset_count: u32,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    /// P-bit of each endpoint
+    endpoint_pbits: bool,
+    /// P-bit shared by endpoints of each subset
+    shared_pbits: bool,
+    index_bits: u32,
+    secondary_index_bits: u32,
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode::new(3, 4, 0, 0, 4, 0, true, false, 3, 0),
+    Bc7Mode::new(2, 6, 0, 0, 6, 0, false, true, 3, 0),
+    Bc7Mode::new(3, 6, 0, 0, 5, 0, false, false, 2, 0),
+    Bc7Mode::new(2, 6, 0, 0, 7, 0, true, false, 2, 0),
+    Bc7Mode::new(1, 0, 2, 1, 5, 6, false, false, 2, 3),
+    Bc7Mode::new(1, 0, 2, 0, 7, 8, false, false, 2, 2),
+    Bc7Mode::new(1, 0, 0, 0, 7, 7, true, false, 4, 0),
+    Bc7Mode::new(2, 6, 0, 0, 5, 5, true, false, 2, 0),
+];
+
+impl Bc7Mode {
+    #[allow(clippy::too_many_arguments)]
+    const fn new(
+        subset_count: u32,
+        partition_bits: u32,
+        rotation_bits: u32,
+        index_selection_bits: u32,
+        color_bits: u32,
+        alpha_bits: u32,
+        endpoint_pbits: bool,
+        shared_pbits: bool,
+        index_bits: u32,
+        secondary_index_bits: u32,
+    ) -> Self {
+        Self {
+            subset_count,
+            partition_bits,
+            rotation_bits,
+            index_selection_bits,
+            color_bits,
+            alpha_bits,
+            endpoint_pbits,
+            shared_pbits,
+            index_bits,
+            secondary_index_bits,
+        }
+    }
+}
+
+/// Texels of a BC7 (BPTC) block, blocks of reserved mode are transparent black.
+pub(super) fn decode_bc7(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut reader = BitReader::new(block);
+    let Some(mode_index) = (0..8).find(|_| reader.read(1) == 1) else {
+        return [[0; 4]; 16];
+    };
+    let mode = &BC7_MODES[mode_index];
+
+    let partition = reader.read(mode.partition_bits) as usize;
+    let rotation = reader.read(mode.rotation_bits);
+    let index_selection = reader.read(mode.index_selection_bits);
+
+    // Endpoints, channel by channel
+    let endpoint_count = mode.subset_count as usize * 2;
+    let mut endpoints = [[0u32; 4]; 6];
+    for channel in 0..4 {
+        let bits = if channel < 3 {
+            mode.color_bits
+        } else {
+            mode.alpha_bits
+        };
+        for endpoint in endpoints.iter_mut().take(endpoint_count) {
+            endpoint[channel] = reader.read(bits);
+        }
+    }
+
+    // P-bits are the least significant bits of endpoints
+    let mut pbits = [0u32; 6];
+    if mode.endpoint_pbits {
+        for pbit in pbits.iter_mut().take(endpoint_count) {
+            *pbit = reader.read(1);
+        }
+    } else if mode.shared_pbits {
+        for subset in 0..mode.subset_count as usize {
+            let pbit = reader.read(1);
+            pbits[subset * 2] = pbit;
+            pbits[subset * 2 + 1] = pbit;
+        }
+    }
+    let has_pbits = mode.endpoint_pbits || mode.shared_pbits;
+    for (endpoint, pbit) in endpoints.iter_mut().zip(pbits).take(endpoint_count) {
+        for (channel, value) in endpoint.iter_mut().enumerate() {
+            let mut bits = if channel < 3 {
+                mode.color_bits
+            } else {
+                mode.alpha_bits
+            };
+            if bits == 0 {
+                // No alpha
+                *value = 255;
+                continue;
+            }
+            if has_pbits {
+                *value = (*value << 1) | pbit;
+                bits += 1;
+            }
+            *value = (*value << (8 - bits)) | (*value >> (2 * bits - 8));
+        }
+    }
+
+    // Indices, anchors of subsets have one bit less
+    let mut indices = [0u32; 16];
+    for (texel, index) in indices.iter_mut().enumerate() {
+        let (_, anchor) = partition_subset(mode.subset_count, partition, texel);
+        *index = reader.read(mode.index_bits - anchor as u32);
+    }
+    let mut secondary_indices = [0u32; 16];
+    if mode.secondary_index_bits > 0 {
+        for (texel, index) in secondary_indices.iter_mut().enumerate() {
+            *index = reader.read(mode.secondary_index_bits - (texel == 0) as u32);
+        }
+    }
+
+    std::array::from_fn(|texel| {
+        let (subset, _) = partition_subset(mode.subset_count, partition, texel);
+        let (e0, e1) = (endpoints[subset * 2], endpoints[subset * 2 + 1]);
+
+        let (mut color_weight, mut alpha_weight) = (
+            weights(mode.index_bits)[indices[texel] as usize],
+            weights(mode.index_bits)[indices[texel] as usize],
+        );
+        if mode.secondary_index_bits > 0 {
+            let secondary_weight =
+                weights(mode.secondary_index_bits)[secondary_indices[texel] as usize];
+            if index_selection == 0 {
+                alpha_weight = secondary_weight;
+            } else {
+                color_weight = secondary_weight;
+            }
+        }
+
+        let mut texel: [u8; 4] = std::array::from_fn(|channel| {
+            let weight = if channel < 3 {
+                color_weight
+            } else {
+                alpha_weight
+            };
+            (((64 - weight) * e0[channel] + weight * e1[channel] + 32) >> 6) as u8
+        });
+        if rotation > 0 {
+            texel.swap(rotation as usize - 1, 3);
+        }
+        texel
+    })
+}
+
+// Fields of BC6H headers
+const RW: usize = 0;
+const RX: usize = 1;
+const RY: usize = 2;
+const RZ: usize = 3;
+const GW: usize = 4;
+const GX: usize = 5;
+const GY: usize = 6;
+const GZ: usize = 7;
+const BW: usize = 8;
+const BX: usize = 9;
+const BY: usize = 10;
+const BZ: usize = 11;
+const D: usize = 12;
+
+/// Layout of a BC6H mode.
+struct Bc6hMode {
+    /// Value of mode bits, 2 bits for the first two modes and 5 bits for others.
+    value: u32,
+    /// Endpoints are deltas from the first one.
+    transformed: bool,
+    endpoint_bits: u32,
+    /// Bits of deltas of red, green & blue.
+    delta_bits: [u32; 3],
+    /// Segments of fields `(field, to, from)`, bits are read from bit `from` to bit `to` of the field, so `from > to`
+    /// means reversed bits.
+    segments: &'static [(usize, u32, u32)],
+}
+
+#[rustfmt::skip]
+const BC6H_MODES: [Bc6hMode; 14] = [
+    Bc6hMode {
+        value: 0b00, transformed: true, endpoint_bits: 10, delta_bits: [5, 5, 5],
+        segments: &[
+            (GY, 4, 4), (BY, 4, 4), (BZ, 4, 4), (RW, 9, 0), (GW, 9, 0), (BW, 9, 0), (RX, 4, 0),
+            (GZ, 4, 4), (GY, 3, 0), (GX, 4, 0), (BZ, 0, 0), (GZ, 3, 0), (BX, 4, 0), (BZ, 1, 1),
+            (BY, 3, 0), (RY, 4, 0), (BZ, 2, 2), (RZ, 4, 0), (BZ, 3, 3), (D, 4, 0),
+        ],
+    },
+    Bc6hMode {
+        value: 0b01, transformed: true, endpoint_bits: 7, delta_bits: [6, 6, 6],
+        segments: &[
+            (GY, 5, 5), (GZ, 4, 4), (GZ, 5, 5), (RW, 6, 0), (BZ, 0, 0), (BZ, 1, 1), (BY, 4, 4),
+            (GW, 6, 0), (BY, 5, 5), (BZ, 2, 2), (GY, 4, 4), (BW, 6, 0), (BZ, 3, 3), (BZ, 5, 5),
+            (BZ, 4, 4), (RX, 5, 0), (GY, 3, 0), (GX, 5, 0), (GZ, 3, 0), (BX, 5, 0), (BY, 3, 0),
+            (RY, 5, 0), (RZ, 5, 0), (D, 4, 0),
+        ],
+    },
+    Bc6hMode {
+        value: 0b00010, transformed: true, endpoint_bits: 11, delta_bits: [5, 4, 4],
+        segments: &[
+            (RW, 9, 0), (GW, 9, 0), (BW, 9, 0), (RX, 4, 0), (RW, 10, 10), (GY, 3, 0), (GX, 3, 0),
+            (GW, 10, 10), (BZ, 0, 0), (GZ, 3, 0), (BX, 3, 0), (BW, 10, 10), (BZ, 1, 1), (BY, 3, 0),
+            (RY, 4, 0), (BZ, 2, 2), (RZ, 4, 0), (BZ, 3, 3), (D, 4, 0),
+        ],
+    },
+    Bc6hMode {
+        value: 0b00110, transformed: true, endpoint_bits: 11, delta_bits: [4, 5, 4],
+        segments: &[
+            (RW, 9, 0), (GW, 9, 0), (BW, 9, 0), (RX, 3, 0), (RW, 10, 10), (GZ, 4, 4), (GY, 3, 0),
+            (GX, 4, 0), (GW, 10, 10), (GZ, 3, 0), (BX, 3, 0), (BW, 10, 10), (BZ, 1, 1), (BY, 3, 0),
+            (RY, 3, 0), (BZ, 0, 0), (BZ, 2, 2), (RZ, 3, 0), (GY, 4, 4), (BZ, 3, 3), (D, 4, 0),
+        ],
+    },
+    Bc6hMode {
+        value: 0b01010, transformed: true, endpoint_bits: 11, delta_bits: [4, 4, 5],
+        segments: &[
+            (RW, 9, 0), (GW, 9, 0), (BW, 9, 0), (RX, 3, 0), (RW, 10, 10), (BY, 4, 4), (GY, 3, 0),
+            (GX, 3, 0), (GW, 10, 10), (BZ, 0, 0), (GZ, 3, 0), (BX, 4, 0), (BW, 10, 10), (BY, 3, 0),
+            (RY, 3, 0), (BZ, 1, 1), (BZ, 2, 2), (RZ, 3, 0), (BZ, 4, 4), (BZ, 3, 3), (D, 4, 0),
+        ],
+    },
+    Bc6hMode {
+        value: 0b01110, transformed: true, endpoint_bits: 9, delta_bits: [5, 5, 5],
+        segments: &[
+            (RW, 8, 0), (BY, 4, 4), (GW, 8, 0), (GY, 4, 4), (BW, 8, 0), (BZ, 4, 4), (RX, 4, 0),
+            (GZ, 4, 4), (GY, 3, 0), (GX, 4, 0), (BZ, 0, 0), (GZ, 3, 0), (BX, 4, 0), (BZ, 1, 1),
+            (BY, 3, 0), (RY, 4, 0), (BZ, 2, 2), (RZ, 4, 0), (BZ, 3, 3), (D, 4, 0),
+        ],
+    },
+    Bc6hMode {
+        value: 0b10010, transformed: true, endpoint_bits: 8, delta_bits: [6, 5, 5],
+        segments: &[
+            (RW, 7, 0), (GZ, 4, 4), (BY, 4, 4), (GW, 7, 0), (BZ, 2, 2), (GY, 4, 4), (BW, 7, 0),
+            (BZ, 3, 3), (BZ, 4, 4), (RX, 5, 0), (GY, 3, 0), (GX, 4, 0), (BZ, 0, 0), (GZ, 3, 0),
+            (BX, 4, 0), (BZ, 1, 1), (BY, 3, 0), (RY, 5, 0), (RZ, 5, 0), (D, 4, 0),
+        ],
+    },
+    Bc6hMode {
+        value: 0b10110, transformed: true, endpoint_bits: 8, delta_bits: [5, 6, 5],
+        segments: &[
+            (RW, 7, 0), (BZ, 0, 0), (BY, 4, 4), (GW, 7, 0), (GY, 5, 5), (GY, 4, 4), (BW, 7, 0),
+            (GZ, 5, 5), (BZ, 4, 4), (RX, 4, 0), (GZ, 4, 4), (GY, 3, 0), (GX, 5, 0), (GZ, 3, 0),
+            (BX, 4, 0), (BZ, 1, 1), (BY, 3, 0), (RY, 4, 0), (BZ, 2, 2), (RZ, 4, 0), (BZ, 3, 3),
+            (D, 4, 0),
+        ],
+    },
+    Bc6hMode {
+        value: 0b11010, transformed: true, endpoint_bits: 8, delta_bits: [5, 5, 6],
+        segments: &[
+            (RW, 7, 0), (BZ, 1, 1), (BY, 4, 4), (GW, 7, 0), (BY, 5, 5), (GY, 4, 4), (BW, 7, 0),
+            (BZ, 5, 5), (BZ, 4, 4), (RX, 4, 0), (GZ, 4, 4), (GY, 3, 0), (GX, 4, 0), (BZ, 0, 0),
+            (GZ, 3, 0), (BX, 5, 0), (BY, 3, 0), (RY, 4, 0), (BZ, 2, 2), (RZ, 4, 0), (BZ, 3, 3),
+            (D, 4, 0),
+        ],
+    },
+    Bc6hMode {
+        value: 0b11110, transformed: false, endpoint_bits: 6, delta_bits: [6, 6, 6],
+        segments: &[
+            (RW, 5, 0), (GZ, 4, 4), (BZ, 0, 0), (BZ, 1, 1), (BY, 4, 4), (GW, 5, 0), (GY, 5, 5),
+            (BY, 5, 5), (BZ, 2, 2), (GY, 4, 4), (BW, 5, 0), (GZ, 5, 5), (BZ, 3, 3), (BZ, 5, 5),
+            (BZ, 4, 4), (RX, 5, 0), (GY, 3, 0), (GX, 5, 0), (GZ, 3, 0), (BX, 5, 0), (BY, 3, 0),
+            (RY, 5, 0), (RZ, 5, 0), (D, 4, 0),
+        ],
+    },
+    Bc6hMode {
+        value: 0b00011, transformed: false, endpoint_bits: 10, delta_bits: [10, 10, 10],
+        segments: &[(RW, 9, 0), (GW, 9, 0), (BW, 9, 0), (RX, 9, 0), (GX, 9, 0), (BX, 9, 0)],
+    },
+    Bc6hMode {
+        value: 0b00111, transformed: true, endpoint_bits: 11, delta_bits: [9, 9, 9],
+        segments: &[
+            (RW, 9, 0), (GW, 9, 0), (BW, 9, 0), (RX, 8, 0), (RW, 10, 10), (GX, 8, 0), (GW, 10, 10),
+            (BX, 8, 0), (BW, 10, 10),
+        ],
+    },
+    Bc6hMode {
+        value: 0b01011, transformed: true, endpoint_bits: 12, delta_bits: [8, 8, 8],
+        segments: &[
+            (RW, 9, 0), (GW, 9, 0), (BW, 9, 0), (RX, 7, 0), (RW, 10, 11), (GX, 7, 0), (GW, 10, 11),
+            (BX, 7, 0), (BW, 10, 11),
+        ],
+    },
+    Bc6hMode {
+        value: 0b01111, transformed: true, endpoint_bits: 16, delta_bits: [4, 4, 4],
+        segments: &[
+            (RW, 9, 0), (GW, 9, 0), (BW, 9, 0), (RX, 3, 0), (RW, 10, 15), (GX, 3, 0), (GW, 10, 15),
+            (BX, 3, 0), (BW, 10, 15),
+        ],
+    },
+];
+
+/// Texels of a BC6H (BPTC float) block, blocks of reserved modes are black.
+pub(super) fn decode_bc6h(block: &[u8], signed: bool) -> [[f32; 3]; 16] {
+    let mut reader = BitReader::new(block);
+    let mut mode_value = reader.read(2);
+    if mode_value > 1 {
+        mode_value |= reader.read(3) << 2;
+    }
+    let Some(mode) = BC6H_MODES.iter().find(|mode| mode.value == mode_value) else {
+        return [[0.0; 3]; 16];
+    };
+
+    let mut fields = [0i32; 13];
+    for &(field, to, from) in mode.segments {
+        if from <= to {
+            for bit in from..=to {
+                fields[field] |= (reader.read(1) << bit) as i32;
+            }
+        } else {
+            for bit in (to..=from).rev() {
+                fields[field] |= (reader.read(1) << bit) as i32;
+            }
+        }
+    }
+
+    // Modes of `0bxxx11` have one subset
+    let subset_count = if mode.value & 0b11 == 0b11 { 1 } else { 2 };
+    let partition = fields[D] as usize;
+
+    // Endpoints of subsets as [subset][endpoint][channel]
+    let channel_fields = [[RW, RX, RY, RZ], [GW, GX, GY, GZ], [BW, BX, BY, BZ]];
+    let mut endpoints = [[[0i32; 3]; 2]; 2];
+    for (channel, [w, x, y, z]) in channel_fields.into_iter().enumerate() {
+        let endpoint_bits = mode.endpoint_bits;
+        let mut values = [fields[w], fields[x], fields[y], fields[z]];
+        if mode.transformed {
+            let mask = (1 << endpoint_bits) - 1;
+            let base = values[0];
+            for value in values.iter_mut().skip(1) {
+                *value = (base + sign_extend(*value, mode.delta_bits[channel])) & mask;
+            }
+        }
+        if signed {
+            let bits = |i: usize| {
+                if i == 0 || mode.transformed {
+                    endpoint_bits
+                } else {
+                    mode.delta_bits[channel]
+                }
+            };
+            for (i, value) in values.iter_mut().enumerate() {
+                *value = sign_extend(*value, bits(i));
+            }
+        }
+        for (i, value) in values.into_iter().enumerate() {
+            endpoints[i / 2][i % 2][channel] = unquantize_bc6h(value, endpoint_bits, signed);
+        }
+    }
+
+    // Indices, anchors of subsets have one bit less
+    let index_bits = if subset_count == 1 { 4 } else { 3 };
+    let mut indices = [0u32; 16];
+    for (texel, index) in indices.iter_mut().enumerate() {
+        let (_, anchor) = partition_subset(subset_count, partition, texel);
+        *index = reader.read(index_bits - anchor as u32);
+    }
+
+    std::array::from_fn(|texel| {
+        let (subset, _) = partition_subset(subset_count, partition, texel);
+        let [e0, e1] = endpoints[subset];
+        let weight = weights(index_bits)[indices[texel] as usize] as i32;
+        std::array::from_fn(|channel| {
+            let value = ((64 - weight) * e0[channel] + weight * e1[channel] + 32) >> 6;
+            half_to_f32(finish_unquantize_bc6h(value, signed))
+        })
+    })
+}
+
+fn sign_extend(value: i32, bits: u32) -> i32 {
+    let shift = 32 - bits;
+    (value << shift) >> shift
+}
+
+/// Scale endpoint of `bits` bits to 16 bits.
+fn unquantize_bc6h(value: i32, bits: u32, signed: bool) -> i32 {
+    if !signed {
+        if bits >= 15 || value == 0 {
+            value
+        } else if value == (1 << bits) - 1 {
+            0xFFFF
+        } else {
+            ((value << 16) + 0x8000) >> bits
+        }
+    } else if bits >= 16 || value == 0 {
+        value
+    } else {
+        let magnitude = value.abs();
+        let unquantized = if magnitude >= (1 << (bits - 1)) - 1 {
+            0x7FFF
+        } else {
+            ((magnitude << 15) + 0x4000) >> (bits - 1)
+        };
+        unquantized * value.signum()
+    }
+}
+
+/// Scale interpolated 16 bits value to bits of half float.
+fn finish_unquantize_bc6h(value: i32, signed: bool) -> u16 {
+    if !signed {
+        ((value * 31) >> 6) as u16
+    } else if value < 0 {
+        0x8000 | (((-value) * 31) >> 5) as u16
+    } else {
+        ((value * 31) >> 5) as u16
+    }
+}
+
+fn half_to_f32(bits: u16) -> f32 {
+    half::f16::from_bits(bits).to_f32()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little endian bit writer of 128 bits blocks, the counterpart of `BitReader`.
+    struct BitWriter {
+        bits: u128,
+        len: u32,
+    }
+
+    impl BitWriter {
+        fn new() -> Self {
+            Self { bits: 0, len: 0 }
+        }
+
+        fn write(&mut self, value: u32, count: u32) -> &mut Self {
+            assert!(u64::from(value) < 1 << count);
+            self.bits |= u128::from(value) << self.len;
+            self.len += count;
+            self
+        }
+
+        fn write_all(&mut self, values: &[u32], count: u32) -> &mut Self {
+            for &value in values {
+                self.write(value, count);
+            }
+            self
+        }
+
+        fn finish(&self) -> [u8; 16] {
+            assert_eq!(self.len, 128);
+            self.bits.to_le_bytes()
+        }
+    }
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    /// 3 bits indices of BC4 blocks, texel `i` uses `i % 8`.
+    const BC4_RAMP_INDICES: [u8; 6] = [0x88, 0xC6, 0xFA, 0x88, 0xC6, 0xFA];
+
+    fn half(bits: u16) -> f32 {
+        half::f16::from_bits(bits).to_f32()
+    }
+
+    #[test]
+    fn bc1_four_colors() {
+        // color0 0xF800 > color1 0x001F, texel `i` uses index `i % 4`
+        let block = [0x00, 0xF8, 0x1F, 0x00, 0xE4, 0xE4, 0xE4, 0xE4];
+        let palette = [RED, BLUE, [170, 0, 85, 255], [85, 0, 170, 255]];
+        for alpha in [false, true] {
+            let texels = decode_bc1(&block, alpha);
+            for (i, texel) in texels.iter().enumerate() {
+                assert_eq!(*texel, palette[i % 4], "texel {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn bc1_three_colors() {
+        // color0 0x001F <= color1 0xF800, so index 3 is black
+        let block = [0x1F, 0x00, 0x00, 0xF8, 0xE4, 0xE4, 0xE4, 0xE4];
+        let texels = decode_bc1(&block, false);
+        let palette = [BLUE, RED, [127, 0, 127, 255], [0, 0, 0, 255]];
+        for (i, texel) in texels.iter().enumerate() {
+            assert_eq!(*texel, palette[i % 4], "texel {i}");
+        }
+
+        // Black is transparent with 1 bit alpha
+        let texels = decode_bc1(&block, true);
+        assert_eq!(texels[3], [0, 0, 0, 0]);
+        assert_eq!(texels[2], [127, 0, 127, 255]);
+    }
+
+    #[test]
+    fn bc2_explicit_alpha() {
+        // Alpha of texel `i` is `i`, color block always has 4 colors even if color0 <= color1
+        let block = [
+            0x10, 0x32, 0x54, 0x76, 0x98, 0xBA, 0xDC, 0xFE, //
+            0x1F, 0x00, 0x00, 0xF8, 0xFF, 0xFF, 0xFF, 0xFF,
+        ];
+        let texels = decode_bc2(&block);
+        for (i, texel) in texels.iter().enumerate() {
+            assert_eq!(*texel, [170, 0, 85, i as u8 * 17], "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc3_interpolated_alpha() {
+        // Alpha of 8 values, white color block
+        let mut block = [255, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0];
+        block[2..8].copy_from_slice(&BC4_RAMP_INDICES);
+        let alphas = [255, 0, 218, 182, 145, 109, 72, 36];
+        let texels = decode_bc3(&block);
+        for (i, texel) in texels.iter().enumerate() {
+            assert_eq!(*texel, [255, 255, 255, alphas[i % 8]], "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc4_six_values() {
+        // red0 <= red1 has 6 interpolated values, 0 and 255
+        let mut block = [0, 255, 0, 0, 0, 0, 0, 0];
+        block[2..].copy_from_slice(&BC4_RAMP_INDICES);
+        let values = [0, 255, 51, 102, 153, 204, 0, 255];
+        let texels = decode_bc4(&block, false);
+        for (i, texel) in texels.iter().enumerate() {
+            assert_eq!(*texel, values[i % 8], "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc4_signed() {
+        // -128 is clamped to -127, -127 <= 127 has 6 interpolated values, -127 and 127
+        let mut block = [0x80, 0x7F, 0, 0, 0, 0, 0, 0];
+        block[2..].copy_from_slice(&BC4_RAMP_INDICES);
+        let values = [-127, 127, -76, -25, 25, 76, -127, 127];
+        let texels = decode_bc4(&block, true);
+        for (i, texel) in texels.iter().enumerate() {
+            assert_eq!(*texel as i8, values[i % 8], "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc5_two_channels() {
+        // Green of all texels is index 1
+        let mut block = [
+            0, 255, 0, 0, 0, 0, 0, 0, 200, 100, 0x49, 0x92, 0x24, 0x49, 0x92, 0x24,
+        ];
+        block[2..8].copy_from_slice(&BC4_RAMP_INDICES);
+        let reds = [0, 255, 51, 102, 153, 204, 0, 255];
+        let texels = decode_bc5(&block, false);
+        for (i, texel) in texels.iter().enumerate() {
+            assert_eq!(*texel, [reds[i % 8], 100], "texel {i}");
+        }
+
+        let mut block = [
+            0x80, 0x7F, 0, 0, 0, 0, 0, 0, 0x9C, 50, 0x49, 0x92, 0x24, 0x49, 0x92, 0x24,
+        ];
+        block[2..8].copy_from_slice(&BC4_RAMP_INDICES);
+        let reds = [-127, 127, -76, -25, 25, 76, -127, 127];
+        let texels = decode_bc5(&block, true);
+        for (i, texel) in texels.iter().enumerate() {
+            assert_eq!(
+                [texel[0] as i8, texel[1] as i8],
+                [reds[i % 8], 50],
+                "texel {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn bc7_mode_0() {
+        // Partition 8 of 3 subsets are rows 0-1, row 2 and row 3
+        let block = BitWriter::new()
+            .write(0b1, 1)
+            .write(8, 4)
+            .write_all(&[15, 0, 0, 0, 0, 0], 4)
+            .write_all(&[0, 0, 15, 0, 0, 0], 4)
+            .write_all(&[0, 0, 0, 0, 15, 0], 4)
+            .write_all(&[1, 0, 0, 0, 1, 0], 1)
+            .write(0, 45)
+            .finish();
+        let texels = decode_bc7(&block);
+        for (i, texel) in texels.iter().enumerate() {
+            let expected = match i / 4 {
+                0 | 1 => [255, 8, 8, 255],
+                2 => [0, 247, 0, 255],
+                _ => [8, 8, 255, 255],
+            };
+            assert_eq!(*texel, expected, "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc7_mode_1() {
+        // Partition 13 of 2 subsets are rows 0-1 and rows 2-3, p-bits are shared by subsets
+        let block = BitWriter::new()
+            .write(0b10, 2)
+            .write(13, 6)
+            .write_all(&[32, 32, 0, 0], 6)
+            .write_all(&[0, 0, 63, 63], 6)
+            .write_all(&[0, 0, 16, 16], 6)
+            .write_all(&[0, 1], 1)
+            .write(0, 46)
+            .finish();
+        let texels = decode_bc7(&block);
+        for (i, texel) in texels.iter().enumerate() {
+            let expected = if i < 8 {
+                [129, 0, 0, 255]
+            } else {
+                [2, 255, 66, 255]
+            };
+            assert_eq!(*texel, expected, "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc7_mode_2() {
+        let block = BitWriter::new()
+            .write(0b100, 3)
+            .write(8, 6)
+            .write_all(&[31, 0, 0, 0, 0, 0], 5)
+            .write_all(&[0, 0, 31, 0, 0, 0], 5)
+            .write_all(&[0, 0, 0, 0, 16, 0], 5)
+            .write(0, 29)
+            .finish();
+        let texels = decode_bc7(&block);
+        for (i, texel) in texels.iter().enumerate() {
+            let expected = match i / 4 {
+                0 | 1 => [255, 0, 0, 255],
+                2 => [0, 255, 0, 255],
+                _ => [0, 0, 132, 255],
+            };
+            assert_eq!(*texel, expected, "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc7_mode_3() {
+        // Texel 15 is the anchor of the second subset, whose index has 1 bit
+        let mut writer = BitWriter::new();
+        writer
+            .write(0b1000, 4)
+            .write(13, 6)
+            .write_all(&[100, 0, 0, 0], 7)
+            .write_all(&[0, 0, 0, 0], 7)
+            .write_all(&[0, 0, 50, 127], 7)
+            .write_all(&[1, 0, 0, 1], 1)
+            .write(0, 1)
+            .write_all(&[0; 7], 2)
+            .write_all(&[3; 7], 2)
+            .write(1, 1);
+        let texels = decode_bc7(&writer.finish());
+        for (i, texel) in texels.iter().enumerate() {
+            let expected = match i {
+                0..=7 => [201, 1, 1, 255],
+                8..=14 => [1, 1, 255, 255],
+                _ => [0, 0, 151, 255],
+            };
+            assert_eq!(*texel, expected, "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc7_mode_4_rotation() {
+        // Rotation 1 swaps red & alpha, alpha uses 3 bits indices
+        let mut writer = BitWriter::new();
+        writer
+            .write(0b10000, 5)
+            .write(1, 2)
+            .write(0, 1)
+            .write_all(&[16, 16], 5)
+            .write_all(&[0, 0], 5)
+            .write_all(&[31, 31], 5)
+            .write_all(&[0, 63], 6)
+            .write(0, 31)
+            .write(0, 2)
+            .write_all(&[7; 15], 3);
+        let texels = decode_bc7(&writer.finish());
+        assert_eq!(texels[0], [0, 0, 255, 132]);
+        for (i, texel) in texels.iter().enumerate().skip(1) {
+            assert_eq!(*texel, [255, 0, 255, 132], "texel {i}");
+        }
+
+        // Index selection 1 swaps indices, color uses 3 bits indices and alpha uses 2 bits indices
+        let mut writer = BitWriter::new();
+        writer
+            .write(0b10000, 5)
+            .write(0, 2)
+            .write(1, 1)
+            .write_all(&[0, 31], 5)
+            .write_all(&[0, 0], 5)
+            .write_all(&[0, 0], 5)
+            .write_all(&[0, 63], 6)
+            .write(0, 1)
+            .write_all(&[3; 15], 2)
+            .write(0, 2)
+            .write_all(&[4; 15], 3);
+        let texels = decode_bc7(&writer.finish());
+        assert_eq!(texels[0], [0, 0, 0, 0]);
+        for (i, texel) in texels.iter().enumerate().skip(1) {
+            assert_eq!(*texel, [147, 0, 0, 255], "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc7_mode_5_rotation() {
+        // Rotation 3 swaps blue & alpha
+        let mut writer = BitWriter::new();
+        writer
+            .write(0b100000, 6)
+            .write(3, 2)
+            .write_all(&[127, 127], 7)
+            .write_all(&[0, 127], 7)
+            .write_all(&[64, 64], 7)
+            .write_all(&[200, 200], 8)
+            .write(0, 1);
+        for i in 1..16 {
+            writer.write(i % 4, 2);
+        }
+        writer.write(0, 31);
+        let texels = decode_bc7(&writer.finish());
+        let greens = [0, 84, 171, 255];
+        for (i, texel) in texels.iter().enumerate() {
+            assert_eq!(*texel, [255, greens[i % 4], 200, 129], "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc7_mode_6() {
+        // Gradient from white to transparent black over all 16 weights
+        let mut writer = BitWriter::new();
+        writer
+            .write(0b1000000, 7)
+            .write_all(&[127, 0, 127, 0, 127, 0, 127, 0], 7)
+            .write_all(&[1, 0], 1)
+            .write(0, 3);
+        for i in 1..16 {
+            writer.write(i, 4);
+        }
+        let texels = decode_bc7(&writer.finish());
+        let values = [
+            255, 239, 219, 203, 187, 171, 151, 135, 120, 104, 84, 68, 52, 36, 16, 0,
+        ];
+        for (i, texel) in texels.iter().enumerate() {
+            assert_eq!(*texel, [values[i]; 4], "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc7_mode_7() {
+        let block = BitWriter::new()
+            .write(0b10000000, 8)
+            .write(13, 6)
+            .write_all(&[16, 16, 0, 0], 5)
+            .write_all(&[0, 0, 31, 31], 5)
+            .write_all(&[0, 0, 0, 0], 5)
+            .write_all(&[8, 8, 31, 31], 5)
+            .write_all(&[0, 0, 1, 1], 1)
+            .write(0, 30)
+            .finish();
+        let texels = decode_bc7(&block);
+        for (i, texel) in texels.iter().enumerate() {
+            let expected = if i < 8 {
+                [130, 0, 0, 65]
+            } else {
+                [4, 255, 4, 255]
+            };
+            assert_eq!(*texel, expected, "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc7_reserved_mode() {
+        assert_eq!(decode_bc7(&[0; 16]), [[0; 4]; 16]);
+        let mut block = [0xFF; 16];
+        block[0] = 0;
+        assert_eq!(decode_bc7(&block), [[0; 4]; 16]);
+    }
+
+    #[test]
+    fn bc6h_mode_0_deltas() {
+        // Red endpoints are 495, 495 + 1 of the first subset and 495 - 1, 495 of the second subset
+        let mut writer = BitWriter::new();
+        writer
+            .write(0b00, 2)
+            .write(0, 3)
+            .write_all(&[495, 0, 0], 10)
+            .write(1, 5)
+            .write(0, 1 + 4 + 5 + 1 + 4 + 5 + 1 + 4)
+            .write(0b11111, 5)
+            .write(0, 1 + 5 + 1)
+            .write(13, 5)
+            .write(0, 2)
+            .write_all(&[7; 7], 3)
+            .write_all(&[0; 7], 3)
+            .write(3, 2);
+        let texels = decode_bc6h(&writer.finish(), false);
+        for (i, texel) in texels.iter().enumerate() {
+            let red = match i {
+                0 => 1.0,
+                1..=7 => half(0x3C1F),
+                8..=14 => half(0x3BE1),
+                _ => half(0x3BEE),
+            };
+            assert_eq!(*texel, [red, 0.0, 0.0], "texel {i}");
+        }
+    }
+
+    #[test]
+    fn bc6h_mode_11() {
+        // One subset of 10 bits endpoints, texel `i` uses index `i`
+        let mut writer = BitWriter::new();
+        writer
+            .write(0b00011, 5)
+            .write_all(&[495, 0, 1023], 10)
+            .write_all(&[0, 495, 1023], 10)
+            .write(0, 3);
+        for i in 1..16 {
+            writer.write(i, 4);
+        }
+        let texels = decode_bc6h(&writer.finish(), false);
+        assert_eq!(texels[0], [1.0, 0.0, 65504.0]);
+        assert_eq!(texels[8], [half(0x1C20), half(0x1FE0), 65504.0]);
+        assert_eq!(texels[15], [0.0, 1.0, 65504.0]);
+    }
+
+    #[test]
+    fn bc6h_mode_11_signed() {
+        // Red endpoints are -495 & 495, green endpoints are 0 & -512, blue endpoints are 511 & 0
+        let mut writer = BitWriter::new();
+        writer
+            .write(0b00011, 5)
+            .write_all(&[1024 - 495, 0, 511], 10)
+            .write_all(&[495, 512, 0], 10)
+            .write(0, 3)
+            .write_all(&[0; 14], 4)
+            .write(15, 4);
+        let texels = decode_bc6h(&writer.finish(), true);
+        assert_eq!(texels[0], [-32800.0, 0.0, 65504.0]);
+        assert_eq!(texels[15], [32800.0, -65504.0, 0.0]);
+    }
+
+    #[test]
+    fn bc6h_mode_14_reversed_bits() {
+        // Bits 10-15 of base endpoints are reversed, blue delta is -1
+        let mut writer = BitWriter::new();
+        writer
+            .write(0b01111, 5)
+            .write_all(&[0x3E0, 0, 0], 10)
+            .write(0, 4)
+            .write(0b011110, 6)
+            .write(0, 4)
+            .write(0b000010, 6)
+            .write(0b1111, 4)
+            .write(0, 6)
+            .write(0, 3)
+            .write_all(&[0; 14], 4)
+            .write(15, 4);
+        let texels = decode_bc6h(&writer.finish(), false);
+        for (i, texel) in texels.iter().enumerate().take(15) {
+            assert_eq!(*texel, [1.0, half(0x1F00), 0.0], "texel {i}");
+        }
+        assert_eq!(texels[15], [1.0, half(0x1F00), 65504.0]);
+    }
+
+    #[test]
+    fn bc6h_reserved_modes() {
+        for mode in [0b10011, 0b10111, 0b11011, 0b11111] {
+            let mut block = [0xFF; 16];
+            block[0] = 0xE0 | mode;
+            assert_eq!(decode_bc6h(&block, false), [[0.0; 3]; 16], "mode {mode:#b}");
+            assert_eq!(decode_bc6h(&block, true), [[0.0; 3]; 16], "mode {mode:#b}");
+        }
+    }
+}
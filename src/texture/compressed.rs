@@ -0,0 +1,600 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
+use gl::types::*;
+
+use super::{bcn, TightUnpackAlignment};
+use crate::{get_gl_version, has_gl_extension, ColorSpace, Texture, TextureOptions, TextureType};
+
+// S3TC internal formats of `GL_EXT_texture_compression_s3tc` & `GL_EXT_texture_sRGB`, which are not core
+const COMPRESSED_RGB_S3TC_DXT1: GLenum = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT1: GLenum = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3: GLenum = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5: GLenum = 0x83F3;
+const COMPRESSED_SRGB_S3TC_DXT1: GLenum = 0x8C4C;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1: GLenum = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3: GLenum = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5: GLenum = 0x8C4F;
+
+/// Identifier at the beginning of KTX2 files.
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+/// Magic number at the beginning of DDS files.
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+
+/// Enum of block compressed formats of KTX2 & DDS textures, whose 4x4 texels blocks are uploaded by
+/// `glCompressedTexImage*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressedFormat {
+    /// BC1 (DXT1) RGB, 8 bytes per block.
+    Bc1,
+    /// BC1 (DXT1) RGB with 1 bit alpha.
+    Bc1Alpha,
+    /// BC2 (DXT3) RGBA with explicit 4 bits alpha.
+    Bc2,
+    /// BC3 (DXT5) RGBA with interpolated alpha.
+    Bc3,
+    /// BC4 (RGTC1) red channel, e.g. roughness or height maps, 8 bytes per block.
+    Bc4,
+    Bc4Signed,
+    /// BC5 (RGTC2) red & green channels, e.g. normal maps, whose Z has to be reconstructed in shaders by
+    /// `sqrt(1 - x * x - y * y)`.
+    Bc5,
+    Bc5Signed,
+    /// BC6H (BPTC float) HDR RGB.
+    Bc6h,
+    Bc6hSigned,
+    /// BC7 (BPTC) RGBA.
+    Bc7,
+}
+
+impl CompressedFormat {
+    /// Size of each 4x4 texels block in bytes.
+    pub fn block_size(&self) -> usize {
+        match self {
+            CompressedFormat::Bc1
+            | CompressedFormat::Bc1Alpha
+            | CompressedFormat::Bc4
+            | CompressedFormat::Bc4Signed => 8,
+            _ => 16,
+        }
+    }
+
+    /// Size of an image of `width` x `height` in bytes, partial blocks at edges count.
+    pub fn image_size(&self, width: u32, height: u32) -> usize {
+        width.div_ceil(4) as usize * height.div_ceil(4) as usize * self.block_size()
+    }
+
+    /// Internal format of `glCompressedTexImage*`. `srgb` only applies to BC1-BC3 and BC7, as other formats are not
+    /// colors.
+    pub fn gl_internal_format(&self, srgb: bool) -> GLenum {
+        match (self, srgb) {
+            (CompressedFormat::Bc1, false) => COMPRESSED_RGB_S3TC_DXT1,
+            (CompressedFormat::Bc1, true) => COMPRESSED_SRGB_S3TC_DXT1,
+            (CompressedFormat::Bc1Alpha, false) => COMPRESSED_RGBA_S3TC_DXT1,
+            (CompressedFormat::Bc1Alpha, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT1,
+            (CompressedFormat::Bc2, false) => COMPRESSED_RGBA_S3TC_DXT3,
+            (CompressedFormat::Bc2, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT3,
+            (CompressedFormat::Bc3, false) => COMPRESSED_RGBA_S3TC_DXT5,
+            (CompressedFormat::Bc3, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT5,
+            (CompressedFormat::Bc4, _) => gl::COMPRESSED_RED_RGTC1,
+            (CompressedFormat::Bc4Signed, _) => gl::COMPRESSED_SIGNED_RED_RGTC1,
+            (CompressedFormat::Bc5, _) => gl::COMPRESSED_RG_RGTC2,
+            (CompressedFormat::Bc5Signed, _) => gl::COMPRESSED_SIGNED_RG_RGTC2,
+            (CompressedFormat::Bc6h, _) => gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT,
+            (CompressedFormat::Bc6hSigned, _) => gl::COMPRESSED_RGB_BPTC_SIGNED_FLOAT,
+            (CompressedFormat::Bc7, false) => gl::COMPRESSED_RGBA_BPTC_UNORM,
+            (CompressedFormat::Bc7, true) => gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+        }
+    }
+
+    /// Whether current OpenGL context supports this format. RGTC (BC4 & BC5) is core since OpenGL 3.0, BPTC (BC6H &
+    /// BC7) since OpenGL 4.2, and S3TC (BC1-BC3) is an extension.
+    pub fn is_supported(&self, srgb: bool) -> bool {
+        match self {
+            CompressedFormat::Bc1
+            | CompressedFormat::Bc1Alpha
+            | CompressedFormat::Bc2
+            | CompressedFormat::Bc3 => {
+                has_gl_extension("GL_EXT_texture_compression_s3tc")
+                    && (!srgb
+                        || has_gl_extension("GL_EXT_texture_sRGB")
+                        || has_gl_extension("GL_EXT_texture_compression_s3tc_srgb"))
+            }
+            CompressedFormat::Bc4
+            | CompressedFormat::Bc4Signed
+            | CompressedFormat::Bc5
+            | CompressedFormat::Bc5Signed => true,
+            CompressedFormat::Bc6h | CompressedFormat::Bc6hSigned | CompressedFormat::Bc7 => {
+                get_gl_version() >= (4, 2) || has_gl_extension("GL_ARB_texture_compression_bptc")
+            }
+        }
+    }
+
+    /// Formats of texels decompressed by `decompress()` as `(internal_format, format, type)` of `glTexImage*`.
+    pub fn gl_decompressed_formats(&self, srgb: bool) -> (GLenum, GLenum, GLenum) {
+        match self {
+            CompressedFormat::Bc4 => (gl::R8, gl::RED, gl::UNSIGNED_BYTE),
+            CompressedFormat::Bc4Signed => (gl::R8_SNORM, gl::RED, gl::BYTE),
+            CompressedFormat::Bc5 => (gl::RG8, gl::RG, gl::UNSIGNED_BYTE),
+            CompressedFormat::Bc5Signed => (gl::RG8_SNORM, gl::RG, gl::BYTE),
+            CompressedFormat::Bc6h | CompressedFormat::Bc6hSigned => {
+                (gl::RGB16F, gl::RGB, gl::FLOAT)
+            }
+            _ if srgb => (gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE),
+            _ => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
+        }
+    }
+
+    /// Decompress blocks of an image of `width` x `height` on CPU, texels are tightly packed rows in formats of
+    /// `gl_decompressed_formats()`.
+    pub fn decompress(&self, data: &[u8], width: u32, height: u32) -> Vec<u8> {
+        let texel_size = match self {
+            CompressedFormat::Bc4 | CompressedFormat::Bc4Signed => 1,
+            CompressedFormat::Bc5 | CompressedFormat::Bc5Signed => 2,
+            CompressedFormat::Bc6h | CompressedFormat::Bc6hSigned => 12,
+            _ => 4,
+        };
+        let (width, height) = (width as usize, height as usize);
+        let blocks_per_row = width.div_ceil(4);
+
+        let mut pixels = vec![0; width * height * texel_size];
+        for (i, block) in data.chunks_exact(self.block_size()).enumerate() {
+            let texels: Vec<u8> = match self {
+                CompressedFormat::Bc1 => bcn::decode_bc1(block, false).concat(),
+                CompressedFormat::Bc1Alpha => bcn::decode_bc1(block, true).concat(),
+                CompressedFormat::Bc2 => bcn::decode_bc2(block).concat(),
+                CompressedFormat::Bc3 => bcn::decode_bc3(block).concat(),
+                CompressedFormat::Bc4 => bcn::decode_bc4(block, false).to_vec(),
+                CompressedFormat::Bc4Signed => bcn::decode_bc4(block, true).to_vec(),
+                CompressedFormat::Bc5 => bcn::decode_bc5(block, false).concat(),
+                CompressedFormat::Bc5Signed => bcn::decode_bc5(block, true).concat(),
+                CompressedFormat::Bc6h | CompressedFormat::Bc6hSigned => {
+                    bcn::decode_bc6h(block, *self == CompressedFormat::Bc6hSigned)
+                        .into_iter()
+                        .flatten()
+                        .flat_map(f32::to_ne_bytes)
+                        .collect()
+                }
+                CompressedFormat::Bc7 => bcn::decode_bc7(block).concat(),
+            };
+
+            // Copy texels inside of image
+            let (block_x, block_y) = (i % blocks_per_row * 4, i / blocks_per_row * 4);
+            for row in 0..4.min(height.saturating_sub(block_y)) {
+                let columns = 4.min(width - block_x);
+                let src = row * 4 * texel_size;
+                let dst = ((block_y + row) * width + block_x) * texel_size;
+                pixels[dst..dst + columns * texel_size]
+                    .copy_from_slice(&texels[src..src + columns * texel_size]);
+            }
+        }
+        pixels
+    }
+}
+
+/// Images of a KTX2 or DDS file.
+struct CompressedImages {
+    format: CompressedFormat,
+    /// Format of file is sRGB.
+    srgb: bool,
+    width: u32,
+    height: u32,
+    /// Count of array layers, 0 if it's not an array texture.
+    layer_count: u32,
+    /// 6 for cubemaps, 1 for others.
+    face_count: u32,
+    /// Data of each mipmap level, which has images of all faces of all layers in turn.
+    levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImages {
+    fn image_count(&self) -> usize {
+        (self.layer_count.max(1) * self.face_count) as usize
+    }
+
+    fn level_size(&self, level: usize) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    fn read_ktx2(bytes: &[u8]) -> anyhow::Result<Self> {
+        let reader = ktx2::Reader::new(bytes).context("Invalid KTX2 file")?;
+        let header = reader.header();
+        if let Some(scheme) = header.supercompression_scheme {
+            bail!("Supercompression {scheme:?} of KTX2 is not supported");
+        }
+        if header.pixel_depth > 1 {
+            bail!("3D textures are not supported");
+        }
+
+        let (format, srgb) = match header.format {
+            Some(ktx2::Format::BC1_RGB_UNORM_BLOCK) => (CompressedFormat::Bc1, false),
+            Some(ktx2::Format::BC1_RGB_SRGB_BLOCK) => (CompressedFormat::Bc1, true),
+            Some(ktx2::Format::BC1_RGBA_UNORM_BLOCK) => (CompressedFormat::Bc1Alpha, false),
+            Some(ktx2::Format::BC1_RGBA_SRGB_BLOCK) => (CompressedFormat::Bc1Alpha, true),
+            Some(ktx2::Format::BC2_UNORM_BLOCK) => (CompressedFormat::Bc2, false),
+            Some(ktx2::Format::BC2_SRGB_BLOCK) => (CompressedFormat::Bc2, true),
+            Some(ktx2::Format::BC3_UNORM_BLOCK) => (CompressedFormat::Bc3, false),
+            Some(ktx2::Format::BC3_SRGB_BLOCK) => (CompressedFormat::Bc3, true),
+            Some(ktx2::Format::BC4_UNORM_BLOCK) => (CompressedFormat::Bc4, false),
+            Some(ktx2::Format::BC4_SNORM_BLOCK) => (CompressedFormat::Bc4Signed, false),
+            Some(ktx2::Format::BC5_UNORM_BLOCK) => (CompressedFormat::Bc5, false),
+            Some(ktx2::Format::BC5_SNORM_BLOCK) => (CompressedFormat::Bc5Signed, false),
+            Some(ktx2::Format::BC6H_UFLOAT_BLOCK) => (CompressedFormat::Bc6h, false),
+            Some(ktx2::Format::BC6H_SFLOAT_BLOCK) => (CompressedFormat::Bc6hSigned, false),
+            Some(ktx2::Format::BC7_UNORM_BLOCK) => (CompressedFormat::Bc7, false),
+            Some(ktx2::Format::BC7_SRGB_BLOCK) => (CompressedFormat::Bc7, true),
+            format => bail!("Unsupported KTX2 format: {format:?}"),
+        };
+
+        Ok(Self {
+            format,
+            srgb,
+            width: header.pixel_width,
+            height: header.pixel_height.max(1),
+            layer_count: header.layer_count,
+            face_count: header.face_count,
+            levels: reader.levels().map(|level| level.data.to_vec()).collect(),
+        })
+    }
+
+    fn read_dds(bytes: &[u8]) -> anyhow::Result<Self> {
+        let dds = ddsfile::Dds::read(bytes).context("Invalid DDS file")?;
+        if dds.get_depth() > 1 {
+            bail!("3D textures are not supported");
+        }
+
+        let (format, srgb) = if let Some(header10) = &dds.header10 {
+            use ddsfile::DxgiFormat;
+            match header10.dxgi_format {
+                DxgiFormat::BC1_Typeless | DxgiFormat::BC1_UNorm => {
+                    (CompressedFormat::Bc1Alpha, false)
+                }
+                DxgiFormat::BC1_UNorm_sRGB => (CompressedFormat::Bc1Alpha, true),
+                DxgiFormat::BC2_Typeless | DxgiFormat::BC2_UNorm => (CompressedFormat::Bc2, false),
+                DxgiFormat::BC2_UNorm_sRGB => (CompressedFormat::Bc2, true),
+                DxgiFormat::BC3_Typeless | DxgiFormat::BC3_UNorm => (CompressedFormat::Bc3, false),
+                DxgiFormat::BC3_UNorm_sRGB => (CompressedFormat::Bc3, true),
+                DxgiFormat::BC4_Typeless | DxgiFormat::BC4_UNorm => (CompressedFormat::Bc4, false),
+                DxgiFormat::BC4_SNorm => (CompressedFormat::Bc4Signed, false),
+                DxgiFormat::BC5_Typeless | DxgiFormat::BC5_UNorm => (CompressedFormat::Bc5, false),
+                DxgiFormat::BC5_SNorm => (CompressedFormat::Bc5Signed, false),
+                DxgiFormat::BC6H_Typeless | DxgiFormat::BC6H_UF16 => {
+                    (CompressedFormat::Bc6h, false)
+                }
+                DxgiFormat::BC6H_SF16 => (CompressedFormat::Bc6hSigned, false),
+                DxgiFormat::BC7_Typeless | DxgiFormat::BC7_UNorm => (CompressedFormat::Bc7, false),
+                DxgiFormat::BC7_UNorm_sRGB => (CompressedFormat::Bc7, true),
+                format => bail!("Unsupported DXGI format of DDS: {format:?}"),
+            }
+        } else {
+            // Legacy DDS identifies formats by FourCC
+            let fourcc = dds
+                .header
+                .spf
+                .fourcc
+                .as_ref()
+                .map(|fourcc| fourcc.0.to_le_bytes());
+            match fourcc.as_ref() {
+                Some(b"DXT1") => (CompressedFormat::Bc1Alpha, false),
+                Some(b"DXT2" | b"DXT3") => (CompressedFormat::Bc2, false),
+                Some(b"DXT4" | b"DXT5") => (CompressedFormat::Bc3, false),
+                Some(b"ATI1" | b"BC4U") => (CompressedFormat::Bc4, false),
+                Some(b"BC4S") => (CompressedFormat::Bc4Signed, false),
+                Some(b"ATI2" | b"BC5U") => (CompressedFormat::Bc5, false),
+                Some(b"BC5S") => (CompressedFormat::Bc5Signed, false),
+                _ => bail!("Unsupported pixel format of DDS: {:?}", dds.header.spf),
+            }
+        };
+
+        let is_cubemap = match &dds.header10 {
+            Some(header10) => header10.misc_flag.contains(ddsfile::MiscFlag::TEXTURECUBE),
+            None => dds.header.caps2.contains(ddsfile::Caps2::CUBEMAP),
+        };
+        let all_faces = ddsfile::Caps2::CUBEMAP_POSITIVEX
+            | ddsfile::Caps2::CUBEMAP_NEGATIVEX
+            | ddsfile::Caps2::CUBEMAP_POSITIVEY
+            | ddsfile::Caps2::CUBEMAP_NEGATIVEY
+            | ddsfile::Caps2::CUBEMAP_POSITIVEZ
+            | ddsfile::Caps2::CUBEMAP_NEGATIVEZ;
+        if dds.header10.is_none() && is_cubemap && !dds.header.caps2.contains(all_faces) {
+            bail!("Cubemap of DDS must have all faces");
+        }
+        let layer_count = match &dds.header10 {
+            Some(header10) if header10.array_size > 1 => header10.array_size,
+            _ => 0,
+        };
+        let mut images = Self {
+            format,
+            srgb,
+            width: dds.get_width(),
+            height: dds.get_height(),
+            layer_count,
+            face_count: if is_cubemap { 6 } else { 1 },
+            levels: vec![Vec::new(); dds.get_num_mipmap_levels().max(1) as usize],
+        };
+
+        // DDS has mipmap chains of faces of layers in turn
+        let mut offset = 0;
+        for _ in 0..images.image_count() {
+            for level in 0..images.levels.len() {
+                let (width, height) = images.level_size(level);
+                let size = format.image_size(width, height);
+                let Some(data) = dds.data.get(offset..offset + size) else {
+                    bail!("Data of DDS file is truncated");
+                };
+                images.levels[level].extend_from_slice(data);
+                offset += size;
+            }
+        }
+
+        Ok(images)
+    }
+}
+
+impl Texture {
+    /// Create Texture from KTX2 (`.ktx2`) or DDS (`.dds`) file of BC1-BC7 format, with the mipmap levels, cubemap
+    /// faces and array layers in it. Cubemaps are `GL_TEXTURE_CUBE_MAP` textures of `TextureType::Cubemap` unless
+    /// `texture_type` is given, and array textures are `GL_TEXTURE_2D_ARRAY` or `GL_TEXTURE_CUBE_MAP_ARRAY`.
+    ///
+    /// Blocks are uploaded by `glCompressedTexImage*` if the format is supported, or decompressed on CPU otherwise.
+    /// Mipmaps are not generated, so textures only have levels in file. Images are uploaded as they're stored, not
+    /// flipped like `create()`, so they start from top-left corner as cubemaps do.
+    ///
+    /// Textures are sRGB if `options.color_space` is, or if the format of file or `texture_type` is sRGB when it's
+    /// `None`.
+    pub fn create_compressed(
+        path: PathBuf,
+        texture_type: Option<TextureType>,
+        options: &TextureOptions,
+    ) -> anyhow::Result<Self> {
+        let bytes = std::fs::read(&path)
+            .with_context(|| format!("Failed to read compressed texture: {path:?}"))?;
+        let images = if bytes.starts_with(&KTX2_IDENTIFIER) {
+            CompressedImages::read_ktx2(&bytes)
+        } else if bytes.starts_with(DDS_MAGIC) {
+            CompressedImages::read_dds(&bytes)
+        } else {
+            Err(anyhow::anyhow!("Not a KTX2 or DDS file"))
+        }
+        .with_context(|| format!("Failed to load compressed texture: {path:?}"))?;
+        if images.width == 0 || images.levels.is_empty() {
+            bail!("Compressed texture {path:?} is empty");
+        }
+        if images.face_count != 1 && images.face_count != 6 {
+            bail!(
+                "Compressed texture {path:?} has {} faces",
+                images.face_count
+            );
+        }
+
+        let is_cubemap = images.face_count == 6;
+        let is_array = images.layer_count > 0;
+        if is_cubemap
+            && is_array
+            && get_gl_version() < (4, 0)
+            && !has_gl_extension("GL_ARB_texture_cube_map_array")
+        {
+            bail!(
+                "Compressed texture {path:?} is a cubemap array, which requires OpenGL 4.0 or \
+                 GL_ARB_texture_cube_map_array"
+            );
+        }
+        // Validate data of all levels before any GL state is changed
+        let format = images.format;
+        let image_count = images.image_count();
+        for (level, data) in images.levels.iter().enumerate() {
+            let (width, height) = images.level_size(level);
+            if data.len() < format.image_size(width, height) * image_count {
+                bail!("Data of mipmap level {level} of compressed texture {path:?} is truncated");
+            }
+        }
+
+        let tex_type = texture_type.unwrap_or(if is_cubemap {
+            TextureType::Cubemap
+        } else {
+            TextureType::Unknown
+        });
+        let mut texture = Self::new(path, tex_type)?;
+        texture.target = match (is_cubemap, is_array) {
+            (false, false) => gl::TEXTURE_2D,
+            (true, false) => gl::TEXTURE_CUBE_MAP,
+            (false, true) => gl::TEXTURE_2D_ARRAY,
+            (true, true) => gl::TEXTURE_CUBE_MAP_ARRAY,
+        };
+
+        let srgb = match options.color_space {
+            Some(color_space) => color_space == ColorSpace::Srgb,
+            None => images.srgb || texture.tex_type.color_space() == ColorSpace::Srgb,
+        };
+        let is_supported = format.is_supported(srgb);
+        let internal_format = format.gl_internal_format(srgb);
+        let (decompressed_internal_format, decompressed_format, decompressed_type) =
+            format.gl_decompressed_formats(srgb);
+
+        unsafe { gl::BindTexture(texture.target, texture.id) }
+        // Rows of decompressed texels are tightly packed
+        let unpack_alignment = TightUnpackAlignment::new();
+        for (level, data) in images.levels.iter().enumerate() {
+            let (width, height) = images.level_size(level);
+            let image_size = format.image_size(width, height);
+
+            // Decompress images of level if format is unsupported
+            let data = if is_supported {
+                data[..image_size * image_count].to_vec()
+            } else {
+                data.chunks_exact(image_size)
+                    .take(image_count)
+                    .flat_map(|image| format.decompress(image, width, height))
+                    .collect()
+            };
+            let level_image_size = data.len() / image_count;
+
+            let (width, height): (GLsizei, GLsizei) = (width.try_into()?, height.try_into()?);
+            let level = level as GLint;
+            unsafe {
+                match texture.target {
+                    gl::TEXTURE_2D | gl::TEXTURE_CUBE_MAP => {
+                        let targets: Vec<GLenum> = if is_cubemap {
+                            super::CubemapFace::ALL
+                                .iter()
+                                .map(|face| face.gl_target())
+                                .collect()
+                        } else {
+                            vec![gl::TEXTURE_2D]
+                        };
+                        for (target, image) in
+                            targets.into_iter().zip(data.chunks_exact(level_image_size))
+                        {
+                            if is_supported {
+                                gl::CompressedTexImage2D(
+                                    target,
+                                    level,
+                                    internal_format,
+                                    width,
+                                    height,
+                                    0,
+                                    image.len().try_into()?,
+                                    image.as_ptr().cast(),
+                                );
+                            } else {
+                                gl::TexImage2D(
+                                    target,
+                                    level,
+                                    decompressed_internal_format as GLint,
+                                    width,
+                                    height,
+                                    0,
+                                    decompressed_format,
+                                    decompressed_type,
+                                    image.as_ptr().cast(),
+                                );
+                            }
+                        }
+                    }
+                    _ => {
+                        // Layers of cubemap arrays are faces of cubemaps
+                        let depth = image_count.try_into()?;
+                        if is_supported {
+                            gl::CompressedTexImage3D(
+                                texture.target,
+                                level,
+                                internal_format,
+                                width,
+                                height,
+                                depth,
+                                0,
+                                data.len().try_into()?,
+                                data.as_ptr().cast(),
+                            );
+                        } else {
+                            gl::TexImage3D(
+                                texture.target,
+                                level,
+                                decompressed_internal_format as GLint,
+                                width,
+                                height,
+                                depth,
+                                0,
+                                decompressed_format,
+                                decompressed_type,
+                                data.as_ptr().cast(),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        drop(unpack_alignment);
+        unsafe {
+            gl::TexParameteri(texture.target, gl::TEXTURE_BASE_LEVEL, 0);
+            gl::TexParameteri(
+                texture.target,
+                gl::TEXTURE_MAX_LEVEL,
+                (images.levels.len() - 1) as GLint,
+            );
+        }
+
+        tracing::debug!(
+            "Compressed texture loaded. path: {:?}, format: {:?}, size: {:?}, levels: {}, layers: {}, faces: {}, \
+             decompressed: {}",
+            texture.path,
+            format,
+            (images.width, images.height),
+            images.levels.len(),
+            images.layer_count,
+            images.face_count,
+            !is_supported
+        );
+
+        texture.set_options(options);
+
+        Ok(texture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// BC4 block whose texels are all `value`.
+    fn bc4_solid(value: u8) -> [u8; 8] {
+        [value, value, 0, 0, 0, 0, 0, 0]
+    }
+
+    #[test]
+    fn decompress_partial_blocks() {
+        // 6x6 has 2x2 blocks, texels outside of image are dropped
+        let data = [10, 20, 30, 40].map(bc4_solid).concat();
+        let pixels = CompressedFormat::Bc4.decompress(&data, 6, 6);
+        assert_eq!(pixels.len(), 6 * 6);
+        for y in 0..6 {
+            for x in 0..6 {
+                let block = y / 4 * 2 + x / 4;
+                assert_eq!(
+                    pixels[y * 6 + x],
+                    [10, 20, 30, 40][block],
+                    "pixel ({x}, {y})"
+                );
+            }
+        }
+
+        // 1x1 only has texel 0 of its block
+        let pixels = CompressedFormat::Bc4.decompress(&bc4_solid(77), 1, 1);
+        assert_eq!(pixels, [77]);
+    }
+
+    #[test]
+    fn decompress_texel_sizes() {
+        // Solid red & blue BC1 blocks of a 5x3 image
+        let data = [
+            0x00, 0xF8, 0x00, 0xF8, 0, 0, 0, 0, //
+            0x1F, 0x00, 0x1F, 0x00, 0, 0, 0, 0,
+        ];
+        let pixels = CompressedFormat::Bc1.decompress(&data, 5, 3);
+        assert_eq!(pixels.len(), 5 * 3 * 4);
+        for (i, pixel) in pixels.chunks_exact(4).enumerate() {
+            let expected = if i % 5 < 4 {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 255, 255]
+            };
+            assert_eq!(pixel, expected, "pixel {i}");
+        }
+
+        // BC5 has 2 bytes per texel
+        let data = [bc4_solid(1), bc4_solid(2)].concat();
+        let pixels = CompressedFormat::Bc5.decompress(&data, 3, 2);
+        assert_eq!(pixels, [1, 2].repeat(3 * 2));
+
+        // BC6H has 3 floats per texel, a block of zero endpoints is black
+        let pixels = CompressedFormat::Bc6h.decompress(
+            &[0b00011, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            2,
+            3,
+        );
+        assert_eq!(pixels.len(), 2 * 3 * 12);
+        assert!(pixels.iter().all(|&byte| byte == 0));
+    }
+}
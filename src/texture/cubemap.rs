@@ -0,0 +1,395 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use gl::types::*;
+use image::{DynamicImage, GenericImageView};
+use nalgebra as na;
+
+use super::{open_hdr_image, TightUnpackAlignment};
+use crate::{ColorFormat, ColorSpace, Texture, TextureOptions, TextureType};
+
+/// Face names of `assets/textures/skybox`, in order of `CubemapFace::ALL`.
+pub const SKYBOX_FACE_NAMES: [&str; 6] = ["right", "left", "top", "bottom", "front", "back"];
+/// Face names by axes, e.g. `posx.jpg`, in order of `CubemapFace::ALL`.
+pub const AXIS_FACE_NAMES: [&str; 6] = ["posx", "negx", "posy", "negy", "posz", "negz"];
+
+/// Enum of cubemap faces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubemapFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubemapFace {
+    /// All faces in order of `GL_TEXTURE_CUBE_MAP_POSITIVE_X + i`.
+    pub const ALL: [CubemapFace; 6] = [
+        CubemapFace::PositiveX,
+        CubemapFace::NegativeX,
+        CubemapFace::PositiveY,
+        CubemapFace::NegativeY,
+        CubemapFace::PositiveZ,
+        CubemapFace::NegativeZ,
+    ];
+
+    /// Get texture target of face, e.g. `GL_TEXTURE_CUBE_MAP_POSITIVE_X`.
+    pub fn gl_target(&self) -> GLenum {
+        gl::TEXTURE_CUBE_MAP_POSITIVE_X + *self as GLenum
+    }
+
+    /// Direction from center of cube through texel of face at `(s, t)`, which are in [-1, 1] from the first texel
+    /// of face image, see table "Selection of cube map images" of OpenGL specification.
+    fn direction(&self, s: f32, t: f32) -> na::Vector3<f32> {
+        match self {
+            CubemapFace::PositiveX => na::Vector3::new(1.0, -t, -s),
+            CubemapFace::NegativeX => na::Vector3::new(-1.0, -t, s),
+            CubemapFace::PositiveY => na::Vector3::new(s, 1.0, t),
+            CubemapFace::NegativeY => na::Vector3::new(s, -1.0, -t),
+            CubemapFace::PositiveZ => na::Vector3::new(s, -t, 1.0),
+            CubemapFace::NegativeZ => na::Vector3::new(-s, -t, -1.0),
+        }
+    }
+}
+
+/// Enum of layouts of faces in a single cubemap image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubemapLayout {
+    /// 4x3 faces, `+Y` above and `-Y` below `+Z` of the middle row `-X +Z +X -Z`.
+    HorizontalCross,
+    /// 3x4 faces, same as `HorizontalCross` except `-Z` is below `-Y` and rotated by 180°.
+    VerticalCross,
+    /// 6x1 faces, `+X -X +Y -Y +Z -Z`.
+    HorizontalStrip,
+    /// 1x6 faces, `+X -X +Y -Y +Z -Z` from top to bottom.
+    VerticalStrip,
+}
+
+impl CubemapLayout {
+    /// Guess layout from aspect ratio of image.
+    pub fn detect(width: u32, height: u32) -> Option<Self> {
+        match (width, height) {
+            (w, h) if w * 3 == h * 4 => Some(CubemapLayout::HorizontalCross),
+            (w, h) if w * 4 == h * 3 => Some(CubemapLayout::VerticalCross),
+            (w, h) if w == h * 6 => Some(CubemapLayout::HorizontalStrip),
+            (w, h) if w * 6 == h => Some(CubemapLayout::VerticalStrip),
+            _ => None,
+        }
+    }
+
+    /// Size of faces of image in this layout.
+    fn face_size(&self, width: u32) -> u32 {
+        match self {
+            CubemapLayout::HorizontalCross => width / 4,
+            CubemapLayout::VerticalCross => width / 3,
+            CubemapLayout::HorizontalStrip => width / 6,
+            CubemapLayout::VerticalStrip => width,
+        }
+    }
+
+    /// Cell (column, row) of face in this layout.
+    fn face_cell(&self, face: CubemapFace) -> (u32, u32) {
+        let index = face as u32;
+        match self {
+            CubemapLayout::HorizontalCross | CubemapLayout::VerticalCross => match face {
+                CubemapFace::PositiveX => (2, 1),
+                CubemapFace::NegativeX => (0, 1),
+                CubemapFace::PositiveY => (1, 0),
+                CubemapFace::NegativeY => (1, 2),
+                CubemapFace::PositiveZ => (1, 1),
+                CubemapFace::NegativeZ if *self == CubemapLayout::HorizontalCross => (3, 1),
+                CubemapFace::NegativeZ => (1, 3),
+            },
+            CubemapLayout::HorizontalStrip => (index, 0),
+            CubemapLayout::VerticalStrip => (0, index),
+        }
+    }
+}
+
+impl Texture {
+    /// Create cubemap from six face images, `faces` are in order of `CubemapFace::ALL`. Faces must be square and of
+    /// the same size.
+    ///
+    /// Face images are not flipped, as cubemaps follow the convention of RenderMan whose images start from top-left
+    /// corner. Cubemaps are usually sampled with `WrapMode::ClampToEdge`.
+    pub fn create_cubemap(faces: &[PathBuf; 6], options: &TextureOptions) -> anyhow::Result<Self> {
+        let mut images = Vec::with_capacity(6);
+        for face in faces.iter() {
+            let img = image::open(face)
+                .with_context(|| format!("Failed to load cubemap face: {face:?}"))?;
+            images.push(img);
+        }
+
+        let texture = Self::new(faces[0].clone(), TextureType::Cubemap)?;
+        texture.upload_cubemap_faces(&images, options)?;
+
+        Ok(texture)
+    }
+
+    /// Create cubemap from face images `{dir}/{name}.{extension}`, `face_names` are in order of `CubemapFace::ALL`,
+    /// e.g. `SKYBOX_FACE_NAMES`.
+    pub fn create_cubemap_from_dir(
+        dir: &Path,
+        face_names: &[&str; 6],
+        extension: &str,
+        options: &TextureOptions,
+    ) -> anyhow::Result<Self> {
+        let faces = face_names.map(|name| dir.join(format!("{name}.{extension}")));
+
+        let mut texture = Self::create_cubemap(&faces, options)?;
+        texture.path = dir.to_path_buf();
+
+        Ok(texture)
+    }
+
+    /// Create cubemap from an image with all faces in `layout`, or layout detected from its aspect ratio if it's
+    /// `None`.
+    pub fn create_cubemap_from_layout(
+        path: PathBuf,
+        layout: Option<CubemapLayout>,
+        options: &TextureOptions,
+    ) -> anyhow::Result<Self> {
+        let img =
+            image::open(&path).with_context(|| format!("Failed to load cubemap: {path:?}"))?;
+        let (width, height) = img.dimensions();
+        let Some(layout) = layout.or_else(|| CubemapLayout::detect(width, height)) else {
+            bail!("Unknown layout of cubemap image {path:?} with size {width}x{height}");
+        };
+
+        let face_size = layout.face_size(width);
+        let images: Vec<_> = CubemapFace::ALL
+            .iter()
+            .map(|&face| {
+                let (column, row) = layout.face_cell(face);
+                let img = img.crop_imm(column * face_size, row * face_size, face_size, face_size);
+                if layout == CubemapLayout::VerticalCross && face == CubemapFace::NegativeZ {
+                    img.rotate180()
+                } else {
+                    img
+                }
+            })
+            .collect();
+
+        let texture = Self::new(path, TextureType::Cubemap)?;
+        texture.upload_cubemap_faces(&images, options)?;
+
+        Ok(texture)
+    }
+
+    /// Create cubemap of `size` with internal `format` from equirectangular (latitude-longitude) image file, e.g.
+    /// Radiance HDR or OpenEXR environment map as `create_hdr()` loads. The image is mapped to directions in the same
+    /// way as `027-equirectangular.frag`, and resampled bilinearly on CPU.
+    pub fn create_cubemap_from_equirect(
+        path: PathBuf,
+        size: u32,
+        format: ColorFormat,
+        options: &TextureOptions,
+    ) -> anyhow::Result<Self> {
+        let img = open_hdr_image(&path)
+            .with_context(|| format!("Failed to load equirectangular image: {path:?}"))?
+            .into_rgba32f();
+        let (width, height) = img.dimensions();
+        if width == 0 || height == 0 {
+            bail!("Equirectangular image {path:?} is empty");
+        }
+
+        // Bilinear sampling, wrapped horizontally & clamped vertically
+        let texel = |x: i64, y: i64| {
+            let x = x.rem_euclid(width as i64) as u32;
+            let y = y.clamp(0, height as i64 - 1) as u32;
+            na::Vector4::from(img.get_pixel(x, y).0)
+        };
+        let sample = |direction: na::Vector3<f32>| {
+            let u = direction.z.atan2(direction.x) * std::f32::consts::FRAC_1_PI * 0.5 + 0.5;
+            let v = direction.y.clamp(-1.0, 1.0).asin() * std::f32::consts::FRAC_1_PI + 0.5;
+            // v is from bottom of image, as the image is flipped when loaded as texture
+            let x = u * width as f32 - 0.5;
+            let y = (1.0 - v) * height as f32 - 0.5;
+            let (x0, y0) = (x.floor(), y.floor());
+            let (fx, fy) = (x - x0, y - y0);
+            let (x0, y0) = (x0 as i64, y0 as i64);
+            let top = texel(x0, y0).lerp(&texel(x0 + 1, y0), fx);
+            let bottom = texel(x0, y0 + 1).lerp(&texel(x0 + 1, y0 + 1), fx);
+            top.lerp(&bottom, fy)
+        };
+
+        let (_, img_format, _) = format.gl_formats();
+        let channel_count = match img_format {
+            gl::RED => 1,
+            gl::RG => 2,
+            gl::RGB => 3,
+            _ => 4,
+        };
+
+        let texture = Self::new(path, TextureType::Cubemap)?;
+        texture.alloc_cubemap(size, format, 1)?;
+        for face in CubemapFace::ALL {
+            let mut pixels: Vec<f32> = Vec::with_capacity((size * size) as usize * channel_count);
+            for y in 0..size {
+                for x in 0..size {
+                    let s = 2.0 * (x as f32 + 0.5) / size as f32 - 1.0;
+                    let t = 2.0 * (y as f32 + 0.5) / size as f32 - 1.0;
+                    let color = sample(face.direction(s, t).normalize());
+                    pixels.extend(color.iter().take(channel_count));
+                }
+            }
+            unsafe {
+                gl::TexSubImage2D(
+                    face.gl_target(),
+                    0,
+                    0,
+                    0,
+                    size.try_into()?,
+                    size.try_into()?,
+                    img_format,
+                    gl::FLOAT,
+                    pixels.as_ptr().cast(),
+                );
+            }
+        }
+
+        unsafe { gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP) }
+        texture.set_options(options);
+
+        Ok(texture)
+    }
+
+    /// Create cubemap without image data, e.g. to be rendered into by `Framebuffer::attach_color_target()`. Faces are
+    /// allocated with `mip_levels` levels of `size`, `size / 2`, etc.
+    pub fn create_empty_cubemap(
+        size: u32,
+        format: ColorFormat,
+        mip_levels: u32,
+        options: &TextureOptions,
+    ) -> anyhow::Result<Self> {
+        let texture = Self::new(PathBuf::new(), TextureType::Cubemap)?;
+        texture.alloc_cubemap(size, format, mip_levels)?;
+        texture.set_options(options);
+
+        Ok(texture)
+    }
+
+    /// Allocate all faces of cubemap with `mip_levels` levels, and limit max level of cubemap to them.
+    fn alloc_cubemap(&self, size: u32, format: ColorFormat, mip_levels: u32) -> anyhow::Result<()> {
+        if size == 0 || mip_levels == 0 || mip_levels > u32::BITS - size.leading_zeros() {
+            bail!("Invalid cubemap size {size} with {mip_levels} mipmap levels");
+        }
+
+        let (internal_format, img_format, img_type) = format.gl_formats();
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.id);
+            for level in 0..mip_levels {
+                let level_size: GLsizei = (size >> level).try_into()?;
+                for face in CubemapFace::ALL {
+                    gl::TexImage2D(
+                        face.gl_target(),
+                        level as GLint,
+                        internal_format as GLint,
+                        level_size,
+                        level_size,
+                        0,
+                        img_format,
+                        img_type,
+                        core::ptr::null(),
+                    );
+                }
+            }
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_BASE_LEVEL, 0);
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MAX_LEVEL,
+                (mip_levels - 1) as GLint,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Upload images of faces in order of `CubemapFace::ALL`, float images are uploaded as `RGB16F`/`RGBA16F`, and 8
+    /// bits images are linear unless `options.color_space` is sRGB.
+    fn upload_cubemap_faces(
+        &self,
+        faces: &[DynamicImage],
+        options: &TextureOptions,
+    ) -> anyhow::Result<()> {
+        let (size, _) = faces[0].dimensions();
+        if let Some(face) = faces.iter().find(|face| face.dimensions() != (size, size)) {
+            bail!(
+                "Cubemap faces must be squares of the same size, but got {:?} and {:?}",
+                faces[0].dimensions(),
+                face.dimensions()
+            );
+        }
+        let has_alpha = faces.iter().any(|face| face.color().has_alpha());
+        let is_float = faces.iter().any(|face| {
+            matches!(
+                face.color(),
+                image::ColorType::Rgb32F | image::ColorType::Rgba32F
+            )
+        });
+
+        let srgb = options.color_space.unwrap_or(self.tex_type.color_space()) == ColorSpace::Srgb;
+
+        unsafe { gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.id) }
+        let _unpack_alignment = TightUnpackAlignment::new();
+        for (face, img) in CubemapFace::ALL.iter().zip(faces) {
+            let (internal_format, img_format, img_type, pixels) = match (is_float, has_alpha) {
+                (false, false) => (
+                    if srgb { gl::SRGB8 } else { gl::RGB8 },
+                    gl::RGB,
+                    gl::UNSIGNED_BYTE,
+                    img.to_rgb8().into_raw(),
+                ),
+                (false, true) => (
+                    if srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 },
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    img.to_rgba8().into_raw(),
+                ),
+                (true, false) => (
+                    gl::RGB16F,
+                    gl::RGB,
+                    gl::FLOAT,
+                    float_bytes(img.to_rgb32f().into_raw()),
+                ),
+                (true, true) => (
+                    gl::RGBA16F,
+                    gl::RGBA,
+                    gl::FLOAT,
+                    float_bytes(img.to_rgba32f().into_raw()),
+                ),
+            };
+            unsafe {
+                gl::TexImage2D(
+                    face.gl_target(),
+                    0,
+                    internal_format as GLint,
+                    size.try_into()?,
+                    size.try_into()?,
+                    0,
+                    img_format,
+                    img_type,
+                    pixels.as_ptr().cast(),
+                );
+            }
+        }
+
+        tracing::debug!(
+            "Cubemap loaded. path: {:?}, face size: {}, float: {}",
+            self.path,
+            size,
+            is_float
+        );
+
+        unsafe { gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP) }
+        self.set_options(options);
+
+        Ok(())
+    }
+}
+
+fn float_bytes(pixels: Vec<f32>) -> Vec<u8> {
+    pixels.into_iter().flat_map(f32::to_ne_bytes).collect()
+}
@@ -0,0 +1,504 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+
+use gl::types::*;
+use nalgebra as na;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum UniformError {
+    /// Uniform is not declared, or it's optimized out by compiler since it's unused.
+    #[error("Uniform `{0}` is not found, or it's not active")]
+    NotFound(String),
+    #[error("Uniform `{name}` is `{expected}`, but value of `{actual}` is given")]
+    TypeMismatch {
+        name: String,
+        expected: &'static str,
+        actual: &'static str,
+    },
+    #[error("Uniform `{name}` has {size} elements, but {count} values are given")]
+    TooManyElements {
+        name: String,
+        size: usize,
+        count: usize,
+    },
+    /// Uniform block is not declared, or none of its members is used.
+    #[error("Uniform block `{0}` is not found, or it's not active")]
+    BlockNotFound(String),
+    #[error("Uniform block `{name}` takes {size} bytes, but uniform buffer has only {buffer_size} bytes")]
+    BlockSizeMismatch {
+        name: String,
+        size: usize,
+        buffer_size: usize,
+    },
+}
+
+/// Info of an active uniform, queried by `glGetActiveUniform`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UniformInfo {
+    pub location: GLint,
+    /// GL type of uniform (element), e.g. `GL_FLOAT_VEC3`.
+    pub ty: GLenum,
+    /// Number of array elements from `location` to the end of array, 1 for non-array uniform.
+    pub size: usize,
+}
+
+impl UniformInfo {
+    /// GLSL name of uniform type.
+    pub fn type_name(&self) -> &'static str {
+        gl_type_name(self.ty)
+    }
+}
+
+/// Query all active uniforms of a linked program, except members of uniform blocks which have no location.
+///
+/// Array uniform `foo[N]` is recorded as `foo` (whole array), and each element `foo[i]` (from `i` to the end).
+/// Members of struct are recorded by full name, e.g. `lights[0].position`.
+///
+/// wrap `glGetActiveUniform`
+pub(crate) fn query_active_uniforms(program_id: GLuint) -> HashMap<String, UniformInfo> {
+    let mut uniform_count = 0;
+    let mut max_name_len = 0;
+    unsafe {
+        gl::GetProgramiv(program_id, gl::ACTIVE_UNIFORMS, &mut uniform_count);
+        gl::GetProgramiv(program_id, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_name_len);
+    }
+
+    let mut uniforms = HashMap::new();
+    for index in 0..uniform_count as GLuint {
+        let mut name_buf = vec![0u8; max_name_len.max(1) as usize];
+        let mut name_len = 0;
+        let mut size = 0;
+        let mut ty = 0;
+        unsafe {
+            gl::GetActiveUniform(
+                program_id,
+                index,
+                name_buf.len() as GLsizei,
+                &mut name_len,
+                &mut size,
+                &mut ty,
+                name_buf.as_mut_ptr() as *mut GLchar,
+            );
+        }
+        name_buf.truncate(name_len as usize);
+        let Ok(name) = String::from_utf8(name_buf) else {
+            continue;
+        };
+
+        let location = get_location(program_id, &name);
+        if location < 0 {
+            continue; // member of uniform block
+        }
+
+        let size = size.max(1) as usize;
+        match name.strip_suffix("[0]") {
+            Some(base_name) => {
+                uniforms.insert(base_name.to_string(), UniformInfo { location, ty, size });
+                // Locations of array elements are not guaranteed to be consecutive, query each of them.
+                for i in 0..size {
+                    let element_name = format!("{base_name}[{i}]");
+                    let location = get_location(program_id, &element_name);
+                    if location >= 0 {
+                        let size = size - i;
+                        uniforms.insert(element_name, UniformInfo { location, ty, size });
+                    }
+                }
+            }
+            None => {
+                uniforms.insert(name, UniformInfo { location, ty, size });
+            }
+        }
+    }
+
+    uniforms
+}
+
+/// Info of an active uniform block, queried by `glGetActiveUniformBlockiv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UniformBlockInfo {
+    pub index: GLuint,
+    /// Minimum size in bytes of buffer backing the block.
+    pub size: usize,
+}
+
+/// Query all active uniform blocks of a linked program by block name (not instance name).
+///
+/// wrap `glGetActiveUniformBlockName`, `glGetActiveUniformBlockiv`
+pub(crate) fn query_uniform_blocks(program_id: GLuint) -> HashMap<String, UniformBlockInfo> {
+    let mut block_count = 0;
+    let mut max_name_len = 0;
+    unsafe {
+        gl::GetProgramiv(program_id, gl::ACTIVE_UNIFORM_BLOCKS, &mut block_count);
+        gl::GetProgramiv(
+            program_id,
+            gl::ACTIVE_UNIFORM_BLOCK_MAX_NAME_LENGTH,
+            &mut max_name_len,
+        );
+    }
+
+    let mut blocks = HashMap::new();
+    for index in 0..block_count as GLuint {
+        let mut name_buf = vec![0u8; max_name_len.max(1) as usize];
+        let mut name_len = 0;
+        let mut size = 0;
+        unsafe {
+            gl::GetActiveUniformBlockName(
+                program_id,
+                index,
+                name_buf.len() as GLsizei,
+                &mut name_len,
+                name_buf.as_mut_ptr() as *mut GLchar,
+            );
+            gl::GetActiveUniformBlockiv(program_id, index, gl::UNIFORM_BLOCK_DATA_SIZE, &mut size);
+        }
+        name_buf.truncate(name_len as usize);
+        let Ok(name) = String::from_utf8(name_buf) else {
+            continue;
+        };
+
+        let size = size as usize;
+        blocks.insert(name, UniformBlockInfo { index, size });
+    }
+
+    blocks
+}
+
+fn get_location(program_id: GLuint, name: &str) -> GLint {
+    match CString::new(name) {
+        Ok(name) => unsafe { gl::GetUniformLocation(program_id, name.as_ptr()) },
+        Err(_) => -1,
+    }
+}
+
+/// Whether `ty` is a sampler type, which is set by texture unit as `int`.
+pub fn is_sampler_type(ty: GLenum) -> bool {
+    matches!(
+        ty,
+        gl::SAMPLER_1D
+            | gl::SAMPLER_2D
+            | gl::SAMPLER_3D
+            | gl::SAMPLER_CUBE
+            | gl::SAMPLER_1D_SHADOW
+            | gl::SAMPLER_2D_SHADOW
+            | gl::SAMPLER_1D_ARRAY
+            | gl::SAMPLER_2D_ARRAY
+            | gl::SAMPLER_1D_ARRAY_SHADOW
+            | gl::SAMPLER_2D_ARRAY_SHADOW
+            | gl::SAMPLER_2D_MULTISAMPLE
+            | gl::SAMPLER_2D_MULTISAMPLE_ARRAY
+            | gl::SAMPLER_CUBE_SHADOW
+            | gl::SAMPLER_CUBE_MAP_ARRAY
+            | gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW
+            | gl::SAMPLER_BUFFER
+            | gl::SAMPLER_2D_RECT
+            | gl::SAMPLER_2D_RECT_SHADOW
+            | gl::INT_SAMPLER_1D
+            | gl::INT_SAMPLER_2D
+            | gl::INT_SAMPLER_3D
+            | gl::INT_SAMPLER_CUBE
+            | gl::INT_SAMPLER_1D_ARRAY
+            | gl::INT_SAMPLER_2D_ARRAY
+            | gl::INT_SAMPLER_2D_MULTISAMPLE
+            | gl::INT_SAMPLER_2D_MULTISAMPLE_ARRAY
+            | gl::INT_SAMPLER_BUFFER
+            | gl::INT_SAMPLER_2D_RECT
+            | gl::UNSIGNED_INT_SAMPLER_1D
+            | gl::UNSIGNED_INT_SAMPLER_2D
+            | gl::UNSIGNED_INT_SAMPLER_3D
+            | gl::UNSIGNED_INT_SAMPLER_CUBE
+            | gl::UNSIGNED_INT_SAMPLER_1D_ARRAY
+            | gl::UNSIGNED_INT_SAMPLER_2D_ARRAY
+            | gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE
+            | gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY
+            | gl::UNSIGNED_INT_SAMPLER_BUFFER
+            | gl::UNSIGNED_INT_SAMPLER_2D_RECT
+    )
+}
+
+/// GLSL name of uniform type, e.g. `vec3` of `GL_FLOAT_VEC3`.
+pub fn gl_type_name(ty: GLenum) -> &'static str {
+    match ty {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::UNSIGNED_INT_VEC2 => "uvec2",
+        gl::UNSIGNED_INT_VEC3 => "uvec3",
+        gl::UNSIGNED_INT_VEC4 => "uvec4",
+        gl::BOOL => "bool",
+        gl::BOOL_VEC2 => "bvec2",
+        gl::BOOL_VEC3 => "bvec3",
+        gl::BOOL_VEC4 => "bvec4",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::FLOAT_MAT2x3 => "mat2x3",
+        gl::FLOAT_MAT2x4 => "mat2x4",
+        gl::FLOAT_MAT3x2 => "mat3x2",
+        gl::FLOAT_MAT3x4 => "mat3x4",
+        gl::FLOAT_MAT4x2 => "mat4x2",
+        gl::FLOAT_MAT4x3 => "mat4x3",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_3D => "sampler3D",
+        gl::SAMPLER_CUBE => "samplerCube",
+        gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        gl::SAMPLER_2D_ARRAY => "sampler2DArray",
+        ty if is_sampler_type(ty) => "sampler",
+        _ => "unknown",
+    }
+}
+
+/// Element type of uniform, which can be sent by `glUniform*v` as an array.
+pub trait UniformElement: Sized {
+    /// Name of type for error message.
+    const TYPE_NAME: &'static str;
+
+    /// Whether uniform of GL type `ty` accepts value of this type.
+    fn accepts(ty: GLenum) -> bool;
+
+    /// Send `values` to uniform array of currently bound program, starting from `location`.
+    fn upload(location: GLint, values: &[Self]);
+}
+
+/// Value which can be sent to a uniform by `ShaderProgram::set_uniform`: `UniformElement` types, or slices/arrays/vectors
+/// of them for array uniforms.
+pub trait UniformValue {
+    const TYPE_NAME: &'static str;
+
+    fn accepts(ty: GLenum) -> bool;
+
+    /// Number of array elements.
+    fn count(&self) -> usize;
+
+    /// Send value to uniform of currently bound program.
+    fn upload(&self, location: GLint);
+}
+
+impl<T: UniformElement> UniformValue for [T] {
+    const TYPE_NAME: &'static str = T::TYPE_NAME;
+
+    fn accepts(ty: GLenum) -> bool {
+        T::accepts(ty)
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn upload(&self, location: GLint) {
+        T::upload(location, self)
+    }
+}
+
+impl<T: UniformElement, const N: usize> UniformValue for [T; N] {
+    const TYPE_NAME: &'static str = T::TYPE_NAME;
+
+    fn accepts(ty: GLenum) -> bool {
+        T::accepts(ty)
+    }
+
+    fn count(&self) -> usize {
+        N
+    }
+
+    fn upload(&self, location: GLint) {
+        T::upload(location, self)
+    }
+}
+
+impl<T: UniformElement> UniformValue for Vec<T> {
+    const TYPE_NAME: &'static str = T::TYPE_NAME;
+
+    fn accepts(ty: GLenum) -> bool {
+        T::accepts(ty)
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn upload(&self, location: GLint) {
+        T::upload(location, self)
+    }
+}
+
+/// Implement `UniformElement` & `UniformValue` for a type, whose memory layout is continuous components of `$component`
+/// type. `$accepts` checks GL type of uniform.
+macro_rules! impl_uniform {
+    ($ty:ty, $name:literal, |$gl_ty:ident| $accepts:expr, |$location:ident, $count:ident, $ptr:ident: *const $component:ty| $upload:expr) => {
+        impl UniformElement for $ty {
+            const TYPE_NAME: &'static str = $name;
+
+            fn accepts($gl_ty: GLenum) -> bool {
+                $accepts
+            }
+
+            fn upload(location: GLint, values: &[Self]) {
+                let $location = location;
+                let $count = values.len() as GLsizei;
+                let $ptr = values.as_ptr() as *const $component;
+                unsafe { $upload }
+            }
+        }
+
+        impl UniformValue for $ty {
+            const TYPE_NAME: &'static str = $name;
+
+            fn accepts(ty: GLenum) -> bool {
+                <Self as UniformElement>::accepts(ty)
+            }
+
+            fn count(&self) -> usize {
+                1
+            }
+
+            fn upload(&self, location: GLint) {
+                <Self as UniformElement>::upload(location, std::slice::from_ref(self))
+            }
+        }
+    };
+}
+
+impl_uniform!(
+    f32,
+    "f32",
+    |ty| matches!(ty, gl::FLOAT),
+    |l, n, p: *const f32| gl::Uniform1fv(l, n, p)
+);
+impl_uniform!(
+    na::Vector2<f32>,
+    "Vector2<f32>",
+    |ty| matches!(ty, gl::FLOAT_VEC2),
+    |l, n, p: *const f32| gl::Uniform2fv(l, n, p)
+);
+impl_uniform!(
+    na::Vector3<f32>,
+    "Vector3<f32>",
+    |ty| matches!(ty, gl::FLOAT_VEC3),
+    |l, n, p: *const f32| gl::Uniform3fv(l, n, p)
+);
+impl_uniform!(
+    na::Vector4<f32>,
+    "Vector4<f32>",
+    |ty| matches!(ty, gl::FLOAT_VEC4),
+    |l, n, p: *const f32| gl::Uniform4fv(l, n, p)
+);
+impl_uniform!(
+    na::Point2<f32>,
+    "Point2<f32>",
+    |ty| matches!(ty, gl::FLOAT_VEC2),
+    |l, n, p: *const f32| gl::Uniform2fv(l, n, p)
+);
+impl_uniform!(
+    na::Point3<f32>,
+    "Point3<f32>",
+    |ty| matches!(ty, gl::FLOAT_VEC3),
+    |l, n, p: *const f32| gl::Uniform3fv(l, n, p)
+);
+
+// tip: `bool` uniforms can be set by either int or float functions, and samplers are set by texture unit as int.
+impl_uniform!(
+    i32,
+    "i32",
+    |ty| matches!(ty, gl::INT | gl::BOOL) || is_sampler_type(ty),
+    |l, n, p: *const i32| gl::Uniform1iv(l, n, p)
+);
+impl_uniform!(
+    na::Vector2<i32>,
+    "Vector2<i32>",
+    |ty| matches!(ty, gl::INT_VEC2 | gl::BOOL_VEC2),
+    |l, n, p: *const i32| gl::Uniform2iv(l, n, p)
+);
+impl_uniform!(
+    na::Vector3<i32>,
+    "Vector3<i32>",
+    |ty| matches!(ty, gl::INT_VEC3 | gl::BOOL_VEC3),
+    |l, n, p: *const i32| gl::Uniform3iv(l, n, p)
+);
+impl_uniform!(
+    na::Vector4<i32>,
+    "Vector4<i32>",
+    |ty| matches!(ty, gl::INT_VEC4 | gl::BOOL_VEC4),
+    |l, n, p: *const i32| gl::Uniform4iv(l, n, p)
+);
+
+impl_uniform!(
+    u32,
+    "u32",
+    |ty| matches!(ty, gl::UNSIGNED_INT | gl::BOOL),
+    |l, n, p: *const u32| gl::Uniform1uiv(l, n, p)
+);
+impl_uniform!(
+    na::Vector2<u32>,
+    "Vector2<u32>",
+    |ty| matches!(ty, gl::UNSIGNED_INT_VEC2),
+    |l, n, p: *const u32| gl::Uniform2uiv(l, n, p)
+);
+impl_uniform!(
+    na::Vector3<u32>,
+    "Vector3<u32>",
+    |ty| matches!(ty, gl::UNSIGNED_INT_VEC3),
+    |l, n, p: *const u32| gl::Uniform3uiv(l, n, p)
+);
+impl_uniform!(
+    na::Vector4<u32>,
+    "Vector4<u32>",
+    |ty| matches!(ty, gl::UNSIGNED_INT_VEC4),
+    |l, n, p: *const u32| gl::Uniform4uiv(l, n, p)
+);
+
+// Matrices of nalgebra are column-major, same as GLSL, so no transpose is needed.
+impl_uniform!(
+    na::Matrix2<f32>,
+    "Matrix2<f32>",
+    |ty| matches!(ty, gl::FLOAT_MAT2),
+    |l, n, p: *const f32| gl::UniformMatrix2fv(l, n, gl::FALSE, p)
+);
+impl_uniform!(
+    na::Matrix3<f32>,
+    "Matrix3<f32>",
+    |ty| matches!(ty, gl::FLOAT_MAT3),
+    |l, n, p: *const f32| gl::UniformMatrix3fv(l, n, gl::FALSE, p)
+);
+impl_uniform!(
+    na::Matrix4<f32>,
+    "Matrix4<f32>",
+    |ty| matches!(ty, gl::FLOAT_MAT4),
+    |l, n, p: *const f32| gl::UniformMatrix4fv(l, n, gl::FALSE, p)
+);
+
+/// `bool` is sent as int, since memory layout of Rust `bool` is not `GLint`.
+impl UniformElement for bool {
+    const TYPE_NAME: &'static str = "bool";
+
+    fn accepts(ty: GLenum) -> bool {
+        ty == gl::BOOL
+    }
+
+    fn upload(location: GLint, values: &[Self]) {
+        let values: Vec<GLint> = values.iter().map(|&value| value as GLint).collect();
+        unsafe { gl::Uniform1iv(location, values.len() as GLsizei, values.as_ptr()) }
+    }
+}
+
+impl UniformValue for bool {
+    const TYPE_NAME: &'static str = "bool";
+
+    fn accepts(ty: GLenum) -> bool {
+        <Self as UniformElement>::accepts(ty)
+    }
+
+    fn count(&self) -> usize {
+        1
+    }
+
+    fn upload(&self, location: GLint) {
+        <Self as UniformElement>::upload(location, std::slice::from_ref(self))
+    }
+}
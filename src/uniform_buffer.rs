@@ -0,0 +1,192 @@
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use anyhow::bail;
+use gl::types::*;
+use nalgebra as na;
+
+use crate::{
+    impl_std140, Buffer, BufferType, BufferUsage, Camera, DirectionalLight, PointLight, Std140,
+};
+
+/// Name of the standard per-frame uniform block, declared in `assets/shaders/include/frame_uniforms.glsl`.
+pub const FRAME_UNIFORMS_BLOCK: &str = "FrameUniforms";
+
+/// Uniform buffer binding point reserved for `FrameUniforms` block.
+///
+/// Every `ShaderProgram` declaring `FrameUniforms` block is connected to it after linking, so the block is updated
+/// once per frame and shared by all programs.
+pub const FRAME_UNIFORMS_BINDING: GLuint = 0;
+
+/// Max number of lights in `FrameUniforms`, must equal `MAX_FRAME_LIGHTS` in GLSL.
+pub const MAX_FRAME_LIGHTS: usize = 8;
+
+static NEXT_UNIFORM_BINDING: AtomicU32 = AtomicU32::new(FRAME_UNIFORMS_BINDING + 1);
+
+/// Allocate an unused uniform buffer binding point, other than `FRAME_UNIFORMS_BINDING`.
+///
+/// Binding points are never freed, there are at least 36 of them since OpenGL 3.1.
+///
+/// wrap `glGetIntegerv` with `GL_MAX_UNIFORM_BUFFER_BINDINGS`
+pub fn alloc_uniform_binding() -> anyhow::Result<GLuint> {
+    let mut max_bindings = 0;
+    unsafe { gl::GetIntegerv(gl::MAX_UNIFORM_BUFFER_BINDINGS, &mut max_bindings) };
+
+    let binding = NEXT_UNIFORM_BINDING.fetch_add(1, Ordering::Relaxed);
+    if binding >= max_bindings as GLuint {
+        bail!("Uniform buffer binding points are used up, max: {max_bindings}");
+    }
+
+    Ok(binding)
+}
+
+/// Uniform Buffer Object holding a `T` in std140 layout, which is bound to a fixed binding point.
+///
+/// Connect uniform blocks of shader programs to the binding point by `ShaderProgram::bind_uniform_buffer()`.
+pub struct UniformBuffer<T: Std140> {
+    buffer: Buffer,
+    binding: GLuint,
+    size: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Std140> UniformBuffer<T> {
+    /// Create a uniform buffer initialized with `value`, and bind it to `binding` point.
+    pub fn new(binding: GLuint, value: &T) -> anyhow::Result<Self> {
+        let buffer = Buffer::new(BufferType::UniformBuffer)?;
+        let uniform_buffer = Self {
+            buffer,
+            binding,
+            // std140 size of a type never changes
+            size: value.to_std140_bytes().len(),
+            _marker: PhantomData,
+        };
+        uniform_buffer.update(value);
+
+        Ok(uniform_buffer)
+    }
+
+    /// Create a uniform buffer at a newly allocated binding point, see `alloc_uniform_binding()`.
+    pub fn create(value: &T) -> anyhow::Result<Self> {
+        Self::new(alloc_uniform_binding()?, value)
+    }
+
+    /// Upload `value` into buffer, and bind buffer to its binding point again.
+    ///
+    /// wrap `glBufferData`, `glBindBufferBase`
+    pub fn update(&self, value: &T) {
+        let bytes = value.to_std140_bytes();
+        self.buffer
+            .set_buffer_data(bytes.as_slice(), BufferUsage::DynamicDraw);
+        self.bind_base();
+    }
+
+    /// Bind buffer to its binding point.
+    ///
+    /// wrap `glBindBufferBase`
+    pub fn bind_base(&self) {
+        self.buffer.bind_base(self.binding);
+    }
+
+    pub fn get_binding(&self) -> GLuint {
+        self.binding
+    }
+
+    /// Size of buffer in bytes.
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get_buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+}
+
+/// A light in `FrameUniforms`, either directional or point light.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameLight {
+    /// `w` is 0 for directional light, whose `xyz` is direction; `w` is 1 for point light, whose `xyz` is position.
+    pub position: na::Vector4<f32>,
+    pub color: na::Vector3<f32>,
+    pub attenuation_linear: f32,
+    pub attenuation_quadratic: f32,
+}
+
+impl_std140!(FrameLight {
+    position,
+    color,
+    attenuation_linear,
+    attenuation_quadratic,
+});
+
+impl From<&DirectionalLight> for FrameLight {
+    fn from(light: &DirectionalLight) -> Self {
+        Self {
+            position: light.direction.push(0.0),
+            color: light.color,
+            attenuation_linear: 0.0,
+            attenuation_quadratic: 0.0,
+        }
+    }
+}
+
+impl From<&PointLight> for FrameLight {
+    fn from(light: &PointLight) -> Self {
+        Self {
+            position: light.position.push(1.0),
+            color: light.color,
+            attenuation_linear: light.attenuation_linear,
+            attenuation_quadratic: light.attenuation_quadratic,
+        }
+    }
+}
+
+/// Standard per-frame uniform block, which matches `FrameUniforms` block in
+/// `assets/shaders/include/frame_uniforms.glsl`.
+///
+/// Upload it by a `UniformBuffer` at `FRAME_UNIFORMS_BINDING` once per frame, then all programs read the same camera
+/// & lights, e.g. `frame.projection * frame.view * model * vec4(a_pos, 1.0)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameUniforms {
+    pub view: na::Matrix4<f32>,
+    pub projection: na::Matrix4<f32>,
+    pub camera_pos: na::Vector3<f32>,
+    /// Time in seconds, e.g. since application started.
+    pub time: f32,
+    pub light_count: i32,
+    pub lights: [FrameLight; MAX_FRAME_LIGHTS],
+}
+
+impl_std140!(FrameUniforms {
+    view,
+    projection,
+    camera_pos,
+    time,
+    light_count,
+    lights,
+});
+
+impl FrameUniforms {
+    /// Create per-frame uniforms from camera & projection matrix, without lights.
+    pub fn new(camera: &Camera, projection: na::Matrix4<f32>, time: f32) -> Self {
+        Self {
+            view: camera.get_lookat_matrix(),
+            projection,
+            camera_pos: camera.get_pos().coords,
+            time,
+            ..Default::default()
+        }
+    }
+
+    /// Append a light, return `false` if there are already `MAX_FRAME_LIGHTS` lights.
+    pub fn push_light(&mut self, light: impl Into<FrameLight>) -> bool {
+        let count = self.light_count as usize;
+        if count >= MAX_FRAME_LIGHTS {
+            return false;
+        }
+
+        self.lights[count] = light.into();
+        self.light_count += 1;
+        true
+    }
+}
@@ -1,3 +1,6 @@
+use std::ffi::CStr;
+
+use gl::types::*;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -40,6 +43,33 @@ pub enum OpenGlError {
     // TableTooLarge = gl::TABLE_TOO_LARGE as isize, // Part of the ARB_imaging extension.
 }
 
+/// Get version of current OpenGL context as `(major, minor)`.
+///
+/// wrap `glGetIntegerv` with `GL_MAJOR_VERSION` & `GL_MINOR_VERSION`
+pub fn get_gl_version() -> (i32, i32) {
+    let mut major = 0;
+    let mut minor = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+        gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    }
+    (major, minor)
+}
+
+/// Whether current OpenGL context supports extension `name`, e.g. `GL_EXT_texture_filter_anisotropic`.
+///
+/// wrap `glGetIntegerv` with `GL_NUM_EXTENSIONS`, `glGetStringi` with `GL_EXTENSIONS`
+pub fn has_gl_extension(name: &str) -> bool {
+    let mut count = 0;
+    unsafe { gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count) };
+
+    (0..count.max(0) as GLuint).any(|i| {
+        let extension = unsafe { gl::GetStringi(gl::EXTENSIONS, i) };
+        !extension.is_null()
+            && unsafe { CStr::from_ptr(extension.cast()) }.to_bytes() == name.as_bytes()
+    })
+}
+
 pub fn clear_gl_error() {
     while unsafe { gl::GetError() } != gl::NO_ERROR {}
 }
@@ -1,14 +1,136 @@
 use gl::types::*;
 use nalgebra as na;
 
-use crate::{get_gl_error, Buffer, BufferType};
+use crate::{get_gl_error, impl_vertex_layout, Buffer, BufferType};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct Vertex {
     pub position: na::Vector3<f32>,
     pub normal: na::Vector3<f32>,
     pub texture_coords: na::Vector2<f32>,
+    /// Direction of increasing `u` of texture coords on surface, used by normal mapping.
+    pub tangent: na::Vector3<f32>,
+    /// Direction of increasing `v` of texture coords on surface, which is `±normal x tangent`.
+    pub bitangent: na::Vector3<f32>,
+}
+
+impl_vertex_layout!(Vertex {
+    position,
+    normal,
+    texture_coords,
+    tangent,
+    bitangent
+});
+
+impl Vertex {
+    /// Create vertex with zero tangent & bitangent, which can be filled by `generate_tangents()` later.
+    pub fn new(
+        position: na::Vector3<f32>,
+        normal: na::Vector3<f32>,
+        texture_coords: na::Vector2<f32>,
+    ) -> Self {
+        Self {
+            position,
+            normal,
+            texture_coords,
+            tangent: na::Vector3::zeros(),
+            bitangent: na::Vector3::zeros(),
+        }
+    }
+}
+
+/// Vertex type whose attribute layout is generated from its fields, implement it by `impl_vertex_layout!`.
+pub trait VertexLayout {
+    /// Description of attributes at locations from 0, in field order.
+    fn vertex_description() -> VertexDescription;
+}
+
+/// Implement `VertexLayout` for a `#[repr(C)]` struct, whose fields are vertex attributes at locations from 0.
+///
+/// Offsets are computed by `offset_of!` and stride is size of the struct, so padding is handled. Element type & count
+/// come from field types, see `VertexAttributeType`. Integer fields are converted to float as is by default, mark
+/// them by `normalized` or `integer`:
+///
+/// ```ignore
+/// #[repr(C)]
+/// struct SkinnedVertex {
+///     position: na::Vector3<f32>,
+///     color: [u8; 4],
+///     bone_ids: [u8; 4],
+/// }
+/// impl_vertex_layout!(SkinnedVertex { position, color: normalized, bone_ids: integer });
+/// ```
+#[macro_export]
+macro_rules! impl_vertex_layout {
+    ($ty:ty { $($field:ident $(: $kind:ident)?),* $(,)? }) => {
+        impl $crate::VertexLayout for $ty {
+            fn vertex_description() -> $crate::VertexDescription {
+                let mut desc = $crate::VertexDescription::new();
+                $(
+                    let pointer = $crate::VertexAttributePointer::of_field(|vertex: &$ty| &vertex.$field)
+                        .with_offset(std::mem::offset_of!($ty, $field));
+                    desc.add_attribute_pointer($crate::impl_vertex_layout!(@kind pointer $($kind)?));
+                )*
+                desc.set_stride(std::mem::size_of::<$ty>() as _);
+                desc
+            }
+        }
+    };
+    (@kind $pointer:ident) => { $pointer };
+    (@kind $pointer:ident normalized) => { $pointer.with_normalized() };
+    (@kind $pointer:ident integer) => { $pointer.with_integer() };
+}
+
+/// Type of vertex attribute field: scalars, arrays `[T; N]` and nalgebra vectors/points/matrices of scalars.
+pub trait VertexAttributeType {
+    /// GL type of elements, e.g. `GL_FLOAT`.
+    const ELE_TYPE: GLenum;
+    /// Number of elements (of each column).
+    const COUNT: GLint;
+    /// Number of columns, e.g. 4 for `mat4`, each of which takes a location.
+    const COLUMNS: GLint = 1;
+}
+
+macro_rules! impl_vertex_attribute_scalar {
+    ($($ty:ty => $ele_type:expr),*) => {
+        $(
+            impl VertexAttributeType for $ty {
+                const ELE_TYPE: GLenum = $ele_type;
+                const COUNT: GLint = 1;
+            }
+        )*
+    };
+}
+
+impl_vertex_attribute_scalar!(
+    i8 => gl::BYTE,
+    u8 => gl::UNSIGNED_BYTE,
+    i16 => gl::SHORT,
+    u16 => gl::UNSIGNED_SHORT,
+    i32 => gl::INT,
+    u32 => gl::UNSIGNED_INT,
+    f32 => gl::FLOAT,
+    f64 => gl::DOUBLE
+);
+
+impl<T: VertexAttributeType, const N: usize> VertexAttributeType for [T; N] {
+    const ELE_TYPE: GLenum = T::ELE_TYPE;
+    const COUNT: GLint = N as GLint * T::COUNT;
+}
+
+/// Vectors are matrices of 1 column.
+impl<T: VertexAttributeType + na::Scalar, const R: usize, const C: usize> VertexAttributeType
+    for na::SMatrix<T, R, C>
+{
+    const ELE_TYPE: GLenum = T::ELE_TYPE;
+    const COUNT: GLint = R as GLint * T::COUNT;
+    const COLUMNS: GLint = C as GLint;
+}
+
+impl<T: VertexAttributeType + na::Scalar, const D: usize> VertexAttributeType for na::Point<T, D> {
+    const ELE_TYPE: GLenum = T::ELE_TYPE;
+    const COUNT: GLint = D as GLint * T::COUNT;
 }
 
 /// Wrapper of [Vertex Array Object](https://www.khronos.org/opengl/wiki/Vertex_Specification#Vertex_Array_Object)
@@ -49,28 +171,149 @@ impl VertexArray {
     }
 }
 
-/// TODO
+/// Description of a vertex attribute, i.e. parameters of `glVertexAttribPointer`.
+///
+/// Supported element types: `BYTE`, `UNSIGNED_BYTE`, `SHORT`, `UNSIGNED_SHORT`, `INT`, `UNSIGNED_INT`, `HALF_FLOAT`,
+/// `FLOAT`, `DOUBLE`, and packed `INT_2_10_10_10_REV` & `UNSIGNED_INT_2_10_10_10_REV` (whose `count` must be 4).
+///
+/// Integer types are converted to float in shader, as is or normalized into `[0, 1]` (`[-1, 1]` for signed) by
+/// `with_normalized()`; or kept as integer for `int`/`uint`/`ivecN`/`uvecN` inputs by `with_integer()`.
 pub struct VertexAttributePointer {
     pub ele_type: GLenum,
     pub count: GLint,
+    /// Number of columns of matrix attribute, which take consecutive locations. 1 for non-matrix attribute.
+    pub columns: GLint,
     pub should_normalized: GLboolean,
+    /// Read by `glVertexAttribIPointer` as integer.
+    pub is_integer: bool,
+    /// Attribute location, or next to location of previous attribute if `None`.
+    pub location: Option<GLuint>,
+    /// Attribute advances once per `divisor` instances, 0 for advancing per vertex.
+    pub divisor: GLuint,
+    /// Offset in vertex in bytes, or right after previous attribute if `None`.
+    pub offset: Option<usize>,
 }
 
 impl VertexAttributePointer {
+    /// Create float attribute of `count` elements of `ele_type`, which is not normalized.
+    pub fn new(ele_type: GLenum, count: GLint) -> Self {
+        Self {
+            ele_type,
+            count,
+            columns: 1,
+            should_normalized: gl::FALSE,
+            is_integer: false,
+            location: None,
+            divisor: 0,
+            offset: None,
+        }
+    }
+
+    /// Create float attribute matching type of a field, which is selected by `field` e.g. `|v: &Vertex| &v.normal`.
+    pub fn of_field<S, F: VertexAttributeType>(_field: fn(&S) -> &F) -> Self {
+        Self::new(F::ELE_TYPE, F::COUNT).with_columns(F::COLUMNS)
+    }
+
+    /// Make it a matrix attribute of `columns` column vectors, e.g. 4 for `mat4`.
+    pub fn with_columns(mut self, columns: GLint) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Normalize integer elements into `[0, 1]`, or `[-1, 1]` for signed types.
+    pub fn with_normalized(mut self) -> Self {
+        self.should_normalized = gl::TRUE;
+        self
+    }
+
+    /// Keep integer elements as integer, for `int`/`uint`/`ivecN`/`uvecN` inputs of shader.
+    pub fn with_integer(mut self) -> Self {
+        self.is_integer = true;
+        self
+    }
+
+    /// Bind to explicit attribute location, e.g. `layout (location = 3) in ...`.
+    pub fn with_location(mut self, location: GLuint) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Place attribute at `offset` bytes in vertex.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Advance attribute once per `divisor` instances of instanced drawing, instead of per vertex.
+    pub fn with_divisor(mut self, divisor: GLuint) -> Self {
+        self.divisor = divisor;
+        self
+    }
+
     pub(crate) fn get_type_size(&self) -> usize {
         match self.ele_type {
-            gl::FLOAT => std::mem::size_of::<f32>(),
-            _ => {
-                unimplemented!()
+            gl::BYTE | gl::UNSIGNED_BYTE => std::mem::size_of::<u8>(),
+            gl::SHORT | gl::UNSIGNED_SHORT | gl::HALF_FLOAT => std::mem::size_of::<u16>(),
+            gl::INT | gl::UNSIGNED_INT | gl::FLOAT => std::mem::size_of::<u32>(),
+            gl::DOUBLE => std::mem::size_of::<f64>(),
+            // All 4 elements are packed into 32 bits
+            gl::INT_2_10_10_10_REV | gl::UNSIGNED_INT_2_10_10_10_REV => std::mem::size_of::<u32>(),
+            // Rejected by `validate()` before attribute is added
+            ele_type => panic!("Unsupported vertex attribute type {ele_type:#x}"),
+        }
+    }
+
+    /// Size of a column in bytes.
+    pub(crate) fn get_column_size(&self) -> usize {
+        match self.ele_type {
+            gl::INT_2_10_10_10_REV | gl::UNSIGNED_INT_2_10_10_10_REV => self.get_type_size(),
+            _ => self.count as usize * self.get_type_size(),
+        }
+    }
+
+    /// Size of whole attribute in bytes.
+    pub(crate) fn get_size(&self) -> usize {
+        self.columns as usize * self.get_column_size()
+    }
+
+    /// Panic if combination of type, count and flags is invalid.
+    fn validate(&self) {
+        assert!(
+            (1..=4).contains(&self.count),
+            "Vertex attribute must have 1-4 elements"
+        );
+        assert!(
+            (1..=4).contains(&self.columns),
+            "Vertex attribute must have 1-4 columns"
+        );
+        match self.ele_type {
+            gl::INT_2_10_10_10_REV | gl::UNSIGNED_INT_2_10_10_10_REV => {
+                assert_eq!(
+                    self.count, 4,
+                    "Packed vertex attribute must have 4 elements"
+                );
+                assert!(!self.is_integer, "Packed vertex attribute can't be integer");
+            }
+            gl::HALF_FLOAT | gl::FLOAT | gl::DOUBLE => {
+                assert!(!self.is_integer, "Float vertex attribute can't be integer");
             }
+            gl::BYTE
+            | gl::UNSIGNED_BYTE
+            | gl::SHORT
+            | gl::UNSIGNED_SHORT
+            | gl::INT
+            | gl::UNSIGNED_INT => {}
+            ele_type => panic!("Unsupported vertex attribute type {ele_type:#x}"),
         }
     }
 }
 
-/// TODO
+/// Layout of interleaved vertex attributes in a Vertex Buffer Object.
 pub struct VertexDescription {
     pointers: Vec<VertexAttributePointer>,
     stride: GLsizei,
+    /// Location of first attribute without explicit location.
+    first_location: GLuint,
 }
 
 impl Default for VertexDescription {
@@ -84,32 +327,78 @@ impl VertexDescription {
         VertexDescription {
             pointers: Vec::new(),
             stride: 0,
+            first_location: 0,
         }
     }
 
+    /// Add float attribute, integer elements are converted to float without normalization.
     pub fn add_attribute(&mut self, ele_type: GLenum, count: GLint) {
-        match ele_type {
-            gl::FLOAT => {
-                let desc = VertexAttributePointer {
-                    ele_type,
-                    count,
-                    should_normalized: gl::FALSE,
-                };
-                self.stride += count * desc.get_type_size() as i32;
-                self.pointers.push(desc);
-            }
-            _ => {
-                unimplemented!()
-            }
+        self.add_attribute_pointer(VertexAttributePointer::new(ele_type, count));
+    }
+
+    /// Add float attribute whose integer elements are normalized, e.g. colors of `UNSIGNED_BYTE`.
+    pub fn add_normalized_attribute(&mut self, ele_type: GLenum, count: GLint) {
+        self.add_attribute_pointer(VertexAttributePointer::new(ele_type, count).with_normalized());
+    }
+
+    /// Add integer attribute, e.g. bone indices of `UNSIGNED_BYTE`.
+    pub fn add_integer_attribute(&mut self, ele_type: GLenum, count: GLint) {
+        self.add_attribute_pointer(VertexAttributePointer::new(ele_type, count).with_integer());
+    }
+
+    /// Add attribute of any kind, see `VertexAttributePointer`. It'll panic if attribute is invalid, e.g. unsupported
+    /// element type or element count out of 1-4.
+    ///
+    /// Stride grows to cover the attribute, see `set_stride()` for vertices with padding.
+    pub fn add_attribute_pointer(&mut self, mut pointer: VertexAttributePointer) {
+        pointer.validate();
+
+        let offset = match pointer.offset {
+            Some(offset) => offset,
+            None => self
+                .pointers
+                .last()
+                .map_or(0, |last| last.offset.unwrap() + last.get_size()),
+        };
+        pointer.offset = Some(offset);
+
+        self.stride = self.stride.max((offset + pointer.get_size()) as GLsizei);
+        self.pointers.push(pointer);
+    }
+
+    /// Override size of a vertex in bytes, e.g. size of vertex struct including padding at the end.
+    pub fn set_stride(&mut self, stride: GLsizei) {
+        self.stride = stride;
+    }
+
+    /// Start locations from `location` instead of 0, e.g. for per-instance attributes following vertex attributes.
+    pub fn set_first_location(&mut self, location: GLuint) {
+        self.first_location = location;
+    }
+
+    /// Set divisor of all attributes, e.g. 1 for per-instance attributes, see `VertexAttributePointer::with_divisor()`.
+    pub fn set_divisor(&mut self, divisor: GLuint) {
+        for pointer in self.pointers.iter_mut() {
+            pointer.divisor = divisor;
         }
     }
 
+    /// Number of locations taken by all attributes, e.g. 4 for a `mat4`.
+    pub fn get_location_count(&self) -> GLuint {
+        self.pointers.iter().map(|p| p.columns as GLuint).sum()
+    }
+
+    /// Size of a vertex in bytes.
+    pub fn get_stride(&self) -> GLsizei {
+        self.stride
+    }
+
     /// Set Vertex Attribute description for Vertex Buffer Object.
     /// This'll call `bind()` of VBO and VAO(if set) automatically.
     ///
     /// If type of this buffer is **not** `ARRAY_BUFFER`, it'll panic!
     ///
-    /// wrap `glEnableVertexAttribArray` & `glVertexAttribPointer`.
+    /// wrap `glEnableVertexAttribArray`, `glVertexAttribPointer`, `glVertexAttribIPointer` & `glVertexAttribDivisor`.
     pub fn bind_to(&mut self, vbo: &Buffer, vao_opt: Option<&VertexArray>) {
         assert_eq!(vbo.buffer_type, BufferType::VertexBuffer);
 
@@ -120,31 +409,174 @@ impl VertexDescription {
         vbo.bind();
 
         // Create & Enable attribute pointers
-        let mut offset = 0_u32;
-        for (index, element) in self.pointers.iter().enumerate() {
-            unsafe {
-                gl::VertexAttribPointer(
-                    // attribute index
-                    index as u32,
-                    // attribute element size
-                    element.count,
-                    // attribute element type
-                    element.ele_type,
-                    // coordinate should be normalized or not
-                    element.should_normalized,
-                    // attribute size
-                    self.stride,
-                    // We have to convert the pointer location using usize values and then cast to a const pointer
-                    // once we have our usize. We do not want to make a null pointer and then offset it with the `offset`
-                    // method. That's gonna generate an out of bounds pointer, which is UB. We could try to remember to use the
-                    // `wrapping_offset` method, or we could just do all the math in usize and then cast at the end.
-                    // I prefer the latter option.
-                    offset as *const _,
-                );
-                gl::EnableVertexAttribArray(index as u32);
+        let mut location = self.first_location;
+        for element in self.pointers.iter() {
+            if let Some(explicit_location) = element.location {
+                location = explicit_location;
             }
 
-            offset += element.count as u32 * element.get_type_size() as u32;
+            // Each column of matrix is an attribute of its own location
+            for column in 0..element.columns as usize {
+                // Resolved when added
+                let offset = element.offset.unwrap() + column * element.get_column_size();
+
+                unsafe {
+                    if element.is_integer {
+                        gl::VertexAttribIPointer(
+                            location,
+                            element.count,
+                            element.ele_type,
+                            self.stride,
+                            offset as *const _,
+                        );
+                    } else {
+                        gl::VertexAttribPointer(
+                            // attribute index
+                            location,
+                            // attribute element size
+                            element.count,
+                            // attribute element type
+                            element.ele_type,
+                            // coordinate should be normalized or not
+                            element.should_normalized,
+                            // attribute size
+                            self.stride,
+                            // We have to convert the pointer location using usize values and then cast to a const pointer
+                            // once we have our usize. We do not want to make a null pointer and then offset it with the `offset`
+                            // method. That's gonna generate an out of bounds pointer, which is UB. We could try to remember to use the
+                            // `wrapping_offset` method, or we could just do all the math in usize and then cast at the end.
+                            // I prefer the latter option.
+                            offset as *const _,
+                        );
+                    }
+                    gl::EnableVertexAttribArray(location);
+                    // Always set it, since VAO may be reused with another description.
+                    gl::VertexAttribDivisor(location, element.divisor);
+                }
+
+                location += 1;
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelInstance;
+
+    /// `(ele_type, count, columns, offset)` of each attribute.
+    fn layout(desc: &VertexDescription) -> Vec<(GLenum, GLint, GLint, usize)> {
+        desc.pointers
+            .iter()
+            .map(|p| (p.ele_type, p.count, p.columns, p.offset.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn vertex_layout() {
+        let desc = Vertex::vertex_description();
+        assert_eq!(
+            layout(&desc),
+            [
+                (gl::FLOAT, 3, 1, 0),
+                (gl::FLOAT, 3, 1, 12),
+                (gl::FLOAT, 2, 1, 24),
+                (gl::FLOAT, 3, 1, 32),
+                (gl::FLOAT, 3, 1, 44),
+            ]
+        );
+        assert_eq!(desc.get_stride(), 56);
+        assert_eq!(desc.get_stride() as usize, std::mem::size_of::<Vertex>());
+        assert_eq!(desc.get_location_count(), 5);
+        assert!(desc
+            .pointers
+            .iter()
+            .all(|p| p.should_normalized == gl::FALSE && !p.is_integer));
+    }
+
+    #[test]
+    fn model_instance_layout() {
+        // mat4 takes 4 locations of vec4 columns
+        let desc = ModelInstance::vertex_description();
+        assert_eq!(layout(&desc), [(gl::FLOAT, 4, 4, 0), (gl::FLOAT, 4, 1, 64)]);
+        let model = &desc.pointers[0];
+        assert_eq!(model.get_column_size(), 16);
+        assert_eq!(model.get_size(), 64);
+        assert_eq!(desc.get_stride(), 80);
+        assert_eq!(desc.get_location_count(), 5);
+    }
+
+    #[repr(C)]
+    struct PaddedVertex {
+        position: na::Vector3<f32>,
+        color: [u8; 4],
+        bone_ids: [u16; 3],
+        weight: f64,
+        flags: u8,
+    }
+
+    impl_vertex_layout!(PaddedVertex {
+        position,
+        color: normalized,
+        bone_ids: integer,
+        weight,
+        flags: integer,
+    });
+
+    #[test]
+    fn padded_vertex_layout() {
+        // `weight` is aligned to 8 after 2 bytes of padding, and stride includes padding at the end
+        let desc = PaddedVertex::vertex_description();
+        assert_eq!(
+            layout(&desc),
+            [
+                (gl::FLOAT, 3, 1, 0),
+                (gl::UNSIGNED_BYTE, 4, 1, 12),
+                (gl::UNSIGNED_SHORT, 3, 1, 16),
+                (gl::DOUBLE, 1, 1, 24),
+                (gl::UNSIGNED_BYTE, 1, 1, 32),
+            ]
+        );
+        assert_eq!(desc.get_stride(), 40);
+        assert_eq!(
+            desc.get_stride() as usize,
+            std::mem::size_of::<PaddedVertex>()
+        );
+
+        let flags: Vec<_> = desc
+            .pointers
+            .iter()
+            .map(|p| (p.should_normalized == gl::TRUE, p.is_integer))
+            .collect();
+        assert_eq!(
+            flags,
+            [
+                (false, false),
+                (true, false),
+                (false, true),
+                (false, false),
+                (false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn attributes_follow_previous_ones() {
+        // Attributes without offset are packed right after previous ones
+        let mut desc = VertexDescription::new();
+        desc.add_attribute(gl::FLOAT, 3);
+        desc.add_normalized_attribute(gl::UNSIGNED_BYTE, 4);
+        desc.add_attribute_pointer(VertexAttributePointer::new(gl::FLOAT, 4).with_columns(4));
+        assert_eq!(
+            layout(&desc),
+            [
+                (gl::FLOAT, 3, 1, 0),
+                (gl::UNSIGNED_BYTE, 4, 1, 12),
+                (gl::FLOAT, 4, 4, 16),
+            ]
+        );
+        assert_eq!(desc.get_stride(), 80);
+        assert_eq!(desc.get_location_count(), 6);
+    }
+}
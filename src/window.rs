@@ -0,0 +1,10 @@
+/// Common interface of render targets which can be presented, such as window or headless context.
+///
+/// Renderer of examples only depends on it, so that it's able to draw into both window and headless context.
+pub trait RenderWindow {
+    /// Get size of default framebuffer.
+    fn get_window_size(&self) -> (u32, u32);
+
+    /// Present rendered frame.
+    fn swap_buffers(&self) -> anyhow::Result<()>;
+}
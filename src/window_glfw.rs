@@ -2,7 +2,9 @@ use std::{ffi::CStr, sync::mpsc};
 
 use anyhow::bail;
 use glfw::Context;
-use tracing::{info, trace};
+use tracing::{error, info, trace};
+
+use crate::capture_screenshot;
 
 pub struct GlfwEventloop {
     glfw: glfw::Glfw,
@@ -21,6 +23,7 @@ impl GlfwEventloop {
 pub struct GlfwWindow {
     glfw: glfw::Glfw,
     inner_win: glfw::Window,
+    screenshot_requested: bool,
 }
 
 impl GlfwWindow {
@@ -44,12 +47,15 @@ impl GlfwWindow {
         {
             glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
         }
+        // sRGB default framebuffer, see `set_framebuffer_srgb()`
+        glfw.window_hint(glfw::WindowHint::SRgbCapable(true));
 
         // Make window
         let (inner_win, events) = glfw.create_window(width, height, title, mode).unwrap();
         let mut win = Self {
             glfw: glfw.clone(),
             inner_win,
+            screenshot_requested: false,
         };
 
         // Setup window
@@ -123,7 +129,17 @@ impl GlfwWindow {
     }
 
     /// Wrapper of `glfw::Window::swap_buffers()`
+    ///
+    /// If screenshot is requested by hotkey, back buffer will be captured before swapping.
     pub fn swap_buffers(&mut self) {
+        if self.screenshot_requested {
+            self.screenshot_requested = false;
+            let (width, height) = self.inner_win.get_framebuffer_size();
+            if let Err(e) = capture_screenshot(width as u32, height as u32) {
+                error!("Failed to capture screenshot: {}", e);
+            }
+        }
+
         self.inner_win.swap_buffers();
     }
 
@@ -137,7 +153,7 @@ impl GlfwWindow {
         self.glfw.poll_events();
     }
 
-    /// Default event handler
+    /// Default event handler: `Escape` to close window, `F12` to capture screenshot.
     pub fn handle_event_default(&mut self, event: &glfw::WindowEvent, _timestamp: f64) -> bool {
         match event {
             glfw::WindowEvent::Close => {
@@ -149,6 +165,10 @@ impl GlfwWindow {
                     self.inner_win.set_should_close(true);
                     return true;
                 }
+                if key == &glfw::Key::F12 && action == &glfw::Action::Press {
+                    self.screenshot_requested = true;
+                    return true;
+                }
             }
             glfw::WindowEvent::Size(w, h) => {
                 trace!("Resizing to ({}, {})", w, h);
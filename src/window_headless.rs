@@ -0,0 +1,180 @@
+// ref: https://github.com/rust-windowing/glutin/blob/v0.30.10/glutin/src/api/egl/device.rs
+
+use std::ffi::{CStr, CString};
+
+use anyhow::bail;
+use tracing::{info, warn};
+
+use glutin::api::egl;
+use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+use glutin::display::GetGlDisplay;
+use glutin::prelude::*;
+
+use crate::{ColorFormat, DepthStencilFormat, Framebuffer, RenderWindow};
+
+/// OpenGL context without any window, which renders into an offscreen `Framebuffer`.
+///
+/// It's created on an EGL device display and made current surfaceless, so it works without X11/Wayland, e.g. in CI
+/// with Mesa llvmpipe (`EGL_MESA_device_software`).
+pub struct HeadlessContext {
+    // Note: framebuffer must be dropped before the context.
+    framebuffer: Framebuffer,
+    #[allow(dead_code)]
+    gl_context: egl::context::PossiblyCurrentContext,
+}
+
+impl HeadlessContext {
+    /// Create headless OpenGL 3.3 core context, and an offscreen framebuffer with `RGBA8` color texture and
+    /// `Depth24Stencil8` renderbuffer as render target.
+    ///
+    /// EGL devices are tried in order until one of them is able to create the context.
+    pub fn new(width: u32, height: u32) -> anyhow::Result<Self> {
+        let devices = match egl::device::Device::query_devices() {
+            Ok(devices) => devices,
+            Err(e) => bail!("Failed to query EGL devices: {}", e),
+        };
+
+        let mut gl_context = None;
+        for device in devices {
+            match Self::create_gl_context(&device) {
+                Ok(context) => {
+                    info!(
+                        "Picked EGL device: name={:?}, vendor={:?}",
+                        device.name(),
+                        device.vendor()
+                    );
+                    gl_context = Some(context);
+                    break;
+                }
+                Err(e) => warn!("Skip EGL device {:?}: {}", device.name(), e),
+            }
+        }
+        let gl_context = match gl_context {
+            Some(gl_context) => gl_context,
+            None => bail!("Failed to create headless context: no usable EGL device"),
+        };
+
+        // Load the OpenGL function pointers
+        gl::load_with(|symbol| {
+            let symbol = CString::new(symbol).unwrap();
+            gl_context
+                .display()
+                .get_proc_address(symbol.as_c_str())
+                .cast()
+        });
+
+        // Print some GL info
+        unsafe {
+            let gl_vendor = CStr::from_ptr(gl::GetString(gl::VENDOR) as _)
+                .to_str()
+                .unwrap();
+            let gl_renderer = CStr::from_ptr(gl::GetString(gl::RENDERER) as _)
+                .to_str()
+                .unwrap();
+            let gl_version = CStr::from_ptr(gl::GetString(gl::VERSION) as _)
+                .to_str()
+                .unwrap();
+            let gl_shading_language_version =
+                CStr::from_ptr(gl::GetString(gl::SHADING_LANGUAGE_VERSION) as _)
+                    .to_str()
+                    .unwrap();
+
+            info!(
+                Vendor = gl_vendor,
+                Renderer = gl_renderer,
+                Version = gl_version,
+                SlVersion = gl_shading_language_version,
+                "Load OpenGL sucessfully!"
+            );
+        }
+
+        // Create offscreen render target, it's sRGB like default framebuffers of windows
+        let framebuffer = Framebuffer::builder(width, height)
+            .with_color_texture(ColorFormat::SRGB8Alpha8, gl::LINEAR)
+            .with_depth_renderbuffer(DepthStencilFormat::Depth24Stencil8)
+            .build()?;
+        // Offscreen framebuffer takes place of default framebuffer of window
+        Framebuffer::set_default_id(framebuffer.id);
+        framebuffer.bind_with_viewport();
+
+        info!("New headless context: width={}, height={}", width, height);
+
+        Ok(Self {
+            framebuffer,
+            gl_context,
+        })
+    }
+
+    fn create_gl_context(
+        device: &egl::device::Device,
+    ) -> anyhow::Result<egl::context::PossiblyCurrentContext> {
+        let display = unsafe { egl::display::Display::with_device(device, None) }?;
+
+        // There is no surface at all, so don't require any surface type.
+        let template = ConfigTemplateBuilder::new()
+            .with_alpha_size(8)
+            .with_surface_type(ConfigSurfaceTypes::empty())
+            .build();
+        let gl_config = match unsafe { display.find_configs(template) }?.next() {
+            Some(gl_config) => gl_config,
+            None => bail!("No suitable EGL config"),
+        };
+
+        // Create OpenGL context attributes (OpenGL 3.3 - Core)
+        let context_attributes = glutin::context::ContextAttributesBuilder::new()
+            .with_context_api(glutin::context::ContextApi::OpenGl(Some(
+                glutin::context::Version::new(3, 3),
+            )))
+            .with_profile(glutin::context::GlProfile::Core)
+            .build(None);
+
+        let not_current_gl_context =
+            unsafe { display.create_context(&gl_config, &context_attributes) }?;
+
+        Ok(not_current_gl_context.make_current_surfaceless()?)
+    }
+
+    /// Get offscreen framebuffer which is the render target of this context.
+    pub fn get_framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    /// Get size of offscreen framebuffer.
+    pub fn get_size(&self) -> (u32, u32) {
+        self.framebuffer.get_size()
+    }
+
+    /// Bind offscreen framebuffer and reset viewport to its size.
+    ///
+    /// Note: `Framebuffer::bind_default()` also binds offscreen framebuffer while this context is alive.
+    pub fn bind(&self) {
+        self.framebuffer.bind_with_viewport();
+    }
+
+    /// Block until all GL commands are complete. It takes place of `swap_buffers()` of windows.
+    ///
+    /// wrap `glFinish`
+    pub fn finish(&self) {
+        unsafe {
+            gl::Finish();
+        }
+    }
+}
+
+impl RenderWindow for HeadlessContext {
+    fn get_window_size(&self) -> (u32, u32) {
+        self.get_size()
+    }
+
+    fn swap_buffers(&self) -> anyhow::Result<()> {
+        self.finish();
+
+        Ok(())
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        Framebuffer::set_default_id(0);
+    }
+}
@@ -1,9 +1,10 @@
 // ref: https://github.com/rust-windowing/glutin/blob/8e0960d7aa8c67ee709897001def551fc1d868bb/glutin_examples/src/lib.rs
 
+use std::cell::Cell;
 use std::ffi::{CStr, CString};
 
 use anyhow::bail;
-use tracing::info;
+use tracing::{error, info};
 
 use glutin::display::GetGlDisplay;
 use glutin::prelude::*;
@@ -14,11 +15,14 @@ use winit::{
     event_loop::ControlFlow,
 };
 
+use crate::{capture_screenshot, RenderWindow};
+
 #[allow(dead_code)]
 pub struct WinitWindow {
     inner_window: winit::window::Window,
     gl_context: glutin::context::PossiblyCurrentContext,
     gl_surface: glutin::surface::Surface<glutin::surface::WindowSurface>,
+    screenshot_requested: Cell<bool>,
 }
 
 impl WinitWindow {
@@ -61,6 +65,7 @@ impl WinitWindow {
                 inner_window: window,
                 gl_context,
                 gl_surface,
+                screenshot_requested: Cell::new(false),
             },
             event_loop,
         ))
@@ -132,8 +137,10 @@ impl WinitWindow {
             .with_profile(glutin::context::GlProfile::Core)
             .build(Some(raw_window_handle));
 
-        // Create gl surface.
-        let surface_attrs = window.build_surface_attributes(<_>::default());
+        // Create gl surface, sRGB capable for `set_framebuffer_srgb()`.
+        let surface_attrs = window.build_surface_attributes(
+            glutin::surface::SurfaceAttributesBuilder::new().with_srgb(Some(true)),
+        );
         let gl_surface = unsafe {
             gl_config
                 .display()
@@ -214,13 +221,22 @@ impl WinitWindow {
     }
 
     /// Swap buffers of gl surface.
+    ///
+    /// If screenshot is requested by hotkey, back buffer will be captured before swapping.
     pub fn swap_buffers(&self) -> anyhow::Result<()> {
+        if self.screenshot_requested.replace(false) {
+            let (width, height) = self.get_window_size();
+            if let Err(e) = capture_screenshot(width, height) {
+                error!("Failed to capture screenshot: {}", e);
+            }
+        }
+
         self.gl_surface.swap_buffers(&self.gl_context)?;
 
         Ok(())
     }
 
-    /// Handle window events with some default processing logical.
+    /// Handle window events with some default processing logical: `Escape` to exit, `F12` to capture screenshot.
     pub fn handle_event_default(&self, event: &Event<()>, control_flow: &mut ControlFlow) -> bool {
         match event {
             // Emitted when all of the event loop’s input events have been processed and redraw processing
@@ -266,9 +282,33 @@ impl WinitWindow {
                     control_flow.set_exit();
                     true
                 }
+                // Capture screenshot when next frame is presented.
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::F12),
+                            ..
+                        },
+                    is_synthetic: false,
+                    ..
+                } => {
+                    self.screenshot_requested.set(true);
+                    true
+                }
                 _ => false,
             },
             _ => false,
         }
     }
 }
+
+impl RenderWindow for WinitWindow {
+    fn get_window_size(&self) -> (u32, u32) {
+        WinitWindow::get_window_size(self)
+    }
+
+    fn swap_buffers(&self) -> anyhow::Result<()> {
+        WinitWindow::swap_buffers(self)
+    }
+}
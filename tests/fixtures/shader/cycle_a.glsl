@@ -0,0 +1,2 @@
+// Includes cycle_b.glsl, which includes this file again
+#include "cycle_b.glsl"
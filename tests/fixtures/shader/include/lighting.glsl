@@ -0,0 +1,6 @@
+#pragma once
+#include "common.glsl"
+
+vec3 lighting(vec3 color) {
+    return color / PI;
+}
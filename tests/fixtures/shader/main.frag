@@ -0,0 +1,10 @@
+#version 330 core
+// Includes lighting.glsl, which includes common.glsl again
+#include "include/common.glsl"
+#include "include/lighting.glsl"
+
+out vec4 frag_color;
+
+void main() {
+    frag_color = vec4(lighting(vec3(1.0)), 1.0);
+}
@@ -0,0 +1,160 @@
+//! Golden-image regression tests of all examples.
+//!
+//! Each example is run in golden mode (see `learn_opengl_rs::GoldenRun`), which renders a fixed number of frames into
+//! a headless context. The captured frame is compared with reference image in `tests/golden/`.
+//!
+//! * Set `LEARN_GOLDEN_BLESS=1` to (re)generate reference images. Examples without reference image fail otherwise.
+//! * Examples whose assets or reference images are not in the repository are ignored, run them by
+//!   `cargo test -- --ignored` after adding their assets.
+//! * On failure, actual and diff images are written into `target/<profile>/golden/`.
+//! * Example binaries are built by `cargo test`, but not by `cargo test --test golden`.
+//! * Tests fail if headless context is unavailable (e.g. no EGL on this machine), set `LEARN_GOLDEN_SKIP=1` to skip
+//!   them instead.
+#![cfg(not(target_os = "macos"))]
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use learn_opengl_rs::{compare_images, HeadlessContext, GOLDEN_OUTPUT_ENV};
+
+/// Max per-channel RMSE between actual and reference image.
+const TOLERANCE: f32 = 0.02;
+const BLESS_ENV: &str = "LEARN_GOLDEN_BLESS";
+const SKIP_ENV: &str = "LEARN_GOLDEN_SKIP";
+
+/// Whether headless context can be created. Panics if it can't, unless skipping is allowed by `LEARN_GOLDEN_SKIP`.
+fn headless_available() -> bool {
+    static AVAILABLE: OnceLock<Result<(), String>> = OnceLock::new();
+    let available = AVAILABLE.get_or_init(|| {
+        HeadlessContext::new(1, 1)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    });
+    match available {
+        Ok(()) => true,
+        Err(e) if std::env::var_os(SKIP_ENV).is_some() => {
+            eprintln!("Headless context is unavailable, skip golden tests: {e}");
+            false
+        }
+        Err(e) => {
+            panic!("Headless context is unavailable: {e}, set {SKIP_ENV}=1 to skip golden tests")
+        }
+    }
+}
+
+/// `target/<profile>` directory, where example binaries are built into.
+fn target_dir() -> PathBuf {
+    let mut dir = std::env::current_exe().unwrap();
+    dir.pop(); // test binary
+    if dir.ends_with("deps") {
+        dir.pop();
+    }
+    dir
+}
+
+fn check_golden(example: &str) {
+    if !headless_available() {
+        return;
+    }
+
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let reference_path = manifest_dir
+        .join("tests/golden")
+        .join(format!("{example}.png"));
+    let output_dir = target_dir().join("golden");
+    let actual_path = output_dir.join(format!("{example}.png"));
+    let diff_path = output_dir.join(format!("{example}-diff.png"));
+
+    // Render example into image
+    let example_path = target_dir()
+        .join("examples")
+        .join(example)
+        .with_extension(std::env::consts::EXE_EXTENSION);
+    let output = Command::new(&example_path)
+        .current_dir(&manifest_dir) // examples load assets from relative path
+        .env(GOLDEN_OUTPUT_ENV, &actual_path)
+        .output()
+        .unwrap_or_else(|e| {
+            panic!(
+                "Failed to run {}: {e}, build examples first by `cargo test` or `cargo build --examples`",
+                example_path.display()
+            )
+        });
+    assert!(
+        output.status.success(),
+        "Example {example} failed: {}\n{}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    if std::env::var_os(BLESS_ENV).is_some() {
+        std::fs::copy(&actual_path, &reference_path).unwrap();
+        return;
+    }
+    assert!(
+        reference_path.exists(),
+        "No reference image {}, run with {BLESS_ENV}=1 to generate it",
+        reference_path.display()
+    );
+
+    // Compare with reference image
+    let actual = image::open(&actual_path).unwrap().to_rgba8();
+    let expected = image::open(&reference_path).unwrap().to_rgba8();
+    let diff = compare_images(&actual, &expected).unwrap();
+    if diff.max_rmse() > TOLERANCE {
+        diff.diff_image.save(&diff_path).unwrap();
+        panic!(
+            "Example {example} differs from reference image: rmse={:?}, tolerance={TOLERANCE}\n  actual: {}\n  diff: {}",
+            diff.rmse,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+macro_rules! golden_tests {
+    ($($(#[$attr:meta])* $test_name:ident: $example:literal,)*) => {
+        $(
+            #[test]
+            $(#[$attr])*
+            fn $test_name() {
+                check_golden($example);
+            }
+        )*
+    };
+}
+
+golden_tests! {
+    golden_001_draw_triangle: "001_draw_triangle",
+    golden_002_draw_quad: "002_draw_quad",
+    golden_003_texture: "003_texture",
+    golden_004_transform: "004_transform",
+    golden_005_depth_test: "005_depth_test",
+    golden_006_camera: "006_camera",
+    golden_007_simple_color: "007_simple_color",
+    golden_008_blinn_phong: "008_blinn_phong",
+    golden_009_material_map: "009_material_map",
+    golden_010_multi_lights: "010_multi_lights",
+    golden_011_model_loading: "011_model_loading",
+    golden_012_advanced_depth_test: "012_advanced_depth_test",
+    golden_013_advanced_stencil_test: "013_advanced_stencil_test",
+    golden_014_discard: "014_discard",
+    golden_015_blending: "015_blending",
+    golden_016_face_culling: "016_face_culling",
+    golden_017_framebuffer: "017_framebuffer",
+    golden_018_cubemap: "018_cubemap",
+    golden_019_shadow_mapping: "019_shadow_mapping",
+    golden_020_normal_map: "020_normal_map",
+    golden_021_parallax_map: "021_parallax_map",
+    golden_022_tone_mapping: "022_tone_mapping",
+    golden_023_bloom: "023_bloom",
+    #[ignore = "assets/references not in repo"]
+    golden_024_deferred_rendering: "024_deferred_rendering",
+    #[ignore = "assets/references not in repo"]
+    golden_025_ssao: "025_ssao",
+    #[ignore = "assets/references not in repo"]
+    golden_026_pbr: "026_pbr",
+    #[ignore = "assets/references not in repo"]
+    golden_027_ibl: "027_ibl",
+}
@@ -0,0 +1,181 @@
+//! Tests of binding material maps of meshes to shaders, which need a headless context like golden tests.
+//!
+//! * Tests fail if headless context is unavailable (e.g. no EGL on this machine), set `LEARN_GOLDEN_SKIP=1` to skip
+//!   them instead.
+#![cfg(not(target_os = "macos"))]
+
+use std::ffi::CString;
+use std::path::PathBuf;
+
+use gl::types::*;
+use learn_opengl_rs::{
+    HeadlessContext, MaterialMtl, MaterialPbr, Mesh, ShaderProgram, Texture, TextureOptions,
+    TextureType, TextureUnit, Vertex,
+};
+use nalgebra as na;
+
+const SKIP_ENV: &str = "LEARN_GOLDEN_SKIP";
+
+fn headless_context() -> Option<HeadlessContext> {
+    match HeadlessContext::new(4, 4) {
+        Ok(ctx) => Some(ctx),
+        Err(e) if std::env::var_os(SKIP_ENV).is_some() => {
+            eprintln!("Headless context is unavailable, skip material tests: {e}");
+            None
+        }
+        Err(e) => {
+            panic!("Headless context is unavailable: {e}, set {SKIP_ENV}=1 to skip material tests")
+        }
+    }
+}
+
+fn map(texture_type: TextureType) -> Texture {
+    let img = image::DynamicImage::ImageRgba8(image::RgbaImage::new(1, 1));
+    let path = PathBuf::from(format!("{texture_type:?}"));
+    Texture::create_from_image(&img, path, Some(texture_type), &TextureOptions::default()).unwrap()
+}
+
+/// Triangle whose MTL & PBR materials have every map set.
+fn mesh_with_all_maps() -> Mesh {
+    let vertices = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]].map(|[x, y]| {
+        Vertex::new(
+            na::Vector3::new(x, y, 0.0),
+            na::Vector3::z(),
+            na::Vector2::new(x, y),
+        )
+    });
+    let mtl_material = MaterialMtl {
+        ambient_map: Some(map(TextureType::BlinnAmbient)),
+        diffuse_map: Some(map(TextureType::BlinnDiffuse)),
+        specular_map: Some(map(TextureType::BlinnSpecular)),
+        normal_map: Some(map(TextureType::Normal)),
+        shininess_map: Some(map(TextureType::BlinnShininess)),
+        alpha_map: Some(map(TextureType::Alpha)),
+        ..Default::default()
+    };
+    let pbr_material = MaterialPbr {
+        albedo_map: Some(map(TextureType::PbrAlbedo)),
+        metallic_map: Some(map(TextureType::PbrMetallic)),
+        roughness_map: Some(map(TextureType::PbrRoughness)),
+        ao_map: Some(map(TextureType::PbrAO)),
+        ..Default::default()
+    };
+
+    Mesh::new(vertices.to_vec(), vec![0, 1, 2], None, None, None, None)
+        .unwrap()
+        .with_mtl_material(mtl_material)
+        .unwrap()
+        .with_pbr_material(pbr_material)
+        .unwrap()
+}
+
+fn get_uniform_i32(shader: &ShaderProgram, name: &str) -> GLint {
+    let location = shader.get_uniform_location(&CString::new(name).unwrap());
+    assert!(location >= 0, "{name} is not active");
+    let mut value = 0;
+    unsafe { gl::GetUniformiv(shader.id, location, &mut value) };
+    value
+}
+
+fn bound_texture(unit: GLint) -> GLuint {
+    let mut id = 0;
+    unsafe {
+        gl::ActiveTexture(gl::TEXTURE0 + unit as GLenum);
+        gl::GetIntegerv(gl::TEXTURE_BINDING_2D, &mut id);
+    }
+    id as GLuint
+}
+
+#[test]
+fn maps_sampled_by_shader_are_bound() {
+    let Some(_ctx) = headless_context() else {
+        return;
+    };
+    let shader = ShaderProgram::create_from_file(
+        "assets/shaders/model_loading/011-object.vert",
+        "assets/shaders/model_loading/011-object.frag",
+    )
+    .unwrap();
+    let mesh = mesh_with_all_maps();
+    mesh.draw(&shader, "material").unwrap();
+
+    let material = mesh.mtl_material.as_ref().unwrap();
+    let maps = [
+        ("ambient_map", &material.ambient_map),
+        ("diffuse_map", &material.diffuse_map),
+        ("specular_map", &material.specular_map),
+        ("shininess_map", &material.shininess_map),
+        ("alpha_map", &material.alpha_map),
+    ];
+    let mut units = Vec::new();
+    for (map_name, map) in maps {
+        assert_eq!(
+            get_uniform_i32(&shader, &format!("material.has_{map_name}")),
+            1
+        );
+        let unit = get_uniform_i32(&shader, &format!("material.{map_name}"));
+        assert!(
+            (10..=15).contains(&unit),
+            "{map_name} is bound to unit {unit}"
+        );
+        assert_eq!(bound_texture(unit), map.as_ref().unwrap().id, "{map_name}");
+        units.push(unit);
+    }
+    units.sort();
+    units.dedup();
+    assert_eq!(units.len(), maps.len(), "maps share texture units");
+
+    // Normal map & PBR maps aren't sampled by shader, so they don't take texture units
+    assert_eq!(units, [10, 11, 12, 13, 14]);
+    assert_eq!(bound_texture(15), 0);
+}
+
+#[test]
+fn too_many_sampled_maps_is_error() {
+    let Some(_ctx) = headless_context() else {
+        return;
+    };
+    let vert = r#"
+        #version 330 core
+        layout (location = 0) in vec3 a_pos;
+        void main() { gl_Position = vec4(a_pos, 1.0); }
+    "#;
+    let frag = r#"
+        #version 330 core
+        struct Material {
+            sampler2D diffuse_map, specular_map, normal_map, ambient_map, shininess_map, alpha_map;
+            sampler2D albedo_map, metallic_map, roughness_map, ao_map;
+        };
+        uniform Material material;
+        out vec4 frag_color;
+        void main() {
+            vec2 uv = vec2(0.5);
+            frag_color = texture(material.diffuse_map, uv) + texture(material.specular_map, uv)
+                + texture(material.normal_map, uv) + texture(material.ambient_map, uv)
+                + texture(material.shininess_map, uv) + texture(material.alpha_map, uv)
+                + texture(material.albedo_map, uv) + texture(material.metallic_map, uv)
+                + texture(material.roughness_map, uv) + texture(material.ao_map, uv);
+        }
+    "#;
+    let shader = ShaderProgram::create_from_source(vert, frag).unwrap();
+    let mesh = mesh_with_all_maps();
+
+    let error = mesh.draw(&shader, "material").unwrap_err();
+    assert!(
+        error.to_string().contains("No texture unit left"),
+        "{error:#}"
+    );
+}
+
+#[test]
+fn texture_unit_increase_stops_at_last_unit() {
+    assert_eq!(
+        TextureUnit::TEXTURE0.increase(),
+        Some(TextureUnit::TEXTURE1)
+    );
+    assert_eq!(
+        TextureUnit::TEXTURE14.increase(),
+        Some(TextureUnit::TEXTURE15)
+    );
+    assert_eq!(TextureUnit::TEXTURE15.increase(), None);
+}